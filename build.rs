@@ -0,0 +1,55 @@
+//! Generates one `#[test]` per fixture under `tests/source/`, so adding a
+//! new source test is as simple as dropping a `.rs` file in that directory.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=tests/source");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
+    let dest = Path::new(&out_dir).join("source_tests.rs");
+    let source_dir = Path::new("tests/source");
+
+    let mut generated = String::new();
+
+    if source_dir.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(source_dir)
+            .expect("couldn't read tests/source")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            if path.extension().map(|ext| ext == "rs").unwrap_or(false) {
+                let stem = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .expect("fixture file name is not valid UTF-8");
+                let test_name = stem.replace(['-', '.'], "_");
+
+                generated.push_str(&format!(
+                    "#[test]\nfn source_{name}() {{ run_source_test(std::path::Path::new({path:?})); }}\n\n",
+                    name = test_name,
+                    path = path,
+                ));
+            } else if path.is_dir() && path.join("Cargo.toml").is_file() {
+                let name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .expect("fixture directory name is not valid UTF-8");
+                let test_name = name.replace(['-', '.'], "_");
+
+                generated.push_str(&format!(
+                    "#[test]\n#[ignore = \"requires a nightly rustc old enough to support `-Z save-analysis`\"]\nfn source_dir_{name}() {{ run_source_crate_test(std::path::Path::new({path:?})); }}\n\n",
+                    name = test_name,
+                    path = path,
+                ));
+            }
+        }
+    }
+
+    fs::write(dest, generated).expect("couldn't write generated source tests");
+}
@@ -0,0 +1,33 @@
+//! A tiny crate documented purely so `rustdoc` has something realistic to
+//! run against during development.
+
+/// A widget with a name and a size.
+///
+/// ```rust
+/// let widget = example::Widget::new("gadget", 3);
+/// assert_eq!(widget.name(), "gadget");
+/// ```
+pub struct Widget {
+    name: String,
+    size: u32,
+}
+
+impl Widget {
+    /// Create a new widget.
+    pub fn new(name: &str, size: u32) -> Widget {
+        Widget {
+            name: name.to_string(),
+            size,
+        }
+    }
+
+    /// The widget's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The widget's size.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
@@ -0,0 +1,13 @@
+//! Fuzzes `rustdoc::examples::find_examples`, the line-based scanner behind
+//! an item's `examples` attribute (see [`rustdoc::json`]) and the doctest
+//! blocks `rustdoc::test::find_tests` compiles and runs. It should never
+//! panic on any input, well-formed or not.
+//!
+//! Run with `cargo fuzz run find_examples` from this directory.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|docs: &str| {
+    let _ = rustdoc::examples::find_examples(docs);
+});
@@ -0,0 +1,116 @@
+//! A thin wrapper around `rls-analysis`, the library that reads the
+//! save-analysis data cargo/rustc emit and lets us walk a crate's items.
+
+use std::path::Path;
+use std::time::Instant;
+
+use rls_analysis::{AResult, AnalysisHost, Def, Id, Target};
+
+use crate::analysis_stats::AnalysisStats;
+use crate::error::*;
+
+/// The loaded save-analysis data for a crate, ready to be queried.
+pub struct Analysis {
+    pub host: AnalysisHost,
+    stats: AnalysisStats,
+}
+
+impl Analysis {
+    /// Load analysis data written by cargo into `base_dir`, with source
+    /// paths rewritten relative to `path_prefix`.
+    ///
+    /// `host.reload` happily succeeds even when `base_dir` contains no
+    /// analysis data at all (e.g. a build script failed before `rustc` ever
+    /// ran, or this toolchain silently ignores `-Z save-analysis`); left
+    /// alone, that surfaces later as a confusing "crate not found" from
+    /// `create_documentation`. Checking for at least one save-analysis JSON
+    /// file right here lets us report the directory we searched instead.
+    pub fn generate(path_prefix: &Path, base_dir: &Path) -> Result<Analysis> {
+        let host = AnalysisHost::new(Target::Debug);
+
+        host.reload(path_prefix, base_dir)
+            .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+        if count_analysis_files(base_dir) == 0 {
+            return Err(ErrorKind::EmptyAnalysis(base_dir.to_path_buf()).into());
+        }
+
+        Ok(Analysis { host, stats: AnalysisStats::default() })
+    }
+
+    /// Like [`AnalysisHost::get_def`], timing the call into
+    /// [`AnalysisStats::record_get_def`] so `--debug-analysis-stats` has
+    /// real numbers to report.
+    pub fn get_def(&self, id: Id) -> AResult<Def> {
+        let started = Instant::now();
+        let result = self.host.get_def(id);
+        self.stats.record_get_def(started.elapsed());
+        result
+    }
+
+    /// Like [`AnalysisHost::for_each_child_def`], timing the call into
+    /// [`AnalysisStats::record_for_each_child_def`] so
+    /// `--debug-analysis-stats` has real numbers to report.
+    pub fn for_each_child_def<F, T>(&self, id: Id, f: F) -> AResult<Vec<T>>
+    where
+        F: FnMut(Id, &Def) -> T,
+    {
+        let started = Instant::now();
+        let result = self.host.for_each_child_def(id, f);
+        self.stats.record_for_each_child_def(started.elapsed());
+        result
+    }
+
+    /// The query counts and durations recorded so far. See
+    /// [`crate::analysis_stats`].
+    pub fn stats(&self) -> &AnalysisStats {
+        &self.stats
+    }
+}
+
+/// Count the `.json` files under `dir`, recursively (cargo nests
+/// save-analysis output under a per-profile, per-crate directory structure,
+/// so we can't just look in `dir` itself).
+fn count_analysis_files(dir: &Path) -> usize {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let mut count = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            count += count_analysis_files(&path);
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_missing_directory_has_no_analysis_files() {
+        assert_eq!(count_analysis_files(Path::new("/nonexistent/path")), 0);
+    }
+
+    #[test]
+    fn counts_json_files_nested_under_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("debug/save-analysis")).unwrap();
+        std::fs::write(dir.path().join("debug/save-analysis/my_crate.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("debug/save-analysis/notes.txt"), "not analysis data").unwrap();
+
+        assert_eq!(count_analysis_files(dir.path()), 1);
+    }
+
+    #[test]
+    fn an_empty_directory_has_no_analysis_files() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(count_analysis_files(dir.path()), 0);
+    }
+}
@@ -0,0 +1,88 @@
+//! Dumping the raw analysis def tree — id, kind, qualname, parent, span —
+//! as JSON, before [`crate::json::create_documentation`] filters it down to
+//! the kinds it knows how to emit and reshapes what's left into
+//! [`crate::json::Data`]. Meant for diagnosing a "why isn't my item
+//! showing up" report and for developing new [`crate::json`] generation
+//! features, where seeing exactly what analysis found before this crate's
+//! own filtering ran is the whole point.
+//!
+//! Walked the way [`crate::json::create_documentation`]'s own `walk`
+//! recurses through modules (into `DefKind::Mod` children), but without
+//! that walk's extra pass over each struct/enum/union's impl blocks, and
+//! with no filtering by kind: a local, or anything else [`crate::json`]
+//! drops, still shows up here.
+
+use rls_analysis::{Def, DefKind, Id};
+use serde_derive::Serialize;
+
+use crate::analysis::Analysis;
+use crate::error::*;
+use crate::json::{def_id, normalize_qualname};
+
+/// One def analysis found, independent of whether [`crate::json`] would
+/// ever emit it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefDebug {
+    /// The same opaque id [`crate::json::Data::id`] would use, if this def
+    /// is ever emitted.
+    pub id: String,
+    /// `rls_analysis::DefKind`'s `Debug` output, e.g. `"Local"` or
+    /// `"Field"` — kinds [`crate::json::create_documentation`] doesn't have
+    /// a JSON-API type for at all.
+    pub kind: String,
+    pub qualname: String,
+    /// The enclosing def's id, if analysis recorded one.
+    pub parent: Option<String>,
+    pub file: String,
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+/// Dump every def analysis found under `crate_name`'s root.
+pub fn dump(analysis: &Analysis, crate_name: &str) -> Result<Vec<DefDebug>> {
+    let roots = analysis
+        .host
+        .def_roots()
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+    let (crate_id, _) = roots
+        .into_iter()
+        .find(|(_, name)| name == crate_name)
+        .ok_or_else(|| ErrorKind::CrateErr(crate_name.to_string()))?;
+
+    let crate_def = analysis
+        .get_def(crate_id)
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    let mut entries = vec![to_debug(crate_id, &crate_def)];
+    walk(analysis, crate_id, &mut entries)?;
+
+    Ok(entries)
+}
+
+fn walk(analysis: &Analysis, id: Id, entries: &mut Vec<DefDebug>) -> Result<()> {
+    let children = analysis
+        .for_each_child_def(id, |child_id, def| (child_id, def.clone()))
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    for (child_id, def) in children {
+        entries.push(to_debug(child_id, &def));
+        if def.kind == DefKind::Mod {
+            walk(analysis, child_id, entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn to_debug(id: Id, def: &Def) -> DefDebug {
+    DefDebug {
+        id: def_id(id),
+        kind: format!("{:?}", def.kind),
+        qualname: normalize_qualname(&def.qualname),
+        parent: def.parent.map(def_id),
+        file: def.span.file.to_string_lossy().to_string(),
+        line_start: def.span.range.row_start.0 as usize,
+        line_end: def.span.range.row_end.0 as usize,
+    }
+}
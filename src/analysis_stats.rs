@@ -0,0 +1,106 @@
+//! Counting and timing this crate's calls into `rls_analysis`
+//! (`AnalysisHost::get_def` and `AnalysisHost::for_each_child_def`, the two
+//! queries [`crate::json::create_documentation`] and
+//! [`crate::analysis_debug::dump`] make over and over while walking a
+//! crate), behind the hidden `--debug-analysis-stats` flag.
+//!
+//! Reported at the end of generation (see [`crate::build`]) so real numbers
+//! on a large crate can guide the caching/parallelization work these
+//! queries will eventually need, instead of guessing at where the time
+//! goes.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+/// How many times, and how long in total, one query kind was called.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryStats {
+    pub calls: usize,
+    pub duration: Duration,
+}
+
+/// Running counts for every `rls_analysis` query this crate instruments.
+///
+/// Cheap enough (a couple of `Cell`s) to update unconditionally on every
+/// call rather than gating the bookkeeping itself on the flag; only
+/// [`AnalysisStats::summary`] is skipped when `--debug-analysis-stats`
+/// isn't set.
+#[derive(Debug, Default)]
+pub struct AnalysisStats {
+    get_def: Cell<QueryStats>,
+    for_each_child_def: Cell<QueryStats>,
+}
+
+impl AnalysisStats {
+    /// Record one `AnalysisHost::get_def` call that took `duration`.
+    pub fn record_get_def(&self, duration: Duration) {
+        record(&self.get_def, duration);
+    }
+
+    /// Record one `AnalysisHost::for_each_child_def` call that took
+    /// `duration`.
+    pub fn record_for_each_child_def(&self, duration: Duration) {
+        record(&self.for_each_child_def, duration);
+    }
+
+    pub fn get_def(&self) -> QueryStats {
+        self.get_def.get()
+    }
+
+    pub fn for_each_child_def(&self) -> QueryStats {
+        self.for_each_child_def.get()
+    }
+
+    /// Render the counts collected so far as the lines `rustdoc build
+    /// --debug-analysis-stats` prints at the end of generation.
+    pub fn summary(&self) -> String {
+        let get_def = self.get_def();
+        let for_each_child_def = self.for_each_child_def();
+        format!(
+            "analysis query stats:\n  get_def: {} call(s), {:?}\n  for_each_child_def: {} call(s), {:?}",
+            get_def.calls, get_def.duration, for_each_child_def.calls, for_each_child_def.duration,
+        )
+    }
+}
+
+fn record(stats: &Cell<QueryStats>, duration: Duration) {
+    let mut current = stats.get();
+    current.calls += 1;
+    current.duration += duration;
+    stats.set(current);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_no_calls_recorded() {
+        let stats = AnalysisStats::default();
+        assert_eq!(stats.get_def().calls, 0);
+        assert_eq!(stats.for_each_child_def().calls, 0);
+    }
+
+    #[test]
+    fn accumulates_calls_and_durations_separately_per_query() {
+        let stats = AnalysisStats::default();
+        stats.record_get_def(Duration::from_millis(10));
+        stats.record_get_def(Duration::from_millis(20));
+        stats.record_for_each_child_def(Duration::from_millis(5));
+
+        assert_eq!(stats.get_def().calls, 2);
+        assert_eq!(stats.get_def().duration, Duration::from_millis(30));
+        assert_eq!(stats.for_each_child_def().calls, 1);
+        assert_eq!(stats.for_each_child_def().duration, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn summary_mentions_both_query_kinds() {
+        let stats = AnalysisStats::default();
+        stats.record_get_def(Duration::from_millis(1));
+
+        let summary = stats.summary();
+        assert!(summary.contains("get_def: 1 call(s)"));
+        assert!(summary.contains("for_each_child_def: 0 call(s)"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Accounting for every file a build writes, so a wrapper (a cargo plugin,
+//! a CI uploader) can know exactly what was produced without globbing the
+//! output directory itself.
+//!
+//! This rides on [`crate::observer::BuildObserver`]'s existing
+//! `on_artifact_written` hook: [`ArtifactCollector`] just records every path
+//! it's told about, then [`build_with_artifacts`] and
+//! [`build_for_targets_with_artifacts`] stat and hash each one afterwards.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::*;
+use crate::json::Documentation;
+use crate::observer::BuildObserver;
+
+/// What role an artifact plays in a build's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// A crate's rendered documentation, e.g. `data.json`.
+    Json,
+    /// A side file: a split-out `docs` overflow, an embedded source
+    /// snippet, `timings.json`, or the `examples.json` index.
+    Asset,
+    /// A compiled doc test binary.
+    TestBinary,
+}
+
+/// A single file a build wrote, with enough to tell whether it changed
+/// since a previous build.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub kind: ArtifactKind,
+    pub size: u64,
+    /// A fingerprint of the file's contents, from `std`'s unspecified (but
+    /// deterministic within a single build) `DefaultHasher`. This is meant
+    /// to let a caller tell whether an artifact changed between two builds
+    /// run with the same toolchain, not to guard against tampering — use a
+    /// real cryptographic hash for that.
+    pub hash: u64,
+}
+
+/// Every artifact a build wrote.
+#[derive(Debug, Clone, Default)]
+pub struct Artifacts {
+    pub files: Vec<Artifact>,
+}
+
+/// A [`BuildObserver`] that records every artifact [`postprocess`][crate::build]
+/// writes, so it can be turned into an [`Artifacts`] report once the build
+/// finishes.
+///
+/// Everything [`on_artifact_written`][BuildObserver::on_artifact_written]
+/// reports is classified as [`ArtifactKind::Asset`]; use [`Self::record`] to
+/// add an entry with a different kind, e.g. the `data.json` a caller writes
+/// after `build()` returns, or a doc test binary from
+/// [`crate::test::compile_tests`].
+#[derive(Default)]
+pub struct ArtifactCollector {
+    recorded: RefCell<Vec<(PathBuf, ArtifactKind)>>,
+}
+
+impl ArtifactCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `path` as an artifact of the given `kind`.
+    pub fn record(&self, path: &Path, kind: ArtifactKind) {
+        self.recorded.borrow_mut().push((path.to_path_buf(), kind));
+    }
+
+    /// Stat and hash every recorded path, producing the final report.
+    ///
+    /// A path that no longer exists (removed between being written and
+    /// this call) is left out rather than failing the whole report.
+    pub fn into_artifacts(self) -> Result<Artifacts> {
+        let mut files = Vec::new();
+
+        for (path, kind) in self.recorded.into_inner() {
+            let contents = match fs::read(&path) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+
+            files.push(Artifact {
+                size: contents.len() as u64,
+                hash: hash_bytes(&contents),
+                kind,
+                path,
+            });
+        }
+
+        Ok(Artifacts { files })
+    }
+}
+
+impl BuildObserver for ArtifactCollector {
+    fn on_artifact_written(&self, path: &Path) {
+        self.record(path, ArtifactKind::Asset);
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Like [`crate::build`], but also returns an [`Artifacts`] report of every
+/// side file the build wrote (split docs, embedded source, `timings.json`,
+/// `examples.json`).
+///
+/// This doesn't include `data.json` itself: writing the documentation out
+/// (and deciding where) is left to the caller, same as [`crate::build`].
+/// Record it with [`ArtifactCollector::record`] before calling
+/// [`ArtifactCollector::into_artifacts`] directly if you need it in the same
+/// report.
+pub fn build_with_artifacts(config: &Config) -> Result<(Documentation, Artifacts)> {
+    let collector = ArtifactCollector::new();
+    let documentation = crate::build_with_observer(config, &collector)?;
+    Ok((documentation, collector.into_artifacts()?))
+}
+
+/// Like [`crate::build_for_targets`], but also returns an [`Artifacts`]
+/// report; see [`build_with_artifacts`] for what it does and doesn't cover.
+pub fn build_for_targets_with_artifacts(config: &Config, targets: &[String]) -> Result<(Documentation, Artifacts)> {
+    let collector = ArtifactCollector::new();
+    let documentation = crate::build_for_targets_with_observer(config, targets, &collector)?;
+    Ok((documentation, collector.into_artifacts()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_size_and_a_stable_hash_for_a_written_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timings.json");
+        fs::write(&path, b"hello").unwrap();
+
+        let collector = ArtifactCollector::new();
+        collector.on_artifact_written(&path);
+        let artifacts = collector.into_artifacts().unwrap();
+
+        assert_eq!(artifacts.files.len(), 1);
+        assert_eq!(artifacts.files[0].kind, ArtifactKind::Asset);
+        assert_eq!(artifacts.files[0].size, 5);
+        assert_eq!(artifacts.files[0].hash, hash_bytes(b"hello"));
+    }
+
+    #[test]
+    fn record_can_override_the_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        fs::write(&path, b"{}").unwrap();
+
+        let collector = ArtifactCollector::new();
+        collector.record(&path, ArtifactKind::Json);
+        let artifacts = collector.into_artifacts().unwrap();
+
+        assert_eq!(artifacts.files[0].kind, ArtifactKind::Json);
+    }
+
+    #[test]
+    fn a_path_removed_before_reporting_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gone.json");
+
+        let collector = ArtifactCollector::new();
+        collector.record(&path, ArtifactKind::Asset);
+        let artifacts = collector.into_artifacts().unwrap();
+
+        assert!(artifacts.files.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+//! Marking items with `badges`: short labels like "Internal" or
+//! "Experimental" a frontend can render next to an item's name, driven by a
+//! configurable mapping from a doc marker to a label.
+//!
+//! Only a marker in an item's own docs is supported for now: a leading line
+//! matching one of the configured markers exactly (e.g. `**Internal**` on a
+//! line by itself) adds that marker's label. Deriving a badge from an
+//! attribute instead (`#[doc(hidden)]`, `#[deprecated]`) isn't possible
+//! yet: `rls_analysis::Def` (0.18.3) doesn't expose attribute data at all
+//! (the same gap noted on [`crate::json::parse_doc_cfg`] and the
+//! `deprecated` attribute in [`crate::json`]), so there's no attribute to
+//! match against.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+use crate::error::*;
+use crate::json::Documentation;
+
+/// Doc markers recognized even without a `badges.toml`: `**Internal**` and
+/// `**Experimental**` as a leading line, mapped to a same-named badge.
+fn default_markers() -> HashMap<String, String> {
+    vec![("**Internal**", "Internal"), ("**Experimental**", "Experimental")]
+        .into_iter()
+        .map(|(marker, label)| (marker.to_string(), label.to_string()))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct BadgesFile {
+    #[serde(default)]
+    markers: HashMap<String, String>,
+}
+
+/// Load `badges.toml` from `crate_root`, merging its `[markers]` table over
+/// [`default_markers`] (a crate-supplied marker overrides the default of
+/// the same text). A missing file just means the defaults apply.
+pub fn load_badge_markers(crate_root: &Path) -> Result<HashMap<String, String>> {
+    let mut markers = default_markers();
+
+    let path = crate_root.join("badges.toml");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(markers),
+    };
+
+    let file: BadgesFile =
+        toml::from_str(&contents).chain_err(|| format!("failed to parse '{}'", path.display()))?;
+    markers.extend(file.markers);
+
+    Ok(markers)
+}
+
+/// The badge labels that apply to `docs`: every configured marker whose
+/// text is `docs`'s first non-empty line.
+fn badges_for_docs(docs: &str, markers: &HashMap<String, String>) -> Vec<String> {
+    let leading_line = docs.lines().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+
+    markers
+        .iter()
+        .filter(|(marker, _)| marker.as_str() == leading_line)
+        .map(|(_, label)| label.clone())
+        .collect()
+}
+
+/// Add a `badges` attribute to every item in `documentation` (the crate
+/// itself and everything in `included`), from its `docs` attribute and
+/// `markers`.
+pub fn apply_badges(documentation: &mut Documentation, markers: &HashMap<String, String>) {
+    let items = std::iter::once(&mut documentation.data).chain(documentation.included.iter_mut());
+
+    for data in items {
+        let docs = data
+            .attributes
+            .get("docs")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let badges = badges_for_docs(&docs, markers);
+        data.attributes.insert(
+            "badges".to_string(),
+            Value::Array(badges.into_iter().map(Value::String).collect()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+
+    fn data_with_docs(docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("docs".to_string(), Value::String(docs.to_string()));
+        Data {
+            id: "item".to_string(),
+            ty: "struct".to_string(),
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn a_leading_marker_line_adds_its_badge() {
+        let markers = default_markers();
+        assert_eq!(
+            badges_for_docs("**Internal**\n\nDon't use this outside the crate.", &markers),
+            vec!["Internal".to_string()]
+        );
+    }
+
+    #[test]
+    fn a_marker_that_is_not_on_the_leading_line_is_ignored() {
+        let markers = default_markers();
+        assert!(badges_for_docs("A widget.\n\n**Internal**", &markers).is_empty());
+    }
+
+    #[test]
+    fn docs_without_a_marker_have_no_badges() {
+        let markers = default_markers();
+        assert!(badges_for_docs("A widget.", &markers).is_empty());
+    }
+
+    #[test]
+    fn apply_badges_sets_the_attribute_on_every_item() {
+        let mut documentation = Documentation {
+            data: data_with_docs("**Internal**\n"),
+            included: vec![data_with_docs("A widget.")],
+            ..Default::default()
+        };
+
+        apply_badges(&mut documentation, &default_markers());
+
+        assert_eq!(
+            documentation.data.attributes.get("badges").unwrap(),
+            &Value::Array(vec![Value::String("Internal".to_string())])
+        );
+        assert_eq!(
+            documentation.included[0].attributes.get("badges").unwrap(),
+            &Value::Array(vec![])
+        );
+    }
+
+    #[test]
+    fn loads_a_crate_specific_marker_from_badges_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("badges.toml"),
+            "[markers]\n\"**Beta**\" = \"Beta\"\n",
+        )
+        .unwrap();
+
+        let markers = load_badge_markers(dir.path()).unwrap();
+        assert_eq!(markers.get("**Beta**").unwrap(), "Beta");
+        assert_eq!(markers.get("**Internal**").unwrap(), "Internal");
+    }
+
+    #[test]
+    fn missing_badges_toml_leaves_only_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_badge_markers(dir.path()).unwrap(), default_markers());
+    }
+}
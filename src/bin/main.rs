@@ -0,0 +1,995 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Instant;
+
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(name = "rustdoc", about = "An experimental replacement for rustdoc")]
+// `Build` has by far the most flags of any subcommand; boxing them to
+// shrink `Opt` would only make `structopt`'s generated destructuring
+// clumsier for no runtime benefit (this enum is parsed once per process).
+#[allow(clippy::large_enum_variant)]
+enum Opt {
+    /// Generate documentation for a crate.
+    Build {
+        /// May be passed more than once to document several crates in one
+        /// run, e.g. for a monorepo that isn't a Cargo workspace. Each
+        /// crate is built in sequence into its own subdirectory of the
+        /// shared output directory, alongside a combined `index.json`.
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: Vec<PathBuf>,
+
+        /// Truncate any item's docs longer than this many bytes, writing the
+        /// full text to a side file instead.
+        #[structopt(long = "max-docs-size")]
+        max_docs_size: Option<usize>,
+
+        /// Embed each item's source snippet into the generated documentation.
+        #[structopt(long = "include-source")]
+        include_source: bool,
+
+        /// Cap the number of documented items at this many, keeping modules
+        /// first, so a pathological crate produces usable partial docs
+        /// instead of running for minutes and writing a `data.json` too big
+        /// to load. A `truncated` entry is added to `meta` when this kicks
+        /// in.
+        #[structopt(long = "max-items")]
+        max_items: Option<usize>,
+
+        /// Record the wall-clock duration of each build phase into
+        /// `timings.json`.
+        #[structopt(long = "timings")]
+        timings: bool,
+
+        /// Compute per-item word count, doc-example count, and a
+        /// readability score into `metrics.json`.
+        #[structopt(long = "metrics")]
+        metrics: bool,
+
+        /// Format to emit: `json`, `yaml`, or `msgpack`.
+        #[structopt(long = "emit", default_value = "json")]
+        emit: String,
+
+        /// Where to write the emitted documentation: a path, or `-` for
+        /// stdout. Defaults to `data.json` under the crate's `target/doc`.
+        /// Unless writing to stdout, every other side-artifact this build
+        /// writes (`--timings`, `--metrics`, `--module-graph`,
+        /// `--coverage-badge`, etc.) is also rooted at this path's parent
+        /// directory instead of the default `target/doc`.
+        #[structopt(short = "o", long = "output")]
+        output: Option<String>,
+
+        /// Suppress progress output. Implied by `--output -`.
+        #[structopt(long = "quiet")]
+        quiet: bool,
+
+        /// Cross-check for this target triple in addition to the host,
+        /// merging the results. May be passed more than once; items only
+        /// present on some targets are annotated with a `platforms`
+        /// attribute.
+        #[structopt(long = "target")]
+        target: Vec<String>,
+
+        /// Base URL to root every item's `links.self` at, e.g.
+        /// `https://docs.example.com/my_crate/`. Also adds a `linkTemplates`
+        /// entry to `meta` for each resource type.
+        #[structopt(long = "base-url")]
+        base_url: Option<String>,
+
+        /// Echo every line of `cargo check`'s stderr while generating
+        /// analysis, instead of just its own progress output.
+        #[structopt(long = "show-cargo-output")]
+        show_cargo_output: bool,
+
+        /// Pass this `KEY=VALUE` environment variable through to `cargo
+        /// check` while generating analysis. May be passed more than once;
+        /// useful for a build script that needs something set that this
+        /// process wasn't launched with.
+        #[structopt(long = "check-only-env", parse(try_from_str = parse_env_var))]
+        check_env: Vec<(String, String)>,
+
+        /// Write a local usage report (which options ran, how long it took,
+        /// how many items were documented) to this path. Never uploaded
+        /// anywhere; see `rustdoc::report`.
+        #[structopt(long = "report")]
+        report: Option<PathBuf>,
+
+        /// Forward `--offline` to every `cargo` invocation this makes, for a
+        /// crate whose dependencies are all vendored or otherwise already
+        /// available without reaching the network.
+        #[structopt(long = "offline")]
+        offline: bool,
+
+        /// Forward `--locked` to every `cargo` invocation this makes,
+        /// failing instead of silently updating `Cargo.lock` when it's out
+        /// of step with `Cargo.toml`, so a documentation build in a release
+        /// pipeline is reproducible against the committed lockfile.
+        #[structopt(long = "locked")]
+        locked: bool,
+
+        /// Whether to color the terminal spinner and every `cargo`/`rustc`
+        /// invocation this makes: `always`, `auto` (the default, color only
+        /// when stderr is a terminal), or `never`.
+        #[structopt(long = "color", default_value = "auto")]
+        color: String,
+
+        /// Parse the crate's entry file with `syn` and report any item it
+        /// finds that analysis didn't, to `completeness.json`.
+        #[structopt(long = "check-completeness")]
+        check_completeness: bool,
+
+        /// Render the crate's module hierarchy and item containment to
+        /// `modules.dot` (graphviz) and `modules.json`.
+        #[structopt(long = "module-graph")]
+        module_graph: bool,
+
+        /// Flag doc examples that still reference a crate item by a path
+        /// that doesn't match any current item, to `stale-examples.json`.
+        #[structopt(long = "check-stale-examples")]
+        check_stale_examples: bool,
+
+        /// Compile a probe binary to record each non-generic
+        /// struct/enum/union's size and alignment in `layout.json`. `repr`
+        /// attributes are captured either way.
+        #[structopt(long = "layout")]
+        layout: bool,
+
+        /// Only document the module at this path under the crate root, e.g.
+        /// `submodule::inner`, instead of the whole crate. Useful for
+        /// previewing one area of a large crate faster than a full build.
+        #[structopt(long = "root")]
+        root: Option<String>,
+
+        /// Leave this item path, and everything nested under it, out of
+        /// generated documentation even though it's `pub`. May be passed
+        /// more than once; useful for staging an API that isn't ready to
+        /// document yet. An item can also exclude itself from its own doc
+        /// comment with an `<!-- rustdoc:skip -->` marker.
+        #[structopt(long = "exclude")]
+        exclude: Vec<String>,
+
+        /// Maximum length, in characters, of every item's `plainSummary`
+        /// attribute, for a frontend's meta description and social card
+        /// text.
+        #[structopt(long = "summary-length", default_value = "160")]
+        summary_length: usize,
+
+        /// Block until another build's lock on the output directory is
+        /// released, instead of failing immediately.
+        #[structopt(long = "wait")]
+        wait: bool,
+
+        /// Don't lock the output directory at all, e.g. for a directory
+        /// shared with a tool that doesn't participate in this locking.
+        #[structopt(long = "no-lock")]
+        no_lock: bool,
+
+        /// After a successful build, print a concise summary (items
+        /// documented per kind, doc example count, output size, elapsed
+        /// time), resembling cargo's own "Finished" line. Sourced from the
+        /// same numbers written to `meta.stats`.
+        #[structopt(long = "stats")]
+        stats: bool,
+
+        /// Stamp `meta.license` (the crate's license expression from
+        /// `Cargo.toml` plus a generation timestamp) and, with
+        /// `--include-source`, the footer of every generated HTML source
+        /// page, for organizations that require this on published
+        /// documentation.
+        #[structopt(long = "stamp-license")]
+        stamp_license: bool,
+
+        /// Load save-analysis data from this directory instead of running
+        /// `cargo check` to generate it, for a pipeline where an earlier CI
+        /// stage (or another build system) already produced it with `-Z
+        /// save-analysis` enabled. Makes the documentation step cacheable
+        /// and cheap when analysis is already on hand.
+        #[structopt(long = "analysis-dir", parse(from_os_str))]
+        analysis_dir: Option<PathBuf>,
+
+        /// Warn about doc comments on items that won't appear anywhere in
+        /// the generated documentation, because analysis walking doesn't
+        /// emit their kind (e.g. a local, or a constant).
+        #[structopt(long = "verbose")]
+        verbose: bool,
+
+        /// Pipe the generated documentation through this shell command
+        /// before writing it out, replacing it with whatever JSON the
+        /// command prints to stdout. Lets an organization inject custom
+        /// attributes, strip internal modules, or rewrite links without
+        /// forking this crate.
+        #[structopt(long = "post-process")]
+        post_process: Option<String>,
+
+        /// Render a `docs NN%` SVG badge to `coverage-badge.svg`, from the
+        /// same per-item doc-comment check `meta.empty` uses, for embedding
+        /// in a README from a CI artifact.
+        #[structopt(long = "coverage-badge")]
+        coverage_badge: bool,
+
+        /// Warn (or, with `--deny-budget`, fail the build) when
+        /// `data.json` serializes to more than this many bytes.
+        #[structopt(long = "max-data-json-size")]
+        max_data_json_size: Option<usize>,
+
+        /// Warn (or, with `--deny-budget`, fail the build) when any single
+        /// item serializes to more than this many bytes.
+        #[structopt(long = "max-document-size")]
+        max_document_size: Option<usize>,
+
+        /// Warn (or, with `--deny-budget`, fail the build) when more than
+        /// this many items end up in `included`.
+        #[structopt(long = "max-included-count")]
+        max_included_count: Option<usize>,
+
+        /// Fail the build instead of only warning when `--max-data-json-size`,
+        /// `--max-document-size`, or `--max-included-count` is exceeded.
+        #[structopt(long = "deny-budget")]
+        deny_budget: bool,
+
+        /// Dump the raw analysis def tree (id, kind, qualname, parent,
+        /// span) to `analysis-debug.json`, before it's filtered and
+        /// reshaped into the generated documentation. Useful for
+        /// diagnosing a "why isn't my item showing up" report.
+        #[structopt(long = "analysis-debug")]
+        analysis_debug: bool,
+
+        /// Print how many times, and how long in total, this build spent in
+        /// the `rls_analysis` queries generation makes repeatedly while
+        /// walking the crate. A hidden developer flag for guiding future
+        /// caching/parallelization work, not something an end user needs.
+        #[structopt(long = "debug-analysis-stats", hidden = true)]
+        debug_analysis_stats: bool,
+
+        /// Send the generated documentation to this installed frontend
+        /// (`cargo-doc-frontend-<name>` on `PATH`) after writing it,
+        /// refusing with an upgrade message if the frontend declares an
+        /// incompatible data format version. See `rustdoc frontend list`.
+        #[structopt(long = "frontend")]
+        frontend: Option<String>,
+    },
+    /// Run a crate's doc tests.
+    Test {
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: PathBuf,
+
+        /// Don't run doc tests belonging to deprecated items.
+        #[structopt(long = "skip-deprecated-doctests")]
+        skip_deprecated_doctests: bool,
+
+        /// Re-run a failing doc test up to this many more times before
+        /// reporting it as failed.
+        #[structopt(long = "retries", default_value = "0")]
+        retries: u32,
+
+        /// Write a local usage report (which options ran, how long it took,
+        /// how many examples ran) to this path. Never uploaded anywhere;
+        /// see `rustdoc::report`.
+        #[structopt(long = "report")]
+        report: Option<PathBuf>,
+
+        /// Forward `--offline` to every `cargo` invocation this makes, for a
+        /// crate whose dependencies are all vendored or otherwise already
+        /// available without reaching the network.
+        #[structopt(long = "offline")]
+        offline: bool,
+
+        /// Forward `--locked` to every `cargo` invocation this makes,
+        /// failing instead of silently updating `Cargo.lock` when it's out
+        /// of step with `Cargo.toml`.
+        #[structopt(long = "locked")]
+        locked: bool,
+
+        /// Whether to color the terminal spinner and every `cargo`/`rustc`
+        /// invocation this makes: `always`, `auto` (the default, color only
+        /// when stderr is a terminal), or `never`.
+        #[structopt(long = "color", default_value = "auto")]
+        color: String,
+
+        /// How to compile and run doc examples: `rustc` (default, compiles
+        /// each example directly) or `cargo` (generates a throwaway Cargo
+        /// package depending on the crate by path, and runs `cargo test` in
+        /// it, for correct dependency resolution and edition handling).
+        #[structopt(long = "doctest-backend", default_value = "rustc")]
+        doctest_backend: String,
+
+        /// Where to write generated doc test sources and binaries. Defaults
+        /// to `target/rustdoc-test/<crate>` (see
+        /// `rustdoc::test::default_tests_dir`), kept separate from the
+        /// published docs output.
+        #[structopt(long = "tests-dir", parse(from_os_str))]
+        tests_dir: Option<PathBuf>,
+
+        /// Print where generated doc test sources and binaries are being
+        /// written.
+        #[structopt(long = "verbose")]
+        verbose: bool,
+    },
+    /// Print one item's docs from a crate's already-generated documentation.
+    Explain {
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: PathBuf,
+
+        /// The item to explain, by qualname (`my_crate::module::Thing`) or
+        /// bare name (`Thing`).
+        item: String,
+
+        /// Format to print the item in: `json`, `md`, or `text`.
+        #[structopt(long = "format", default_value = "text")]
+        format: String,
+
+        /// Format the generated documentation was written in, so this knows
+        /// which file under the crate's output directory to load.
+        #[structopt(long = "emit", default_value = "json")]
+        emit: String,
+    },
+    /// Check a crate's already-generated documentation for internal
+    /// consistency (mismatched relationship types, dangling ids), the same
+    /// check a debug build already runs right after generating it.
+    Check {
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: PathBuf,
+
+        /// Format the generated documentation was written in, so this knows
+        /// which file under the crate's output directory to load.
+        #[structopt(long = "emit", default_value = "json")]
+        emit: String,
+    },
+    /// Open a crate's generated documentation in a browser.
+    Open {
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: PathBuf,
+
+        /// Open with this command instead of the platform default, e.g.
+        /// `firefox` or `xdg-open`.
+        #[structopt(long = "browser")]
+        browser: Option<String>,
+
+        /// Don't open anything; just print the resolved `index.html` path.
+        #[structopt(long = "print-path")]
+        print_path: bool,
+    },
+    /// Build and doc-test a generated sample crate, to check that this
+    /// installation works end to end.
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Selftest,
+    /// Serve a crate's already-generated documentation over HTTP as a
+    /// JSON:API (requires the `api-server` feature).
+    #[cfg(feature = "api-server")]
+    Serve {
+        #[structopt(long = "manifest-path", parse(from_os_str), default_value = "Cargo.toml")]
+        manifest_path: PathBuf,
+
+        /// Format the generated documentation was written in, so this knows
+        /// which file under the crate's output directory to load.
+        #[structopt(long = "emit", default_value = "json")]
+        emit: String,
+
+        /// Address to listen on.
+        #[structopt(long = "addr", default_value = "127.0.0.1:8000")]
+        addr: String,
+    },
+    /// One-command dev loop for contributors: document and doc-test the
+    /// bundled `example/` crate, then optionally open the result.
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Dev {
+        /// Document and test the bundled `example/` crate. Currently the
+        /// only supported mode; required so the command reads the way it
+        /// will once other dev targets exist.
+        #[structopt(long = "example")]
+        example: bool,
+
+        /// Open the generated docs in a browser afterward.
+        #[structopt(long = "open")]
+        open: bool,
+    },
+    /// Discover and inspect installed frontend plugins.
+    Frontend {
+        #[structopt(subcommand)]
+        command: FrontendCmd,
+    },
+}
+
+#[derive(StructOpt)]
+enum FrontendCmd {
+    /// List every `cargo-doc-frontend-*` binary found on `PATH`, along with
+    /// the data format version each one declares support for.
+    List,
+}
+
+/// Parse a `KEY=VALUE` environment variable, as accepted by `--check-only-env`.
+fn parse_env_var(input: &str) -> Result<(String, String), String> {
+    input
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected `KEY=VALUE`, got '{}'", input))
+}
+
+fn main() {
+    env_logger::init();
+
+    let opt = Opt::from_args();
+
+    let result = match opt {
+        Opt::Build {
+            manifest_path,
+            max_docs_size,
+            include_source,
+            max_items,
+            timings,
+            metrics,
+            emit,
+            output,
+            quiet,
+            target,
+            base_url,
+            show_cargo_output,
+            check_env,
+            report,
+            offline,
+            locked,
+            color,
+            check_completeness,
+            module_graph,
+            check_stale_examples,
+            layout,
+            root,
+            exclude,
+            summary_length,
+            wait,
+            no_lock,
+            stats,
+            stamp_license,
+            analysis_dir,
+            verbose,
+            post_process,
+            coverage_badge,
+            max_data_json_size,
+            max_document_size,
+            max_included_count,
+            deny_budget,
+            analysis_debug,
+            debug_analysis_stats,
+            frontend,
+        } => run_build(BuildOptions {
+            manifest_path,
+            max_docs_size,
+            include_source,
+            max_items,
+            timings,
+            metrics,
+            emit,
+            output,
+            quiet,
+            target,
+            base_url,
+            show_cargo_output,
+            check_env,
+            report,
+            offline,
+            locked,
+            color,
+            check_completeness,
+            module_graph,
+            check_stale_examples,
+            layout,
+            root,
+            exclude,
+            summary_length,
+            wait,
+            no_lock,
+            stats,
+            stamp_license,
+            analysis_dir,
+            verbose,
+            post_process,
+            coverage_badge,
+            max_data_json_size,
+            max_document_size,
+            max_included_count,
+            deny_budget,
+            analysis_debug,
+            debug_analysis_stats,
+            frontend,
+        }),
+        Opt::Test {
+            manifest_path,
+            skip_deprecated_doctests,
+            retries,
+            report,
+            offline,
+            locked,
+            color,
+            doctest_backend,
+            tests_dir,
+            verbose,
+        } => run_test(
+            manifest_path,
+            skip_deprecated_doctests,
+            retries,
+            report,
+            offline,
+            locked,
+            color,
+            doctest_backend,
+            tests_dir,
+            verbose,
+        ),
+        Opt::Explain {
+            manifest_path,
+            item,
+            format,
+            emit,
+        } => run_explain(manifest_path, &item, &format, &emit),
+        Opt::Check { manifest_path, emit } => run_check(manifest_path, &emit),
+        Opt::Open {
+            manifest_path,
+            browser,
+            print_path,
+        } => run_open(manifest_path, browser, print_path),
+        Opt::Selftest => run_selftest(),
+        #[cfg(feature = "api-server")]
+        Opt::Serve { manifest_path, emit, addr } => run_serve(manifest_path, &emit, &addr),
+        Opt::Dev { example, open } => run_dev(example, open),
+        Opt::Frontend { command } => run_frontend(command),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        exit(exit_code_for(&e));
+    }
+}
+
+/// The process exit code for a failed run, so CI scripts can branch on the
+/// cause without parsing stderr.
+///
+/// Only two causes get a dedicated code: `2` for cargo/analysis failures
+/// and `4` for a failing doc test. A failure in a frontend plugin (see
+/// [`rustdoc::frontend`]), a `--deny-budget` violation, and everything else
+/// exit with the generic `1`; no cause maps to `3` or `5`.
+fn exit_code_for(error: &rustdoc::Error) -> i32 {
+    match error.kind() {
+        rustdoc::ErrorKind::Cargo(_) | rustdoc::ErrorKind::Analysis(_) | rustdoc::ErrorKind::EmptyAnalysis(_) | rustdoc::ErrorKind::LockfileDrift(_) => 2,
+        rustdoc::ErrorKind::DocTest(_) => 4,
+        _ => 1,
+    }
+}
+
+/// Parsed `rustdoc build` arguments, grouped so `run_build` doesn't have to
+/// take each one as a separate parameter.
+struct BuildOptions {
+    manifest_path: Vec<PathBuf>,
+    max_docs_size: Option<usize>,
+    include_source: bool,
+    max_items: Option<usize>,
+    timings: bool,
+    metrics: bool,
+    emit: String,
+    output: Option<String>,
+    quiet: bool,
+    target: Vec<String>,
+    base_url: Option<String>,
+    show_cargo_output: bool,
+    check_env: Vec<(String, String)>,
+    report: Option<PathBuf>,
+    offline: bool,
+    locked: bool,
+    color: String,
+    check_completeness: bool,
+    module_graph: bool,
+    check_stale_examples: bool,
+    layout: bool,
+    root: Option<String>,
+    exclude: Vec<String>,
+    summary_length: usize,
+    wait: bool,
+    no_lock: bool,
+    stats: bool,
+    stamp_license: bool,
+    analysis_dir: Option<PathBuf>,
+    verbose: bool,
+    post_process: Option<String>,
+    coverage_badge: bool,
+    max_data_json_size: Option<usize>,
+    max_document_size: Option<usize>,
+    max_included_count: Option<usize>,
+    deny_budget: bool,
+    analysis_debug: bool,
+    debug_analysis_stats: bool,
+    frontend: Option<String>,
+}
+
+/// Build a [`rustdoc::Config`] for one crate out of the options shared by
+/// every crate in a `rustdoc build` invocation (batched or not).
+fn configure_build(manifest_path: PathBuf, options: &BuildOptions) -> rustdoc::Result<rustdoc::Config> {
+    let mut config = rustdoc::Config::new(manifest_path)?;
+    config.max_docs_size = options.max_docs_size;
+    config.include_source = options.include_source;
+    config.max_items = options.max_items;
+    config.timings = options.timings;
+    config.metrics = options.metrics;
+    config.quiet = options.quiet;
+    config.base_url = options.base_url.clone();
+    config.show_cargo_output = options.show_cargo_output;
+    config.check_env = options.check_env.clone();
+    config.offline = options.offline;
+    config.locked = options.locked;
+    config.color = rustdoc::color::ColorChoice::parse(&options.color)?;
+    config.check_completeness = options.check_completeness;
+    config.module_graph = options.module_graph;
+    config.check_stale_examples = options.check_stale_examples;
+    config.layout = options.layout;
+    config.root = options.root.clone();
+    config.exclude = options.exclude.clone();
+    config.summary_length = options.summary_length;
+    config.stamp_license = options.stamp_license;
+    config.analysis_dir = options.analysis_dir.clone();
+    config.verbose = options.verbose;
+    config.post_process = options.post_process.clone();
+    config.coverage_badge = options.coverage_badge;
+    config.budget = rustdoc::budget::Budget {
+        max_data_json_size: options.max_data_json_size,
+        max_document_size: options.max_document_size,
+        max_included_count: options.max_included_count,
+        deny: options.deny_budget,
+    };
+    config.analysis_debug = options.analysis_debug;
+    config.debug_analysis_stats = options.debug_analysis_stats;
+    config.lock_policy = match (options.no_lock, options.wait) {
+        (true, _) => rustdoc::lock::LockPolicy::Skip,
+        (false, true) => rustdoc::lock::LockPolicy::Wait,
+        (false, false) => rustdoc::lock::LockPolicy::Fail,
+    };
+    Ok(config)
+}
+
+fn run_build(mut options: BuildOptions) -> rustdoc::Result<()> {
+    if options.manifest_path.len() > 1 {
+        return run_build_batch(options);
+    }
+
+    let started = Instant::now();
+    let emit = rustdoc::format::EmitFormat::parse(&options.emit)?;
+
+    let to_stdout = options.output.as_deref() == Some("-");
+
+    let mut usage_options = HashMap::new();
+    usage_options.insert("emit".to_string(), options.emit.clone());
+    usage_options.insert("includeSource".to_string(), options.include_source.to_string());
+    usage_options.insert("timings".to_string(), options.timings.to_string());
+    usage_options.insert("metrics".to_string(), options.metrics.to_string());
+    usage_options.insert("targetCount".to_string(), options.target.len().to_string());
+    usage_options.insert("offline".to_string(), options.offline.to_string());
+    usage_options.insert("locked".to_string(), options.locked.to_string());
+
+    let manifest_path = options.manifest_path.remove(0);
+    let mut config = configure_build(manifest_path, &options)?;
+    config.quiet = options.quiet || to_stdout;
+    // A non-stdout `--output` redirects every side-artifact this build
+    // writes (timings, metrics, the module graph, the coverage badge, the
+    // output-directory lock, ...) to its parent directory, not just the
+    // primary emitted document, so a build pointed at a custom output path
+    // doesn't leave the rest of its artifacts behind in the default
+    // `target/doc`.
+    if let Some(path) = &options.output {
+        if path != "-" {
+            let output_dir = PathBuf::from(path).parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+            config.set_output_path(output_dir);
+        }
+    }
+    let documentation = rustdoc::build_for_targets(&config, &options.target)?;
+
+    let bytes = rustdoc::format::serialize(&documentation, emit)?;
+
+    if to_stdout {
+        std::io::stdout().write_all(&bytes)?;
+    } else if let Some(path) = options.output {
+        let path = PathBuf::from(path);
+        report_change_since_last_build(&path, emit, &documentation, config.quiet);
+        std::fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+        rustdoc::write::write_if_changed(&path, &bytes)?;
+    } else {
+        let path = config.output_path().join(emit.default_file_name());
+        report_change_since_last_build(&path, emit, &documentation, config.quiet);
+        std::fs::create_dir_all(config.output_path())?;
+        rustdoc::write::write_if_changed(&path, &bytes)?;
+    }
+
+    if let Some(name) = &options.frontend {
+        let runner = rustdoc::command::SystemProcessRunner;
+        rustdoc::frontend::send_to_frontend(name, &documentation, &runner)?;
+    }
+
+    if options.stats {
+        let ui = rustdoc::ui::Ui::new(config.quiet, config.color);
+        let stats = rustdoc::stats::compute_stats(&documentation);
+        ui.info(&format!(
+            "{}, wrote {} bytes in {:.2}s",
+            stats.summary(),
+            bytes.len(),
+            started.elapsed().as_secs_f64()
+        ));
+    }
+
+    if let Some(report_path) = options.report {
+        let item_count = documentation.included.len() + 1;
+        let usage = rustdoc::report::UsageReport::new("build", usage_options, started.elapsed(), Some(item_count));
+        rustdoc::report::write_report(&report_path, &usage)?;
+    }
+
+    Ok(())
+}
+
+/// One crate's entry in a batch build's combined `index.json`.
+#[derive(serde_derive::Serialize)]
+struct BatchIndexEntry {
+    name: String,
+    #[serde(rename = "manifestPath")]
+    manifest_path: PathBuf,
+    path: PathBuf,
+}
+
+/// Document every manifest in `options.manifest_path` in sequence into its
+/// own `<crate name>` subdirectory of a shared output directory (`--output`,
+/// defaulting to `target/doc` in the current directory), then write a
+/// combined `index.json` listing each crate's name and where its docs
+/// ended up — for a monorepo that isn't a single Cargo workspace, so
+/// there's no single `cargo metadata` call that already knows every crate.
+fn run_build_batch(options: BuildOptions) -> rustdoc::Result<()> {
+    let emit = rustdoc::format::EmitFormat::parse(&options.emit)?;
+    let output_root = options.output.as_deref().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("target/doc"));
+    std::fs::create_dir_all(&output_root)?;
+
+    let mut index = Vec::with_capacity(options.manifest_path.len());
+    for manifest_path in options.manifest_path.clone() {
+        let mut config = configure_build(manifest_path.clone(), &options)?;
+
+        let metadata = rustdoc::cargo::metadata(&config.manifest_path, options.offline, options.locked)?;
+        let package = metadata.root_package().ok_or_else(|| "no root package found in `cargo metadata`".to_string())?;
+
+        // Point every side-artifact this build writes (not just the primary
+        // emitted document below) at this crate's own subdirectory of the
+        // shared output root, the same as a non-batch `--output` does in
+        // `run_build`.
+        let crate_dir = output_root.join(&package.name);
+        config.set_output_path(crate_dir.clone());
+
+        let documentation = rustdoc::build_for_targets(&config, &options.target)?;
+
+        std::fs::create_dir_all(&crate_dir)?;
+        let path = crate_dir.join(emit.default_file_name());
+        let bytes = rustdoc::format::serialize(&documentation, emit)?;
+        report_change_since_last_build(&path, emit, &documentation, options.quiet);
+        rustdoc::write::write_if_changed(&path, &bytes)?;
+
+        index.push(BatchIndexEntry { name: package.name.clone(), manifest_path, path });
+    }
+
+    let index_path = output_root.join("index.json");
+    let index_bytes = serde_json::to_vec_pretty(&index)?;
+    rustdoc::write::write_if_changed(&index_path, &index_bytes)?;
+
+    Ok(())
+}
+
+/// Print a short "N added, N removed, N changed" summary comparing
+/// `documentation` against whatever's already at `path`, unless `quiet` is
+/// set.
+///
+/// A missing, unreadable, or differently-formatted previous build is
+/// treated the same as "nothing to compare against" rather than an error:
+/// this is meant to be informative on top of an otherwise-successful build,
+/// not to block one just because there's no baseline yet.
+fn report_change_since_last_build(path: &Path, format: rustdoc::format::EmitFormat, documentation: &rustdoc::Documentation, quiet: bool) {
+    if quiet {
+        return;
+    }
+
+    let previous = match std::fs::File::open(path).ok().and_then(|file| rustdoc::Documentation::from_reader(file, format).ok()) {
+        Some(previous) => previous,
+        None => return,
+    };
+
+    let changes = rustdoc::diff::diff_documentation(&previous, documentation);
+    let summary = rustdoc::diff::summarize(&changes);
+    if !summary.is_empty() {
+        eprintln!("since last build: {}", summary);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_test(
+    manifest_path: PathBuf,
+    skip_deprecated_doctests: bool,
+    retries: u32,
+    report: Option<PathBuf>,
+    offline: bool,
+    locked: bool,
+    color: String,
+    doctest_backend: String,
+    tests_dir: Option<PathBuf>,
+    verbose: bool,
+) -> rustdoc::Result<()> {
+    let started = Instant::now();
+    let backend = rustdoc::test::TestBackend::parse(&doctest_backend)?;
+    let color = rustdoc::color::ColorChoice::parse(&color)?;
+    let mut config = rustdoc::Config::new(manifest_path)?;
+    config.offline = offline;
+    config.locked = locked;
+    config.color = color;
+    let documentation = rustdoc::build(&config)?;
+    let manifest_dir = config.manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let tests_dir = match tests_dir {
+        Some(tests_dir) => tests_dir,
+        None => {
+            let metadata = rustdoc::cargo::metadata(&config.manifest_path, offline, locked)?;
+            let package = metadata.root_package().ok_or_else(|| "no root package found in `cargo metadata`".to_string())?;
+            rustdoc::test::default_tests_dir(manifest_dir, &package.name)
+        }
+    };
+    let result = rustdoc::test(
+        &documentation,
+        &tests_dir,
+        manifest_dir,
+        skip_deprecated_doctests,
+        retries,
+        offline,
+        locked,
+        verbose,
+        backend,
+        color,
+    );
+
+    if let Some(report_path) = report {
+        let mut usage_options = HashMap::new();
+        usage_options.insert("skipDeprecatedDoctests".to_string(), skip_deprecated_doctests.to_string());
+        usage_options.insert("retries".to_string(), retries.to_string());
+        usage_options.insert("offline".to_string(), offline.to_string());
+        usage_options.insert("locked".to_string(), locked.to_string());
+        usage_options.insert("doctestBackend".to_string(), doctest_backend);
+        usage_options.insert("passed".to_string(), result.is_ok().to_string());
+
+        let usage = rustdoc::report::UsageReport::new("test", usage_options, started.elapsed(), None);
+        rustdoc::report::write_report(&report_path, &usage)?;
+    }
+
+    result
+}
+
+/// Load the documentation `rustdoc build` previously wrote for
+/// `manifest_path`'s crate (in `emit`'s format) and print `item`'s docs,
+/// signature, and relations in `format`.
+fn run_explain(manifest_path: PathBuf, item: &str, format: &str, emit: &str) -> rustdoc::Result<()> {
+    let format = rustdoc::explain::ExplainFormat::parse(format)?;
+    let emit = rustdoc::format::EmitFormat::parse(emit)?;
+
+    let config = rustdoc::Config::new(manifest_path)?;
+    let data_path = config.output_path().join(emit.default_file_name());
+    let file = std::fs::File::open(&data_path)
+        .map_err(|e| format!("couldn't open '{}': {} (did you run `rustdoc build` first?)", data_path.display(), e))?;
+    let documentation = rustdoc::Documentation::from_reader(file, emit)?;
+
+    let found = rustdoc::explain::find_item(&documentation, item)?;
+    println!("{}", rustdoc::explain::explain(found, format)?);
+    Ok(())
+}
+
+fn run_check(manifest_path: PathBuf, emit: &str) -> rustdoc::Result<()> {
+    let emit = rustdoc::format::EmitFormat::parse(emit)?;
+
+    let config = rustdoc::Config::new(manifest_path)?;
+    let data_path = config.output_path().join(emit.default_file_name());
+    let file = std::fs::File::open(&data_path)
+        .map_err(|e| format!("couldn't open '{}': {} (did you run `rustdoc build` first?)", data_path.display(), e))?;
+    let documentation = rustdoc::Documentation::from_reader(file, emit)?;
+
+    let errors = rustdoc::validate::check_consistency(&documentation);
+    if errors.is_empty() {
+        println!("no inconsistencies found");
+        Ok(())
+    } else {
+        for error in &errors {
+            eprintln!("{:?}", error);
+        }
+        Err(format!("found {} inconsistenc{}", errors.len(), if errors.len() == 1 { "y" } else { "ies" }).into())
+    }
+}
+
+fn run_open(manifest_path: PathBuf, browser: Option<String>, print_path: bool) -> rustdoc::Result<()> {
+    let config = rustdoc::Config::new(manifest_path)?;
+    config.open_docs(browser.as_deref(), print_path)
+}
+
+fn run_selftest() -> rustdoc::Result<()> {
+    rustdoc::selftest::run()?;
+    println!("selftest passed");
+    Ok(())
+}
+
+fn run_dev(example: bool, open: bool) -> rustdoc::Result<()> {
+    if !example {
+        return Err("`rustdoc dev` currently only supports `--example`".into());
+    }
+
+    let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("example").join("Cargo.toml");
+    let config = rustdoc::Config::new(manifest_path)?;
+    let documentation = rustdoc::build(&config)?;
+
+    let manifest_dir = config.manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let metadata = rustdoc::cargo::metadata(&config.manifest_path, false, false)?;
+    let package = metadata.root_package().ok_or_else(|| "no root package found in `cargo metadata`".to_string())?;
+    let tests_dir = rustdoc::test::default_tests_dir(manifest_dir, &package.name);
+    rustdoc::test(
+        &documentation,
+        &tests_dir,
+        manifest_dir,
+        false,
+        0,
+        false,
+        false,
+        false,
+        rustdoc::test::TestBackend::Rustc,
+        config.color,
+    )?;
+
+    println!("documented and tested example/ -> {}", config.output_path().display());
+
+    if open {
+        config.open_docs(None, false)?;
+    }
+
+    Ok(())
+}
+
+fn run_frontend(command: FrontendCmd) -> rustdoc::Result<()> {
+    match command {
+        FrontendCmd::List => run_frontend_list(),
+    }
+}
+
+/// List every frontend found on `PATH`, noting which ones this build of
+/// `rustdoc` can actually hand data to (see
+/// [`rustdoc::frontend::check_compatible`]).
+fn run_frontend_list() -> rustdoc::Result<()> {
+    let runner = rustdoc::command::SystemProcessRunner;
+    let frontends = rustdoc::frontend::discover_frontends(&runner);
+
+    if frontends.is_empty() {
+        println!("no frontends found on PATH (expected a `cargo-doc-frontend-<name>` binary)");
+        return Ok(());
+    }
+
+    for frontend in frontends {
+        match frontend.info {
+            Ok(info) => {
+                let note = if info.is_compatible() {
+                    String::new()
+                } else {
+                    format!(" [incompatible: this build of rustdoc writes format {}]", rustdoc::format::FORMAT_VERSION)
+                };
+                println!("{} v{} (data format {}){} - {}", frontend.name, info.version, info.data_format_version, note, frontend.path.display());
+            }
+            Err(e) => eprintln!("{} ({}) - could not be queried: {}", frontend.name, frontend.path.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "api-server")]
+fn run_serve(manifest_path: PathBuf, emit: &str, addr: &str) -> rustdoc::Result<()> {
+    let emit = rustdoc::format::EmitFormat::parse(emit)?;
+    let addr: std::net::SocketAddr = addr.parse().map_err(|e| format!("'{}' isn't a valid address: {}", addr, e))?;
+
+    let config = rustdoc::Config::new(manifest_path)?;
+    let data_path = config.output_path().join(emit.default_file_name());
+    let file = std::fs::File::open(&data_path)
+        .map_err(|e| format!("couldn't open '{}': {} (did you run `rustdoc build` first?)", data_path.display(), e))?;
+    let documentation = rustdoc::Documentation::from_reader(file, emit)?;
+
+    println!("serving on http://{}", addr);
+    rustdoc::server::serve(documentation, addr)
+}
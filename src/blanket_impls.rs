@@ -0,0 +1,129 @@
+//! Detecting blanket trait implementations (`impl<T: Bound> Trait for T`) in
+//! a crate's own source.
+//!
+//! [`crate::json::create_documentation`] walks impl blocks only for the
+//! methods they define (see `walk_impls` in that module) and emits no
+//! "impl" [`crate::json::Data`] node, so there's nothing in
+//! `Documentation` for a `blanket` attribute to go on. Rather than block on
+//! that larger gap, this surfaces what it finds as a `meta.blanketImpls`
+//! list instead: enough for a frontend to flag "`T: Foo` gets this trait
+//! through a blanket impl" without this crate pretending to fully model
+//! trait resolution.
+//!
+//! Detected structurally with `syn`, not through real trait resolution: an
+//! `impl<T: Bound, ...> Trait for T` is recognized when the impl's `Self`
+//! type is exactly a bare identifier that also names one of the impl's own
+//! generic type parameters. That catches the common shape (`impl<T:
+//! Display> ToString for T`) but not one hidden behind a type alias, or
+//! written for a compound self type (`impl<T> Trait for Box<T>`), since
+//! that isn't a blanket impl over every `T` in the same sense.
+
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Serialize;
+use syn::visit::{self, Visit};
+
+use crate::error::*;
+
+/// One blanket impl found in the crate's source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlanketImpl {
+    /// The trait being implemented, e.g. `"ToString"`.
+    pub trait_name: String,
+    /// The generic type parameter the impl is blanket over, e.g. `"T"`.
+    pub type_param: String,
+    /// The bounds on `type_param`, as written, e.g. `["Display"]`.
+    pub bounds: Vec<String>,
+}
+
+#[derive(Default)]
+struct BlanketImplCollector {
+    impls: Vec<BlanketImpl>,
+}
+
+/// The bare identifier a type resolves to, if it's just `Name` (no path
+/// segments, no generic arguments) rather than something like `Box<T>` or
+/// `other::Name`.
+fn as_bare_ident(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() && type_path.path.segments.len() == 1 => {
+            let segment = &type_path.path.segments[0];
+            matches!(segment.arguments, syn::PathArguments::None).then(|| segment.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+impl<'ast> Visit<'ast> for BlanketImplCollector {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        if let (Some((_, trait_path, _)), Some(self_ident)) = (&node.trait_, as_bare_ident(&node.self_ty)) {
+            let type_param = node.generics.type_params().find(|param| param.ident == self_ident);
+
+            if let Some(type_param) = type_param {
+                let trait_name = trait_path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+                let bounds = type_param
+                    .bounds
+                    .iter()
+                    .filter_map(|bound| match bound {
+                        syn::TypeParamBound::Trait(trait_bound) => {
+                            trait_bound.path.segments.last().map(|segment| segment.ident.to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+
+                self.impls.push(BlanketImpl { trait_name, type_param: self_ident, bounds });
+            }
+        }
+
+        visit::visit_item_impl(self, node);
+    }
+}
+
+/// Parse `entry_path` with `syn` and return every blanket impl it defines.
+pub fn find_blanket_impls(entry_path: &Path) -> Result<Vec<BlanketImpl>> {
+    let source = fs::read_to_string(entry_path).chain_err(|| format!("failed to read '{}'", entry_path.display()))?;
+    let file = syn::parse_file(&source).chain_err(|| format!("failed to parse '{}' with syn", entry_path.display()))?;
+
+    let mut collector = BlanketImplCollector::default();
+    collector.visit_file(&file);
+    Ok(collector.impls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(source: &str) -> Vec<BlanketImpl> {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, source).unwrap();
+        find_blanket_impls(&entry_path).unwrap()
+    }
+
+    #[test]
+    fn a_blanket_impl_over_a_bounded_generic_param_is_found() {
+        let impls = find("trait ToString {}\nimpl<T: std::fmt::Display> ToString for T {}\n");
+
+        assert_eq!(impls, vec![BlanketImpl { trait_name: "ToString".to_string(), type_param: "T".to_string(), bounds: vec!["Display".to_string()] }]);
+    }
+
+    #[test]
+    fn a_direct_impl_for_a_concrete_type_is_not_a_blanket_impl() {
+        let impls = find("struct Widget;\ntrait Foo {}\nimpl Foo for Widget {}\n");
+        assert!(impls.is_empty());
+    }
+
+    #[test]
+    fn an_impl_for_a_compound_self_type_is_not_a_blanket_impl() {
+        let impls = find("trait Foo {}\nimpl<T> Foo for Box<T> {}\n");
+        assert!(impls.is_empty());
+    }
+
+    #[test]
+    fn an_inherent_impl_is_not_a_blanket_impl() {
+        let impls = find("struct Widget;\nimpl Widget {}\n");
+        assert!(impls.is_empty());
+    }
+}
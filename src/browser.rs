@@ -0,0 +1,38 @@
+//! Opening generated documentation in the user's browser.
+//!
+//! This crate doesn't bundle a frontend itself (see the crate-level docs;
+//! [`crate::frontend`] only finds separately installed ones on `PATH`): it
+//! only emits `data.json` and friends, so there's no
+//! `CARGO_MANIFEST_DIR`-relative frontend path computed anywhere in this
+//! crate to work around a caller's working directory for. `open_docs`
+//! below just assumes an `index.html` already exists under `output_path`,
+//! written by whatever separate frontend build step the caller ran over
+//! the JSON.
+
+use std::path::Path;
+
+use crate::error::*;
+
+/// Open the generated documentation's index page in the user's default
+/// browser, or `browser` if given, e.g. for a headless environment where
+/// `open::that`'s platform default guess either fails outright or opens the
+/// wrong application.
+///
+/// If `print_path` is set, nothing is opened at all; the resolved
+/// `index.html` path is printed to stdout instead, for scripts that want to
+/// hand it to their own tooling.
+pub fn open_docs(output_path: &Path, browser: Option<&str>, print_path: bool) -> Result<()> {
+    let index = output_path.join("index.html");
+
+    if print_path {
+        println!("{}", index.display());
+        return Ok(());
+    }
+
+    let result = match browser {
+        Some(browser) => open::with(&index, browser),
+        None => open::that(&index),
+    };
+
+    result.chain_err(|| format!("couldn't open '{}' in a browser", index.display()))
+}
@@ -0,0 +1,124 @@
+//! Size/"weight" budgets for generated documentation, so a team notices a
+//! payload getting too big to load quickly before it ships, the same way a
+//! JS bundle-size budget catches an app payload regression.
+//!
+//! Distinct from [`crate::json::split_long_docs`] and
+//! [`crate::json::limit_items`] (gated by [`crate::Config::max_docs_size`]
+//! and [`crate::Config::max_items`]): those actively reshape the
+//! documentation to fit a limit, while this only reports when a limit is
+//! exceeded, for a CI step that wants to catch a regression rather than
+//! silently truncate it away.
+
+use crate::error::*;
+use crate::json::Documentation;
+
+/// Size/count thresholds to check a build's [`Documentation`] against. Every
+/// threshold is independently optional; a `None` threshold is never
+/// checked.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// Max size, in bytes, of `documentation` serialized to JSON as a
+    /// whole (roughly what a frontend fetches in one request).
+    pub max_data_json_size: Option<usize>,
+    /// Max size, in bytes, of any single item (`data`, or one entry of
+    /// `included`) serialized to JSON on its own.
+    pub max_document_size: Option<usize>,
+    /// Max number of items in `documentation.included`.
+    pub max_included_count: Option<usize>,
+    /// Fail the build instead of only warning when a threshold above is
+    /// exceeded.
+    pub deny: bool,
+}
+
+/// Every threshold in `budget` that `documentation` exceeds, each as a
+/// human-readable message.
+pub fn check(documentation: &Documentation, budget: &Budget) -> Result<Vec<String>> {
+    let mut violations = Vec::new();
+
+    if let Some(max_data_json_size) = budget.max_data_json_size {
+        let actual = serde_json::to_vec(documentation)?.len();
+        if actual > max_data_json_size {
+            violations.push(format!("data.json is {} byte(s), over the {}-byte budget", actual, max_data_json_size));
+        }
+    }
+
+    if let Some(max_document_size) = budget.max_document_size {
+        let items = std::iter::once(&documentation.data).chain(documentation.included.iter());
+        for item in items {
+            let actual = serde_json::to_vec(item)?.len();
+            if actual > max_document_size {
+                violations.push(format!("'{}' is {} byte(s), over the {}-byte per-document budget", item.id, actual, max_document_size));
+            }
+        }
+    }
+
+    if let Some(max_included_count) = budget.max_included_count {
+        let actual = documentation.included.len();
+        if actual > max_included_count {
+            violations.push(format!("{} item(s) in `included`, over the {}-item budget", actual, max_included_count));
+        }
+    }
+
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn data(id: &str, docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        Data { id: id.to_string(), ty: "struct".to_string(), attributes, relationships: None, links: None }
+    }
+
+    fn documentation() -> Documentation {
+        Documentation {
+            data: data("crate", "The crate."),
+            included: vec![data("a", "A thing."), data("b", "Another thing.")],
+            meta: HashMap::new(),
+            links: None,
+        }
+    }
+
+    #[test]
+    fn no_thresholds_configured_means_no_violations() {
+        let violations = check(&documentation(), &Budget::default()).unwrap();
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn an_oversized_data_json_is_flagged() {
+        let budget = Budget { max_data_json_size: Some(1), ..Budget::default() };
+        let violations = check(&documentation(), &budget).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("data.json"));
+    }
+
+    #[test]
+    fn an_oversized_document_is_flagged_by_id() {
+        let budget = Budget { max_document_size: Some(1), ..Budget::default() };
+        let violations = check(&documentation(), &budget).unwrap();
+        assert_eq!(violations.len(), 3);
+        assert!(violations.iter().any(|v| v.contains("'crate'")));
+        assert!(violations.iter().any(|v| v.contains("'a'")));
+        assert!(violations.iter().any(|v| v.contains("'b'")));
+    }
+
+    #[test]
+    fn too_many_included_items_is_flagged() {
+        let budget = Budget { max_included_count: Some(1), ..Budget::default() };
+        let violations = check(&documentation(), &budget).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("2 item(s)"));
+    }
+
+    #[test]
+    fn a_document_within_every_budget_has_no_violations() {
+        let budget = Budget { max_data_json_size: Some(1_000_000), max_document_size: Some(1_000_000), max_included_count: Some(100), deny: true };
+        let violations = check(&documentation(), &budget).unwrap();
+        assert!(violations.is_empty());
+    }
+}
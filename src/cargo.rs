@@ -0,0 +1,1038 @@
+//! Everything involving shelling out to `cargo` itself: running `cargo
+//! metadata` to understand a workspace, and running `cargo check` with
+//! save-analysis enabled to produce the data `analysis` consumes.
+
+use std::cell::Cell;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use cargo_metadata::{Metadata, MetadataCommand, Package, Target};
+use serde_derive::Deserialize;
+
+use crate::color::ColorChoice;
+use crate::command::{CommandBridge, ProcessRunner};
+use crate::error::*;
+
+/// Where [`AnalysisSession::ensure_generated`] records the compiler version
+/// used, under the analysis directory it generated into, so a later run
+/// pointed at the same directory via [`crate::Config::analysis_dir`] can
+/// tell whether it was generated by the toolchain currently on `PATH`. See
+/// [`rustc_version`], [`record_rustc_version`], and [`read_rustc_version`].
+const RUSTC_VERSION_FILE: &str = "rustc-version.txt";
+
+/// The current toolchain's `rustc --version --verbose` output, trimmed.
+/// Recorded in `meta.compiler` and used to detect analysis generated by a
+/// different compiler than the one currently on `PATH`.
+pub fn rustc_version() -> Result<String> {
+    let output = CommandBridge::new("rustc", "rustc --version --verbose")
+        .args(["--version", "--verbose"])
+        .run()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Record `version` (see [`rustc_version`]) alongside the analysis data
+/// just generated into `analysis_dir`.
+fn record_rustc_version(analysis_dir: &Path, version: &str) -> Result<()> {
+    std::fs::create_dir_all(analysis_dir)?;
+    std::fs::write(analysis_dir.join(RUSTC_VERSION_FILE), version)?;
+    Ok(())
+}
+
+/// Read back the compiler version [`record_rustc_version`] recorded
+/// alongside the analysis data in `analysis_dir`, if any. `None` for
+/// analysis this crate didn't generate itself (e.g. produced by another
+/// build system and pointed at via `--analysis-dir`), not an error.
+pub fn read_rustc_version(analysis_dir: &Path) -> Option<String> {
+    std::fs::read_to_string(analysis_dir.join(RUSTC_VERSION_FILE))
+        .ok()
+        .map(|version| version.trim().to_string())
+}
+
+/// Prefixes of cargo's own progress lines, which are safe to echo straight
+/// through to the user while we're busy building save-analysis data.
+///
+/// This is necessarily incomplete (cargo doesn't document the full set of
+/// verbs it prints, and adds new ones from time to time), so
+/// [`generate_analysis`]'s `show_all_output` escape hatch exists for
+/// anything that isn't on it.
+const CARGO_PROGRESS_PREFIXES: &[&str] = &[
+    "Compiling",
+    "Checking",
+    "Documenting",
+    "Finished",
+    "Updating",
+    "Downloading",
+    "Fetching",
+    "Fresh",
+    "Ignored",
+    "Adding",
+    "Removing",
+    "Installing",
+    "Replacing",
+    "Unpacking",
+    "Packaging",
+    "Verifying",
+];
+
+/// Whether `line` (a raw line of `cargo check`'s stderr) looks like one of
+/// cargo's own progress lines, based on [`CARGO_PROGRESS_PREFIXES`].
+fn is_cargo_progress_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    CARGO_PROGRESS_PREFIXES.iter().any(|prefix| trimmed.starts_with(prefix))
+}
+
+/// Run `cargo metadata` for the crate rooted at `manifest_path`.
+///
+/// `offline` forwards `--offline`, so a crate whose dependencies are all
+/// vendored (or otherwise already available locally, e.g. via an
+/// alternative registry mirror already fetched) never has this reach out to
+/// the network, the same way a plain `cargo build --offline` wouldn't.
+///
+/// `locked` forwards `--locked`, so this fails outright (see
+/// [`ErrorKind::LockfileDrift`]) rather than silently updating `Cargo.lock`
+/// when it's out of step with `Cargo.toml`.
+pub fn metadata(manifest_path: &Path, offline: bool, locked: bool) -> Result<Metadata> {
+    let mut command = MetadataCommand::new();
+    command.manifest_path(manifest_path);
+    // Cargo discovers `.cargo/config.toml` (and thus any source
+    // replacement, e.g. for a vendored dependency) starting from the
+    // process's current directory, not from `--manifest-path`; running from
+    // the manifest's own directory keeps this in step with `cargo metadata`
+    // run by hand from inside the crate.
+    if let Some(dir) = manifest_path.parent() {
+        command.current_dir(dir);
+    }
+    let mut other_options = Vec::new();
+    if offline {
+        other_options.push("--offline".to_string());
+    }
+    if locked {
+        other_options.push("--locked".to_string());
+    }
+    if !other_options.is_empty() {
+        command.other_options(other_options);
+    }
+
+    match command.exec() {
+        Err(cargo_metadata::Error::CargoMetadata { stderr }) => {
+            let lines: Vec<String> = stderr.lines().map(str::to_string).collect();
+            match lockfile_drift_detail(&lines) {
+                Some(detail) => Err(ErrorKind::LockfileDrift(detail).into()),
+                None => Err(format!("failed to run `cargo metadata`: {}", stderr).into()),
+            }
+        }
+        other => other.chain_err(|| "failed to run `cargo metadata`"),
+    }
+}
+
+/// The `[build]` table of a `.cargo/config.toml`, as far as this crate cares
+/// about it.
+#[derive(Debug, Default, Deserialize)]
+struct CargoConfigBuild {
+    #[serde(default)]
+    rustflags: Option<Rustflags>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CargoConfig {
+    #[serde(default)]
+    build: CargoConfigBuild,
+}
+
+/// `build.rustflags` may be written as either a space-separated string or an
+/// array of arguments; cargo accepts both.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Rustflags {
+    List(Vec<String>),
+    String(String),
+}
+
+impl Rustflags {
+    fn into_flags(self) -> Vec<String> {
+        match self {
+            Rustflags::List(flags) => flags,
+            Rustflags::String(flags) => flags.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+}
+
+/// Walk upward from `start` looking for the nearest `.cargo/config.toml` (or
+/// the older, extensionless `.cargo/config`), mirroring where cargo itself
+/// would find one.
+///
+/// Unlike cargo, this only reads the *nearest* config file rather than
+/// merging every config found between `start` and the filesystem root; a
+/// crate with a more elaborate `.cargo/config.toml` setup further up its
+/// workspace won't have those flags picked up here.
+fn find_cargo_config(start: &Path) -> Option<PathBuf> {
+    let mut dir = Some(start);
+    while let Some(current) = dir {
+        for name in ["config.toml", "config"] {
+            let candidate = current.join(".cargo").join(name);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+/// The `RUSTFLAGS` cargo would use to build `manifest_path`'s crate on its
+/// own, before this crate adds `-Z save-analysis` to them.
+///
+/// Cargo prefers an inherited `RUSTFLAGS` environment variable over its own
+/// `build.rustflags` config; a malformed or unreadable config file is
+/// treated the same as a missing one; it doesn't set the flags, but it
+/// shouldn't fail an otherwise-working `cargo check` either.
+fn inherited_rustflags(manifest_path: &Path) -> Vec<String> {
+    if let Ok(env_flags) = std::env::var("RUSTFLAGS") {
+        return env_flags.split_whitespace().map(str::to_string).collect();
+    }
+
+    let start = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let config_path = match find_cargo_config(start) {
+        Some(path) => path,
+        None => return Vec::new(),
+    };
+
+    let contents = match std::fs::read_to_string(&config_path) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+
+    let config: CargoConfig = match toml::from_str(&contents) {
+        Ok(config) => config,
+        Err(_) => return Vec::new(),
+    };
+
+    config.build.rustflags.map(Rustflags::into_flags).unwrap_or_default()
+}
+
+/// Build the `cargo check` command [`generate_analysis`] runs, without
+/// spawning it, so its flags and environment can be inspected directly in
+/// tests.
+///
+/// `CARGO_TARGET_DIR` is pointed at `analysis_dir` (the same directory
+/// [`analysis::Analysis::generate`](crate::analysis::Analysis::generate) is
+/// later pointed at to read the result back), rather than left at cargo's
+/// default. An earlier attempt at this pointed every invocation at a fresh,
+/// throwaway directory, so each one paid for a full rebuild with nothing to
+/// reuse; because `analysis_dir` is the same stable, deterministic path
+/// across runs (see [`analysis_dir_for_target`]), cargo can incrementally
+/// reuse what a previous analysis run already compiled there, and none of
+/// it collides with the user's regular `target/debug`.
+///
+/// `check_env` is applied on top of that, for crates whose build script
+/// needs something set to succeed (a native library path, an API key
+/// consulted at build time, and so on) that the caller's own shell already
+/// has, but that a bare `cargo check` here wouldn't otherwise inherit.
+///
+/// `offline` forwards `--offline`, for a crate whose dependencies are all
+/// vendored or otherwise already available without reaching the network.
+///
+/// `locked` forwards `--locked`, so this fails instead of silently updating
+/// `Cargo.lock` when it's out of step with `Cargo.toml` (see
+/// [`ErrorKind::LockfileDrift`]).
+///
+/// `color` forwards `--color` (see [`ColorChoice::as_cargo_arg`]), so cargo
+/// (and the `rustc` it drives) decides whether to color its own diagnostics
+/// the same way we decided whether to color ours, rather than falling back
+/// to its own `auto` detection against the pipe [`run_check_once`] reads
+/// its stderr through.
+#[allow(clippy::too_many_arguments)]
+fn build_check_command(
+    manifest_path: &Path,
+    all_targets: bool,
+    target_triple: Option<&str>,
+    analysis_dir: &Path,
+    check_env: &[(String, String)],
+    offline: bool,
+    locked: bool,
+    color: ColorChoice,
+) -> Command {
+    let mut rustflags = inherited_rustflags(manifest_path);
+    rustflags.push("-Z".to_string());
+    rustflags.push("save-analysis".to_string());
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("check")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("--color")
+        .arg(color.as_cargo_arg())
+        .env("RUSTFLAGS", rustflags.join(" "))
+        .env("CARGO_TARGET_DIR", analysis_dir)
+        .envs(check_env.iter().map(|(key, value)| (key, value)))
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    // See the comment on `metadata`'s `current_dir` call: cargo's own config
+    // discovery (source replacement included) starts from the process's
+    // current directory, not `--manifest-path`.
+    if let Some(dir) = manifest_path.parent() {
+        command.current_dir(dir);
+    }
+
+    if all_targets {
+        command.arg("--all-targets");
+    }
+
+    if let Some(target_triple) = target_triple {
+        command.arg("--target").arg(target_triple);
+    }
+
+    if offline {
+        command.arg("--offline");
+    }
+
+    if locked {
+        command.arg("--locked");
+    }
+
+    command
+}
+
+/// If `lines` (a `cargo check` invocation's stderr) contain cargo's own
+/// build-script-failure message, pull out the failing package's spec and
+/// the rest of the message cargo printed about it (including its "Caused
+/// by" block, which is where the build script's own output ends up), so
+/// [`generate_analysis`] can report specifically which package's build
+/// script failed instead of a generic "cargo check did not run
+/// successfully".
+fn build_script_failure(lines: &[String]) -> Option<(String, String)> {
+    let start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("error: failed to run custom build command for `"))?;
+
+    let package = lines[start]
+        .trim_start()
+        .trim_start_matches("error: failed to run custom build command for `")
+        .trim_end_matches('`')
+        .to_string();
+
+    Some((package, lines[start..].join("\n")))
+}
+
+/// If `lines` (a cargo invocation's stderr, run with `--locked`) contain
+/// cargo's "lock file needs to be updated" message, pull out that message
+/// (and anything cargo printed alongside it, e.g. the suggestion to drop
+/// `--locked` for `--offline` instead), so the caller can report it via
+/// [`ErrorKind::LockfileDrift`] instead of a generic failure.
+fn lockfile_drift_detail(lines: &[String]) -> Option<String> {
+    let start = lines.iter().position(|line| line.contains("--locked was passed to prevent this"))?;
+    Some(lines[start..].join("\n"))
+}
+
+/// Substrings of a failed `cargo check`'s stderr that indicate the failure
+/// was transient (the compiler itself falling over, rather than a real
+/// error in the crate being checked), and thus worth retrying once against
+/// a clean `analysis_dir` rather than reporting outright. See
+/// [`generate_analysis`].
+///
+/// This is deliberately narrow: anything not on this list is assumed to be
+/// a real compile error, which retrying would only delay reporting.
+const TRANSIENT_FAILURE_SIGNATURES: &[&str] = &[
+    "internal compiler error",
+    "the compiler unexpectedly panicked",
+    "error: rustc interrupted by SIGSEGV",
+    "signal: 11, SIGSEGV",
+    "failed to write analysis",
+];
+
+/// Whether `lines` (a failed `cargo check` invocation's stderr) contain one
+/// of [`TRANSIENT_FAILURE_SIGNATURES`].
+fn is_transient_failure(lines: &[String]) -> bool {
+    lines.iter().any(|line| TRANSIENT_FAILURE_SIGNATURES.iter().any(|signature| line.contains(signature)))
+}
+
+/// Run `cargo check` once, returning its stderr lines alongside the error
+/// (if any), so [`generate_analysis`] can inspect them to decide whether a
+/// failure is worth retrying.
+#[allow(clippy::too_many_arguments)]
+fn run_check_once(
+    manifest_path: &Path,
+    all_targets: bool,
+    target_triple: Option<&str>,
+    analysis_dir: &Path,
+    check_env: &[(String, String)],
+    show_all_output: bool,
+    offline: bool,
+    locked: bool,
+    color: ColorChoice,
+) -> (Vec<String>, Result<()>) {
+    let mut command = build_check_command(manifest_path, all_targets, target_triple, analysis_dir, check_env, offline, locked, color);
+
+    let mut child = match command.spawn().chain_err(|| ErrorKind::Cargo("cargo check".into())) {
+        Ok(child) => child,
+        Err(err) => return (Vec::new(), Err(err)),
+    };
+
+    let mut lines = Vec::new();
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines().map_while(std::result::Result::ok) {
+            if show_all_output || is_cargo_progress_line(&line) {
+                eprintln!("{}", line);
+            }
+            lines.push(line);
+        }
+    }
+
+    let status = match child.wait().chain_err(|| ErrorKind::Cargo("cargo check".into())) {
+        Ok(status) => status,
+        Err(err) => return (lines, Err(err)),
+    };
+
+    if !status.success() {
+        let err = match (lockfile_drift_detail(&lines), build_script_failure(&lines)) {
+            (Some(detail), _) => ErrorKind::LockfileDrift(detail).into(),
+            (None, Some((package, detail))) => ErrorKind::Cargo(format!("build script for {} failed:\n{}", package, detail)).into(),
+            (None, None) => ErrorKind::Cargo("cargo check".into()).into(),
+        };
+        return (lines, Err(err));
+    }
+
+    (lines, Ok(()))
+}
+
+/// Generate save-analysis data for the crate at `manifest_path` by running
+/// `cargo check` with the appropriate nightly flag set, writing into
+/// `analysis_dir` (see [`build_check_command`]).
+///
+/// When `all_targets` is set, every target (lib, bins, examples, tests) is
+/// checked in one invocation, so documenting several of a crate's targets
+/// doesn't each pay for their own `cargo check`.
+///
+/// When `target_triple` is set, cargo cross-checks for that target instead
+/// of the host.
+///
+/// Cargo's stderr is filtered down to lines that look like its own progress
+/// output (see [`CARGO_PROGRESS_PREFIXES`]) unless `show_all_output` is set,
+/// in which case every line is echoed through verbatim, e.g. for debugging
+/// a `cargo check` failure that isn't a whitelisted verb.
+///
+/// `-Z save-analysis` is appended to whatever `RUSTFLAGS` the crate would
+/// otherwise be built with (see [`inherited_rustflags`]), rather than
+/// replacing them outright, so documenting a crate that relies on `--cfg` or
+/// codegen flags to even compile doesn't silently drop them.
+///
+/// `check_env` is passed straight through to `cargo check`'s environment
+/// (see [`build_check_command`]). If a build script still fails, the
+/// package it failed for and cargo's own report of why are surfaced via
+/// [`ErrorKind::Cargo`] instead of the generic "cargo check did not run
+/// successfully" (see [`build_script_failure`]).
+///
+/// There's no way to make `cargo check` skip build scripts outright (cargo
+/// has no such flag, and running the ones our own crate doesn't need would
+/// defeat the purpose of a native-dependency-free build); `check_env` is
+/// the escape hatch for a build script that just needs a value it isn't
+/// otherwise getting.
+///
+/// `offline` forwards `--offline` to the underlying `cargo check` (see
+/// [`build_check_command`]).
+///
+/// `locked` forwards `--locked`, so a `Cargo.lock` out of step with
+/// `Cargo.toml` fails the build with [`ErrorKind::LockfileDrift`] instead of
+/// being silently updated, for a release pipeline that wants documentation
+/// builds reproducible against the committed lockfile. A lockfile-drift
+/// failure is never treated as transient, even though `is_transient_failure`
+/// wouldn't recognize it as one anyway; retrying wouldn't change cargo's
+/// answer.
+///
+/// If the first attempt fails with what looks like a transient, ICE-like
+/// failure (see [`TRANSIENT_FAILURE_SIGNATURES`]) rather than a real error
+/// in the crate being checked, `analysis_dir` is wiped and the check is
+/// retried exactly once before the failure is reported, since some
+/// nightlies intermittently corrupt or fail to write incremental
+/// save-analysis state under load. A second failure (transient-looking or
+/// not) is reported as-is.
+///
+/// `color` forwards `--color` to the underlying `cargo check` (see
+/// [`build_check_command`]); since it's forwarded rather than guessed at,
+/// the lines echoed straight through above are already colored exactly the
+/// way `color` asked for, with nothing more to do here.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_analysis(
+    manifest_path: &Path,
+    all_targets: bool,
+    target_triple: Option<&str>,
+    analysis_dir: &Path,
+    check_env: &[(String, String)],
+    show_all_output: bool,
+    offline: bool,
+    locked: bool,
+    color: ColorChoice,
+) -> Result<()> {
+    let (lines, result) = run_check_once(manifest_path, all_targets, target_triple, analysis_dir, check_env, show_all_output, offline, locked, color);
+
+    if result.is_ok() || !is_transient_failure(&lines) {
+        return result;
+    }
+
+    eprintln!("cargo check failed with what looks like a transient error; retrying once with a clean analysis directory");
+    if analysis_dir.is_dir() {
+        std::fs::remove_dir_all(analysis_dir).chain_err(|| format!("failed to clear stale analysis directory '{}'", analysis_dir.display()))?;
+    }
+
+    run_check_once(manifest_path, all_targets, target_triple, analysis_dir, check_env, show_all_output, offline, locked, color).1
+}
+
+/// Build the `cargo build` command [`build_extern_crate`] runs, without
+/// spawning it, so its flags can be inspected directly in tests (see
+/// [`build_check_command`], which does the same for `cargo check`).
+fn build_extern_crate_command(manifest_path: &Path, crate_name: &str, offline: bool, locked: bool, color: ColorChoice) -> Command {
+    let mut command = Command::new("cargo");
+    command
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("--package")
+        .arg(crate_name)
+        .arg("--lib")
+        .arg("--message-format=json")
+        .arg("--color")
+        .arg(color.as_cargo_arg())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    // See the comment on `metadata`'s `current_dir` call.
+    if let Some(dir) = manifest_path.parent() {
+        command.current_dir(dir);
+    }
+
+    if offline {
+        command.arg("--offline");
+    }
+
+    if locked {
+        command.arg("--locked");
+    }
+
+    command
+}
+
+/// Build `crate_name`'s library, as a `--extern` dependency for a compiled
+/// doc example (see [`crate::doctest::DoctestConfig::inject`]), and return
+/// the path to its `.rlib`.
+///
+/// This runs a plain `cargo build --package <crate_name> --lib`, rather than
+/// guessing at `target/debug/deps/lib<crate_name>-<hash>.rlib`'s naming (a
+/// package can produce more than one, e.g. across feature-gated builds), by
+/// reading the artifact path back out of `--message-format=json` instead.
+///
+/// `offline` forwards `--offline` to this `cargo build`, so injecting a
+/// vendored dependency's extern crate into a doc example doesn't reach the
+/// network either.
+///
+/// `locked` forwards `--locked`, so this fails with
+/// [`ErrorKind::LockfileDrift`] rather than silently updating `Cargo.lock`.
+///
+/// `color` forwards `--color` to this `cargo build`, the same as
+/// [`generate_analysis`] does for `cargo check`.
+///
+/// `runner` is how the `cargo build` itself gets spawned (see
+/// [`crate::command::ProcessRunner`]), so this can be exercised in a test
+/// with a fake process outcome instead of actually invoking `cargo`.
+pub fn build_extern_crate(manifest_path: &Path, crate_name: &str, offline: bool, locked: bool, color: ColorChoice, runner: &dyn ProcessRunner) -> Result<PathBuf> {
+    let mut command = build_extern_crate_command(manifest_path, crate_name, offline, locked, color);
+    let description = format!("cargo build -p {}", crate_name);
+
+    let output = runner.run(&mut command).chain_err(|| ErrorKind::Cargo(description.clone()))?;
+
+    let mut rlib = None;
+    for message in cargo_metadata::Message::parse_stream(output.stdout.as_slice()) {
+        let message = message.chain_err(|| ErrorKind::Cargo(description.clone()))?;
+        if let cargo_metadata::Message::CompilerArtifact(artifact) = message {
+            if artifact.target.kind.iter().any(|kind| kind == "lib") {
+                rlib = artifact
+                    .filenames
+                    .iter()
+                    .find(|path| path.extension() == Some("rlib"))
+                    .map(|path| path.clone().into_std_path_buf());
+            }
+        }
+    }
+
+    if !output.status.success() {
+        return Err(ErrorKind::Cargo(description).into());
+    }
+
+    rlib.ok_or_else(|| format!("cargo build -p {} produced no rlib to inject", crate_name).into())
+}
+
+/// The `kind`s a library target can be built as, per its `[lib]` section's
+/// `crate-type`. A target's `kind` mirrors `crate-type` exactly (unlike an
+/// example target that happens to be a library, where `kind` stays
+/// `"example"` and `crate_types` carries this list instead), so a crate
+/// declaring `crate-type = ["cdylib", "rlib"]` has a `kind` of exactly
+/// that, never `"lib"`.
+const LIBRARY_KINDS: &[&str] = &["lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"];
+
+/// Pick the library target to document out of a package's full target list
+/// (which also includes bins, examples, tests, and benches).
+///
+/// A `[lib]` with more than one `crate-type` (e.g. `["cdylib", "rlib"]`) is
+/// reported as a target whose `kind` is that whole list rather than the
+/// single value `"lib"`; matching against [`LIBRARY_KINDS`] rather than the
+/// literal string `"lib"` picks it up regardless of which combination of
+/// crate-types it was built with.
+pub fn target_from_metadata(package: &Package) -> Result<&Target> {
+    package
+        .targets
+        .iter()
+        .find(|target| target.kind.iter().any(|kind| LIBRARY_KINDS.contains(&kind.as_str())))
+        .ok_or_else(|| format!("no library target found for package '{}'", package.name).into())
+}
+
+/// The subset of `Cargo.toml`'s package metadata crates.io itself uses for
+/// discovery: keywords, categories, and links back to a homepage/repo. Kept
+/// as its own JSON object (rather than several separate attributes) so a
+/// consumer can tell "no discovery metadata set" from "set to an empty
+/// list" by checking whether the object is even present.
+pub fn discovery_metadata(package: &Package) -> serde_json::Value {
+    serde_json::json!({
+        "keywords": package.keywords,
+        "categories": package.categories,
+        "homepage": package.homepage,
+        "repository": package.repository,
+    })
+}
+
+/// Where cargo writes save-analysis data for `target_triple` (or the host,
+/// if `None`) under a crate's base `target/` directory.
+pub fn analysis_dir_for_target(base_target_dir: &Path, target_triple: Option<&str>) -> PathBuf {
+    match target_triple {
+        Some(triple) => base_target_dir.join(triple).join("rls"),
+        None => base_target_dir.join("rls"),
+    }
+}
+
+/// Coordinates analysis generation across every target being documented in
+/// a single invocation, so that documenting a lib and its bins (or several
+/// workspace members) runs `cargo check --all-targets` once instead of once
+/// per target.
+pub struct AnalysisSession {
+    manifest_path: PathBuf,
+    target_triple: Option<String>,
+    analysis_dir: PathBuf,
+    check_env: Vec<(String, String)>,
+    show_all_output: bool,
+    offline: bool,
+    locked: bool,
+    color: ColorChoice,
+    generated: Cell<bool>,
+}
+
+impl AnalysisSession {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        manifest_path: PathBuf,
+        analysis_dir: PathBuf,
+        target_triple: Option<String>,
+        check_env: Vec<(String, String)>,
+        show_all_output: bool,
+        offline: bool,
+        locked: bool,
+        color: ColorChoice,
+    ) -> AnalysisSession {
+        AnalysisSession {
+            manifest_path,
+            target_triple,
+            analysis_dir,
+            check_env,
+            show_all_output,
+            offline,
+            locked,
+            color,
+            generated: Cell::new(false),
+        }
+    }
+
+    /// Generate analysis for every target, unless a previous call on this
+    /// session already did so.
+    pub fn ensure_generated(&self) -> Result<()> {
+        if self.generated.get() {
+            return Ok(());
+        }
+
+        generate_analysis(
+            &self.manifest_path,
+            true,
+            self.target_triple.as_deref(),
+            &self.analysis_dir,
+            &self.check_env,
+            self.show_all_output,
+            self.offline,
+            self.locked,
+            self.color,
+        )?;
+
+        if let Ok(version) = rustc_version() {
+            record_rustc_version(&self.analysis_dir, &version)?;
+        }
+
+        self.generated.set(true);
+        Ok(())
+    }
+
+    /// The directory analysis data for every target was written to.
+    pub fn analysis_dir(&self) -> &Path {
+        &self.analysis_dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_generated_only_runs_once() {
+        // `cargo` isn't available to actually generate analysis in this
+        // test, but the memoization can still be checked: once a session is
+        // marked as generated, `ensure_generated` returns `Ok` straight away
+        // without running `cargo check` again.
+        let session = AnalysisSession::new(PathBuf::from("Cargo.toml"), PathBuf::from("target/rls"), None, Vec::new(), false, false, false, ColorChoice::Auto);
+        session.generated.set(true);
+        assert!(session.ensure_generated().is_ok());
+    }
+
+    #[test]
+    fn analysis_dir_for_target_nests_under_the_triple() {
+        assert_eq!(
+            analysis_dir_for_target(Path::new("target"), Some("x86_64-unknown-linux-musl")),
+            PathBuf::from("target/x86_64-unknown-linux-musl/rls")
+        );
+    }
+
+    #[test]
+    fn analysis_dir_for_target_defaults_to_the_host() {
+        assert_eq!(analysis_dir_for_target(Path::new("target"), None), PathBuf::from("target/rls"));
+    }
+
+    #[test]
+    fn read_rustc_version_returns_none_when_nothing_was_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_rustc_version(dir.path()), None);
+    }
+
+    #[test]
+    fn read_rustc_version_returns_what_record_rustc_version_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        record_rustc_version(dir.path(), "rustc 1.99.0 (deadbeef 2026-01-01)").unwrap();
+        assert_eq!(read_rustc_version(dir.path()), Some("rustc 1.99.0 (deadbeef 2026-01-01)".to_string()));
+    }
+
+    #[test]
+    fn recognizes_checking_and_documenting_as_progress_lines() {
+        assert!(is_cargo_progress_line("  Checking rustdoc v0.1.0"));
+        assert!(is_cargo_progress_line("Documenting rustdoc v0.1.0"));
+    }
+
+    #[test]
+    fn does_not_treat_arbitrary_output_as_progress() {
+        assert!(!is_cargo_progress_line("error[E0308]: mismatched types"));
+    }
+
+    #[test]
+    fn reads_rustflags_as_an_array_from_the_nearest_cargo_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".cargo")).unwrap();
+        std::fs::write(
+            dir.path().join(".cargo").join("config.toml"),
+            "[build]\nrustflags = [\"--cfg\", \"foo\"]\n",
+        )
+        .unwrap();
+
+        let flags = inherited_rustflags(&dir.path().join("Cargo.toml"));
+        assert_eq!(flags, vec!["--cfg".to_string(), "foo".to_string()]);
+    }
+
+    #[test]
+    fn reads_rustflags_as_a_string_from_the_nearest_cargo_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".cargo")).unwrap();
+        std::fs::write(dir.path().join(".cargo").join("config.toml"), "[build]\nrustflags = \"--cfg foo\"\n").unwrap();
+
+        let flags = inherited_rustflags(&dir.path().join("Cargo.toml"));
+        assert_eq!(flags, vec!["--cfg".to_string(), "foo".to_string()]);
+    }
+
+    #[test]
+    fn no_cargo_config_means_no_inherited_rustflags() {
+        let dir = tempfile::tempdir().unwrap();
+        let flags = inherited_rustflags(&dir.path().join("Cargo.toml"));
+        assert!(flags.is_empty());
+    }
+
+    #[test]
+    fn points_cargo_target_dir_at_the_analysis_dir() {
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &[], false, false, ColorChoice::Auto);
+        let target_dir = command
+            .get_envs()
+            .find(|(key, _)| *key == "CARGO_TARGET_DIR")
+            .and_then(|(_, value)| value);
+        assert_eq!(target_dir, Some(std::ffi::OsStr::new("target/rls")));
+    }
+
+    #[test]
+    fn passes_check_env_through_to_the_command() {
+        let check_env = vec![("FOO".to_string(), "bar".to_string())];
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &check_env, false, false, ColorChoice::Auto);
+        let foo = command.get_envs().find(|(key, _)| *key == "FOO").and_then(|(_, value)| value);
+        assert_eq!(foo, Some(std::ffi::OsStr::new("bar")));
+    }
+
+    #[test]
+    fn offline_adds_the_flag_to_the_check_command() {
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &[], true, false, ColorChoice::Auto);
+        assert!(command.get_args().any(|arg| arg == "--offline"));
+    }
+
+    #[test]
+    fn without_offline_the_check_command_has_no_such_flag() {
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &[], false, false, ColorChoice::Auto);
+        assert!(!command.get_args().any(|arg| arg == "--offline"));
+    }
+
+    #[test]
+    fn locked_adds_the_flag_to_the_check_command() {
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &[], false, true, ColorChoice::Auto);
+        assert!(command.get_args().any(|arg| arg == "--locked"));
+    }
+
+    #[test]
+    fn without_locked_the_check_command_has_no_such_flag() {
+        let command = build_check_command(Path::new("Cargo.toml"), true, None, Path::new("target/rls"), &[], false, false, ColorChoice::Auto);
+        assert!(!command.get_args().any(|arg| arg == "--locked"));
+    }
+
+    #[test]
+    fn offline_adds_the_flag_to_the_extern_crate_build_command() {
+        let command = build_extern_crate_command(Path::new("Cargo.toml"), "foo", true, false, ColorChoice::Auto);
+        assert!(command.get_args().any(|arg| arg == "--offline"));
+    }
+
+    #[test]
+    fn locked_adds_the_flag_to_the_extern_crate_build_command() {
+        let command = build_extern_crate_command(Path::new("Cargo.toml"), "foo", false, true, ColorChoice::Auto);
+        assert!(command.get_args().any(|arg| arg == "--locked"));
+    }
+
+    #[test]
+    fn build_extern_crate_finds_the_rlib_from_a_fake_cargo_build() {
+        use crate::command::testing::FakeProcessRunner;
+
+        let message = serde_json::json!({
+            "reason": "compiler-artifact",
+            "package_id": "foo 0.1.0 (path+file:///tmp/foo)",
+            "manifest_path": "/tmp/foo/Cargo.toml",
+            "target": {
+                "kind": ["lib"],
+                "crate_types": ["lib"],
+                "name": "foo",
+                "src_path": "/tmp/foo/src/lib.rs",
+                "edition": "2021",
+                "doctest": true,
+                "test": true,
+            },
+            "profile": {
+                "opt_level": "0",
+                "debuginfo": 2,
+                "debug_assertions": true,
+                "overflow_checks": true,
+                "test": false,
+            },
+            "features": [],
+            "filenames": ["/tmp/foo/target/debug/libfoo.rlib"],
+            "executable": null,
+            "fresh": false,
+        })
+        .to_string();
+        let stdout = format!("{}\n", message).into_bytes();
+
+        let runner = FakeProcessRunner::new(vec![(0, stdout)]);
+        let rlib = build_extern_crate(Path::new("/tmp/foo/Cargo.toml"), "foo", false, false, ColorChoice::Auto, &runner).unwrap();
+        assert_eq!(rlib, PathBuf::from("/tmp/foo/target/debug/libfoo.rlib"));
+    }
+
+    #[test]
+    fn build_extern_crate_errors_out_when_cargo_build_fails() {
+        use crate::command::testing::FakeProcessRunner;
+
+        let runner = FakeProcessRunner::new(vec![(1, Vec::new())]);
+        assert!(build_extern_crate(Path::new("/tmp/foo/Cargo.toml"), "foo", false, false, ColorChoice::Auto, &runner).is_err());
+    }
+
+    #[test]
+    fn extracts_the_failing_package_and_message_from_a_build_script_failure() {
+        let lines: Vec<String> = vec![
+            "   Compiling foo v0.1.0".to_string(),
+            "error: failed to run custom build command for `foo v0.1.0`".to_string(),
+            "".to_string(),
+            "Caused by:".to_string(),
+            "  process didn't exit successfully: `.../build-script-build` (exit status: 101)".to_string(),
+            "  --- stderr".to_string(),
+            "  thread 'main' panicked at 'missing FOO_LIB_DIR'".to_string(),
+        ]
+        .into_iter()
+        .collect();
+
+        let (package, detail) = build_script_failure(&lines).unwrap();
+        assert_eq!(package, "foo v0.1.0");
+        assert!(detail.contains("missing FOO_LIB_DIR"));
+    }
+
+    #[test]
+    fn a_plain_compile_error_has_no_build_script_failure() {
+        let lines = vec!["error[E0308]: mismatched types".to_string()];
+        assert!(build_script_failure(&lines).is_none());
+    }
+
+    #[test]
+    fn extracts_the_message_from_a_lockfile_drift_failure() {
+        let lines: Vec<String> = vec![
+            "error: the lock file needs to be updated but --locked was passed to prevent this".to_string(),
+            "If you want to try to generate the lock file without accessing the network, remove the --locked flag and use --offline instead.".to_string(),
+        ]
+        .into_iter()
+        .collect();
+
+        let detail = lockfile_drift_detail(&lines).unwrap();
+        assert!(detail.contains("--locked was passed to prevent this"));
+        assert!(detail.contains("--offline instead"));
+    }
+
+    #[test]
+    fn a_plain_compile_error_has_no_lockfile_drift_detail() {
+        let lines = vec!["error[E0308]: mismatched types".to_string()];
+        assert!(lockfile_drift_detail(&lines).is_none());
+    }
+
+    fn package_with_targets(targets: serde_json::Value) -> Package {
+        serde_json::from_value(serde_json::json!({
+            "name": "test-crate",
+            "version": "0.1.0",
+            "id": "test-crate 0.1.0 (path+file:///tmp/test-crate)",
+            "dependencies": [],
+            "targets": targets,
+            "features": {},
+            "manifest_path": "/tmp/test-crate/Cargo.toml",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_a_plain_lib_target() {
+        let package = package_with_targets(serde_json::json!([
+            {"name": "test_crate", "kind": ["lib"], "src_path": "src/lib.rs"},
+        ]));
+        assert_eq!(target_from_metadata(&package).unwrap().name, "test_crate");
+    }
+
+    #[test]
+    fn finds_a_cdylib_and_rlib_target() {
+        let package = package_with_targets(serde_json::json!([
+            {"name": "bin_helper", "kind": ["bin"], "src_path": "src/main.rs"},
+            {"name": "test_crate", "kind": ["cdylib", "rlib"], "src_path": "src/lib.rs"},
+        ]));
+        assert_eq!(target_from_metadata(&package).unwrap().name, "test_crate");
+    }
+
+    #[test]
+    fn finds_a_staticlib_target() {
+        let package = package_with_targets(serde_json::json!([
+            {"name": "test_crate", "kind": ["staticlib"], "src_path": "src/lib.rs"},
+        ]));
+        assert_eq!(target_from_metadata(&package).unwrap().name, "test_crate");
+    }
+
+    #[test]
+    fn errors_when_no_target_is_a_library() {
+        let package = package_with_targets(serde_json::json!([
+            {"name": "test_crate", "kind": ["bin"], "src_path": "src/main.rs"},
+        ]));
+        assert!(target_from_metadata(&package).is_err());
+    }
+
+    fn package_with_discovery_fields(keywords: serde_json::Value, categories: serde_json::Value, homepage: serde_json::Value, repository: serde_json::Value) -> Package {
+        serde_json::from_value(serde_json::json!({
+            "name": "test-crate",
+            "version": "0.1.0",
+            "id": "test-crate 0.1.0 (path+file:///tmp/test-crate)",
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": "/tmp/test-crate/Cargo.toml",
+            "keywords": keywords,
+            "categories": categories,
+            "homepage": homepage,
+            "repository": repository,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn discovery_metadata_carries_keywords_categories_and_links() {
+        let package = package_with_discovery_fields(
+            serde_json::json!(["parsing", "cli"]),
+            serde_json::json!(["command-line-utilities"]),
+            serde_json::json!("https://example.com"),
+            serde_json::json!("https://github.com/example/test-crate"),
+        );
+
+        assert_eq!(
+            discovery_metadata(&package),
+            serde_json::json!({
+                "keywords": ["parsing", "cli"],
+                "categories": ["command-line-utilities"],
+                "homepage": "https://example.com",
+                "repository": "https://github.com/example/test-crate",
+            })
+        );
+    }
+
+    #[test]
+    fn discovery_metadata_reports_unset_fields_as_empty_rather_than_omitting_them() {
+        let package = package_with_discovery_fields(
+            serde_json::json!([]),
+            serde_json::json!([]),
+            serde_json::Value::Null,
+            serde_json::Value::Null,
+        );
+
+        assert_eq!(
+            discovery_metadata(&package),
+            serde_json::json!({
+                "keywords": [],
+                "categories": [],
+                "homepage": null,
+                "repository": null,
+            })
+        );
+    }
+
+    #[test]
+    fn recognizes_an_internal_compiler_error_as_transient() {
+        let lines = vec!["error: internal compiler error: unexpected panic".to_string()];
+        assert!(is_transient_failure(&lines));
+    }
+
+    #[test]
+    fn does_not_treat_a_plain_compile_error_as_transient() {
+        let lines = vec!["error[E0308]: mismatched types".to_string()];
+        assert!(!is_transient_failure(&lines));
+    }
+
+    #[test]
+    fn a_malformed_cargo_config_is_treated_as_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".cargo")).unwrap();
+        std::fs::write(dir.path().join(".cargo").join("config.toml"), "not valid toml =").unwrap();
+
+        let flags = inherited_rustflags(&dir.path().join("Cargo.toml"));
+        assert!(flags.is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Whether to emit ANSI color: the terminal spinner (see [`crate::ui`]) and
+//! every `cargo`/`rustc` invocation this crate shells out to (see
+//! [`crate::cargo`] and [`crate::test`]).
+//!
+//! Resolved once, from a `--color` flag, rather than re-checked at every
+//! print site; a subprocess is simply told which of `always`/`auto`/`never`
+//! to use itself (`Auto` is `cargo`'s and `rustc`'s own default anyway), so
+//! this crate's own output and the output it echoes from them always agree.
+
+use crate::error::*;
+
+/// A `--color` value: `always`, `auto` (the default), or `never`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    Always,
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorChoice {
+    /// Parse a `--color` value, e.g. `"always"`.
+    pub fn parse(value: &str) -> Result<ColorChoice> {
+        match value {
+            "always" => Ok(ColorChoice::Always),
+            "auto" => Ok(ColorChoice::Auto),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!("unsupported --color value '{}'; expected 'always', 'auto', or 'never'", other).into()),
+        }
+    }
+
+    /// The literal value `cargo`'s (and `rustc`'s) own `--color` flag
+    /// expects, for forwarding this crate's own choice straight through to
+    /// a subprocess instead of letting it fall back to its own `auto`
+    /// detection against a pipe.
+    pub fn as_cargo_arg(self) -> &'static str {
+        match self {
+            ColorChoice::Always => "always",
+            ColorChoice::Auto => "auto",
+            ColorChoice::Never => "never",
+        }
+    }
+
+    /// Whether this crate's own output (the terminal spinner, informational
+    /// lines) should be colored, resolving `Auto` against whether stderr
+    /// looks like a terminal.
+    ///
+    /// Only available with the `cli` feature, since resolving `Auto` needs
+    /// [`console`]'s terminal detection, and nothing outside the `rustdoc`
+    /// binary itself prints anything this would color.
+    #[cfg(feature = "cli")]
+    pub fn resolve_stderr(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => console::user_attended_stderr(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_valid_value() {
+        assert_eq!(ColorChoice::parse("always").unwrap(), ColorChoice::Always);
+        assert_eq!(ColorChoice::parse("auto").unwrap(), ColorChoice::Auto);
+        assert_eq!(ColorChoice::parse("never").unwrap(), ColorChoice::Never);
+    }
+
+    #[test]
+    fn rejects_an_unknown_value() {
+        assert!(ColorChoice::parse("rainbow").is_err());
+    }
+
+    #[test]
+    fn as_cargo_arg_round_trips_through_parse() {
+        for choice in [ColorChoice::Always, ColorChoice::Auto, ColorChoice::Never] {
+            assert_eq!(ColorChoice::parse(choice.as_cargo_arg()).unwrap(), choice);
+        }
+    }
+}
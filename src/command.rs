@@ -0,0 +1,183 @@
+//! A thin wrapper around `std::process::Command`.
+//!
+//! [`CommandBridge`] centralizes the error context and output handling
+//! common to every subprocess this crate runs; [`ProcessRunner`] is the
+//! injectable seam underneath it, so a test can hand back a canned process
+//! outcome instead of actually spawning anything (see
+//! [`testing::FakeProcessRunner`]), and an embedder can substitute its own
+//! implementation to sandbox or audit what actually gets shelled out to.
+//!
+//! Not every subprocess this crate runs goes through this seam yet.
+//! [`crate::cargo::generate_analysis`] streams `cargo check`'s stderr line
+//! by line as it runs, and [`crate::test::compile_tests`] lets `rustc`
+//! inherit the parent's stdout/stderr so a broken doc example's compile
+//! errors show up immediately; both need the child process's output as it
+//! happens, not just its result once it exits, so wrapping them here would
+//! need a streaming counterpart to [`ProcessRunner::run`], not just another
+//! implementation swapped in behind this one.
+
+use std::process::{Command, Output};
+
+use crate::error::*;
+
+/// Something that can run a [`Command`] to completion and hand back its
+/// output, standing in for `Command::output` itself. Swapping the
+/// implementation lets a test substitute a fake process outcome (see
+/// [`testing::FakeProcessRunner`]), or an embedder sandbox or audit what
+/// actually gets shelled out to.
+pub trait ProcessRunner {
+    fn run(&self, command: &mut Command) -> std::io::Result<Output>;
+}
+
+/// The default [`ProcessRunner`]: actually spawns `command` and waits for
+/// it, the way [`CommandBridge`] always used to.
+pub struct SystemProcessRunner;
+
+impl ProcessRunner for SystemProcessRunner {
+    fn run(&self, command: &mut Command) -> std::io::Result<Output> {
+        command.output()
+    }
+}
+
+/// A command to be run, along with the context needed to report a useful
+/// error if it fails.
+pub struct CommandBridge {
+    command: Command,
+    description: String,
+    runner: Box<dyn ProcessRunner>,
+}
+
+impl CommandBridge {
+    pub fn new(program: &str, description: &str) -> CommandBridge {
+        CommandBridge::with_runner(program, description, Box::new(SystemProcessRunner))
+    }
+
+    /// Like [`CommandBridge::new`], but running the command through `runner`
+    /// instead of always spawning it for real; see [`ProcessRunner`].
+    pub fn with_runner(program: &str, description: &str, runner: Box<dyn ProcessRunner>) -> CommandBridge {
+        CommandBridge {
+            command: Command::new(program),
+            description: description.to_string(),
+            runner,
+        }
+    }
+
+    pub fn arg(&mut self, arg: &str) -> &mut CommandBridge {
+        self.command.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut CommandBridge
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for arg in args {
+            self.command.arg(arg.as_ref());
+        }
+        self
+    }
+
+    pub fn env(&mut self, key: &str, value: &str) -> &mut CommandBridge {
+        self.command.env(key, value);
+        self
+    }
+
+    /// Run the command to completion, returning an error described by
+    /// `description` if it couldn't be spawned or exited unsuccessfully.
+    pub fn run(&mut self) -> Result<Output> {
+        let output = self
+            .runner
+            .run(&mut self.command)
+            .chain_err(|| format!("failed to run {}", self.description))?;
+
+        if !output.status.success() {
+            return Err(ErrorKind::Cargo(self.description.clone()).into());
+        }
+
+        Ok(output)
+    }
+}
+
+/// [`ProcessRunner`] test doubles shared across this crate's own
+/// `#[cfg(test)]` modules (e.g. [`crate::cargo`], [`crate::test`]), so a test
+/// can assert on what a function tried to run without actually spawning it.
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Command, ExitStatus, Output};
+    use std::rc::Rc;
+
+    use super::ProcessRunner;
+
+    /// A canned `(exit code, stdout)` outcome for one [`FakeProcessRunner`]
+    /// call.
+    type FakeOutcome = (i32, Vec<u8>);
+
+    /// A [`ProcessRunner`] that hands back a fixed queue of [`FakeOutcome`]s,
+    /// one per call (repeating a successful empty outcome once the queue
+    /// runs dry), and records every command's program and arguments so a
+    /// test can assert on what was actually run.
+    ///
+    /// Cloning shares the same recorded outcomes and invocations (they sit
+    /// behind an `Rc`), so a test can keep one handle to inspect while
+    /// handing the other to whatever takes ownership of a
+    /// `Box<dyn ProcessRunner>`.
+    #[derive(Clone, Default)]
+    pub(crate) struct FakeProcessRunner {
+        outcomes: Rc<RefCell<VecDeque<FakeOutcome>>>,
+        pub(crate) invocations: Rc<RefCell<Vec<Vec<String>>>>,
+    }
+
+    impl FakeProcessRunner {
+        pub(crate) fn new(outcomes: Vec<FakeOutcome>) -> FakeProcessRunner {
+            FakeProcessRunner { outcomes: Rc::new(RefCell::new(outcomes.into())), invocations: Rc::new(RefCell::new(Vec::new())) }
+        }
+    }
+
+    impl ProcessRunner for FakeProcessRunner {
+        fn run(&self, command: &mut Command) -> std::io::Result<Output> {
+            let mut argv = vec![command.get_program().to_string_lossy().into_owned()];
+            argv.extend(command.get_args().map(|arg| arg.to_string_lossy().into_owned()));
+            self.invocations.borrow_mut().push(argv);
+
+            let (code, stdout) = self.outcomes.borrow_mut().pop_front().unwrap_or((0, Vec::new()));
+            Ok(Output { status: ExitStatus::from_raw(code << 8), stdout, stderr: Vec::new() })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::FakeProcessRunner;
+    use super::*;
+
+    #[test]
+    fn a_successful_command_returns_its_output() {
+        let runner = FakeProcessRunner::new(vec![(0, b"hello".to_vec())]);
+        let mut bridge = CommandBridge::with_runner("echo", "echo hello", Box::new(runner));
+        let output = bridge.run().unwrap();
+        assert_eq!(output.stdout, b"hello");
+    }
+
+    #[test]
+    fn a_failing_command_errors_out_with_the_description() {
+        let runner = FakeProcessRunner::new(vec![(1, Vec::new())]);
+        let mut bridge = CommandBridge::with_runner("false", "false always fails", Box::new(runner));
+        let error = bridge.run().unwrap_err();
+        assert!(error.to_string().contains("false always fails"));
+    }
+
+    #[test]
+    fn records_the_program_and_arguments_it_was_asked_to_run() {
+        let runner = FakeProcessRunner::new(vec![(0, Vec::new())]);
+        let recorded = runner.clone();
+        let mut bridge = CommandBridge::with_runner("cargo", "cargo check", Box::new(runner));
+        bridge.arg("check").arg("--all-targets");
+        bridge.run().unwrap();
+
+        assert_eq!(recorded.invocations.borrow()[0], vec!["cargo", "check", "--all-targets"]);
+    }
+}
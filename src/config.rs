@@ -0,0 +1,250 @@
+//! User-facing configuration for a `rustdoc` invocation.
+
+use std::path::{Path, PathBuf};
+
+use crate::budget::Budget;
+use crate::color::ColorChoice;
+use crate::error::*;
+use crate::lock::LockPolicy;
+use crate::summary::DEFAULT_SUMMARY_LENGTH;
+
+/// Everything `rustdoc` needs to know in order to document a single crate.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Path to the `Cargo.toml` of the crate being documented.
+    pub manifest_path: PathBuf,
+
+    /// Directory generated documentation is written into.
+    output_path: PathBuf,
+
+    /// If set, any item's `docs` attribute longer than this many bytes is
+    /// truncated in `data.json`, with the full text written to a side file
+    /// under `output_path/docs` instead.
+    pub max_docs_size: Option<usize>,
+
+    /// If set, cap the number of items in `data.json`'s `included` at this
+    /// many, so a pathological crate can't produce a build that runs for
+    /// minutes and a JSON file too big to load. See
+    /// [`crate::json::limit_items`] for what gets kept.
+    pub max_items: Option<usize>,
+
+    /// Embed each item's source snippet (read from its span) into the
+    /// generated documentation, and render each referenced source file to a
+    /// browsable HTML page under `output_path/src`, with a `sourceHref`
+    /// attribute on every item pointing at its own line range. See
+    /// [`crate::source::embed_source_snippets`] and
+    /// [`crate::source_pages::build_source_pages`].
+    pub include_source: bool,
+
+    /// Record the wall-clock duration of each build phase, writing a
+    /// `timings.json` alongside the generated documentation.
+    pub timings: bool,
+
+    /// Compute per-item word count, doc-example count, and a readability
+    /// score, writing a `metrics.json` alongside the generated
+    /// documentation. See [`crate::metrics::compute_metrics`].
+    pub metrics: bool,
+
+    /// Suppress progress output, so only what a caller explicitly asks for
+    /// (e.g. documentation piped to stdout) shows up.
+    pub quiet: bool,
+
+    /// If set, every item is given a `links.self` URL rooted at this base,
+    /// and a `linkTemplates` entry is added to `meta` for each resource
+    /// type. See [`crate::links`].
+    pub base_url: Option<String>,
+
+    /// Echo every line of `cargo check`'s stderr while generating analysis,
+    /// instead of just the lines that look like cargo's own progress
+    /// output. Useful when a build fails in a way that doesn't match any
+    /// whitelisted verb.
+    pub show_cargo_output: bool,
+
+    /// Extra environment variables to pass through to `cargo check` while
+    /// generating analysis, for a build script that needs something set
+    /// (a native library path, a codegen setting) that this process doesn't
+    /// already have in its own environment.
+    pub check_env: Vec<(String, String)>,
+
+    /// Forward `--offline` to every `cargo` invocation this crate makes
+    /// (`cargo metadata`, `cargo check`, and the `cargo build` behind
+    /// [`crate::cargo::build_extern_crate`]), for a crate whose dependencies
+    /// are all vendored or otherwise already available without reaching the
+    /// network.
+    pub offline: bool,
+
+    /// Forward `--locked` to every `cargo` invocation this crate makes,
+    /// failing outright with [`crate::ErrorKind::LockfileDrift`] instead of
+    /// silently updating `Cargo.lock` when it's out of step with
+    /// `Cargo.toml`, so a documentation build in a release pipeline is
+    /// reproducible against the committed lockfile.
+    pub locked: bool,
+
+    /// Parse the crate's own entry file with `syn` and compare it against
+    /// the generated documentation, writing any item `syn` found but
+    /// analysis didn't to `completeness.json`. See [`crate::reconcile`].
+    pub check_completeness: bool,
+
+    /// How to behave when another build already holds the lock on
+    /// [`Config::output_path`]. See [`crate::lock`].
+    pub lock_policy: LockPolicy,
+
+    /// Render the crate's module hierarchy and item containment to
+    /// `modules.dot` and `modules.json`. See [`crate::module_graph`].
+    pub module_graph: bool,
+
+    /// Maximum length, in characters, of every item's `plainSummary`
+    /// attribute. See [`crate::summary`].
+    pub summary_length: usize,
+
+    /// Flag doc examples that still reference a `crate_name::...` path no
+    /// longer among the crate's own items, writing any found to
+    /// `stale-examples.json`. See [`crate::staleness`].
+    pub check_stale_examples: bool,
+
+    /// Compile a probe binary to record each non-generic struct/enum/union's
+    /// size and alignment in `layout.json`. `repr` attributes are always
+    /// captured regardless of this flag; only the compiled size/alignment
+    /// probe is gated on it, since it's a real (if small) extra build. See
+    /// [`crate::layout`].
+    pub layout: bool,
+
+    /// If set, restrict documentation to the module at this path under the
+    /// crate root (e.g. `"submodule::inner"`), rather than the whole crate.
+    /// See [`crate::json::create_documentation`].
+    pub root: Option<String>,
+
+    /// Stamp `meta.license` (the crate's license expression from
+    /// `Cargo.toml` plus a generation timestamp) and, when
+    /// [`Config::include_source`] is also set, the footer of every
+    /// generated HTML source page. See [`crate::license`].
+    pub stamp_license: bool,
+
+    /// If set, load save-analysis data from this directory instead of
+    /// running `cargo check` to generate it, for a pipeline where an
+    /// earlier CI stage (or another build system) already produced it with
+    /// `-Z save-analysis` enabled. See [`crate::cargo::AnalysisSession`].
+    pub analysis_dir: Option<PathBuf>,
+
+    /// Warn about doc comments that won't appear anywhere in the generated
+    /// documentation, because they're on an item kind
+    /// [`crate::json::create_documentation`] doesn't emit (e.g. a local, or
+    /// a constant). Off by default since
+    /// most crates have at least a few of these and the warning is purely
+    /// diagnostic.
+    pub verbose: bool,
+
+    /// If set, pipe the fully post-processed documentation through this
+    /// shell command, replacing it with whatever JSON the command prints to
+    /// stdout, so an organization can inject custom attributes, strip
+    /// internal modules, or rewrite links without forking this crate. Runs
+    /// last, after every other post-processing option. See
+    /// [`crate::post_process`].
+    pub post_process: Option<String>,
+
+    /// Render a `docs NN%` SVG badge to `coverage-badge.svg`, from the same
+    /// per-item `docs` check [`crate::empty::detect`] uses, so a project can
+    /// embed a live documentation-coverage badge in its README from a CI
+    /// artifact. See [`crate::coverage_badge`].
+    pub coverage_badge: bool,
+
+    /// Size/count thresholds to warn about (or, with
+    /// [`crate::budget::Budget::deny`], fail the build over) when exceeded,
+    /// so a team notices a documentation payload getting too big to load
+    /// quickly before it ships. Every threshold defaults to unset, i.e. not
+    /// checked. See [`crate::budget`].
+    pub budget: Budget,
+
+    /// Dump the raw analysis def tree (id, kind, qualname, parent, span) to
+    /// `analysis-debug.json`, before [`crate::json::create_documentation`]
+    /// filters and reshapes it, for diagnosing a "why isn't my item
+    /// showing up" report or developing a new generation feature. See
+    /// [`crate::analysis_debug`].
+    pub analysis_debug: bool,
+
+    /// Item paths to leave out of generated documentation, along with
+    /// everything nested under them, even though they're `pub`. An item
+    /// can also exclude itself this way from its own doc comment, with an
+    /// `<!-- rustdoc:skip -->` marker. See [`crate::exclude`].
+    pub exclude: Vec<String>,
+
+    /// Print a summary of how many times, and how long in total, this build
+    /// spent in the `rls_analysis` queries [`crate::json::create_documentation`]
+    /// and [`crate::analysis_debug::dump`] make repeatedly while walking a
+    /// crate. A hidden developer flag: real numbers on a large crate, not
+    /// something an end user needs. See [`crate::analysis_stats`].
+    pub debug_analysis_stats: bool,
+
+    /// Whether to emit ANSI color in the terminal spinner and every `cargo`/
+    /// `rustc` invocation this crate shells out to, forwarded to each of
+    /// them as their own `--color` flag so their echoed output always
+    /// agrees with our own. See [`crate::color`].
+    pub color: ColorChoice,
+}
+
+impl Config {
+    /// Create a `Config` from a manifest path, defaulting the output path to
+    /// `<crate>/target/doc`.
+    pub fn new(manifest_path: PathBuf) -> Result<Config> {
+        let output_path = manifest_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("target")
+            .join("doc");
+
+        Ok(Config {
+            manifest_path,
+            output_path,
+            max_docs_size: None,
+            max_items: None,
+            include_source: false,
+            timings: false,
+            metrics: false,
+            quiet: false,
+            base_url: None,
+            show_cargo_output: false,
+            check_env: Vec::new(),
+            offline: false,
+            locked: false,
+            check_completeness: false,
+            lock_policy: LockPolicy::Fail,
+            module_graph: false,
+            summary_length: DEFAULT_SUMMARY_LENGTH,
+            check_stale_examples: false,
+            layout: false,
+            root: None,
+            stamp_license: false,
+            analysis_dir: None,
+            verbose: false,
+            post_process: None,
+            coverage_badge: false,
+            budget: Budget::default(),
+            analysis_debug: false,
+            exclude: Vec::new(),
+            debug_analysis_stats: false,
+            color: ColorChoice::Auto,
+        })
+    }
+
+    /// The directory documentation artifacts are written to.
+    pub fn output_path(&self) -> &Path {
+        &self.output_path
+    }
+
+    /// Override the default output directory.
+    pub fn set_output_path(&mut self, output_path: PathBuf) {
+        self.output_path = output_path;
+    }
+
+    /// Open the generated documentation in the user's browser. See
+    /// [`crate::browser::open_docs`] for what `browser` and `print_path` do.
+    ///
+    /// Only available with the (default) `cli` feature, since it's the one
+    /// place the JSON-generating core reaches for a browser or terminal UI
+    /// dependency; a consumer building against `--no-default-features`
+    /// (e.g. a docs web service embedding this crate) never pulls those in.
+    #[cfg(feature = "cli")]
+    pub fn open_docs(&self, browser: Option<&str>, print_path: bool) -> Result<()> {
+        crate::browser::open_docs(&self.output_path, browser, print_path)
+    }
+}
@@ -0,0 +1,121 @@
+//! Rendering a shields.io-style `docs NN%` SVG badge from a crate's
+//! documentation coverage, so a project can embed a live badge in its
+//! README from a CI-produced artifact.
+//!
+//! The percentage counts the same items [`crate::empty::has_docs`] already
+//! knows how to check (the crate root plus everything in `included`), so
+//! this badge's number can't drift from what [`crate::empty::detect`]
+//! considers "has docs at all".
+
+use crate::empty::has_docs;
+use crate::json::Documentation;
+
+/// The percentage, rounded down, of `documentation`'s items (the crate root
+/// plus everything in `included`) with a non-empty `docs` attribute. `0` for
+/// a crate with no items at all.
+pub fn coverage_percentage(documentation: &Documentation) -> u32 {
+    let items: Vec<_> = std::iter::once(&documentation.data).chain(documentation.included.iter()).collect();
+    if items.is_empty() {
+        return 0;
+    }
+
+    let documented = items.iter().filter(|data| has_docs(data)).count();
+    (documented * 100 / items.len()) as u32
+}
+
+/// The badge color shields.io itself uses for a coverage-style percentage:
+/// green at 90%+, tapering down to red below 50%.
+fn badge_color(percentage: u32) -> &'static str {
+    match percentage {
+        90..=100 => "#4c1",
+        70..=89 => "#97ca00",
+        50..=69 => "#dfb317",
+        _ => "#e05d44",
+    }
+}
+
+/// Render a `docs NN%` badge as a standalone SVG document, in the same
+/// layout shields.io's own static badges use (a gray label half, a colored
+/// value half, sized to fit each half's text).
+pub fn render_svg(percentage: u32) -> String {
+    let label = "docs";
+    let value = format!("{}%", percentage);
+    let color = badge_color(percentage);
+
+    let label_width = 6 + label.len() as u32 * 7;
+    let value_width = 6 + value.len() as u32 * 7;
+    let total_width = label_width + value_width;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="20" role="img" aria-label="{label}: {value}">
+  <rect width="{total_width}" height="20" rx="3" fill="#555"/>
+  <rect x="{label_width}" width="{value_width}" height="20" rx="3" fill="{color}"/>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{value_x}" y="14">{value}</text>
+  </g>
+</svg>
+"##,
+        total_width = total_width,
+        label_width = label_width,
+        value_width = value_width,
+        color = color,
+        label_x = label_width / 2,
+        value_x = label_width + value_width / 2,
+        label = label,
+        value = value,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn data(docs: Option<&str>) -> Data {
+        let mut attributes = HashMap::new();
+        if let Some(docs) = docs {
+            attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        }
+        Data { id: "item".to_string(), ty: "struct".to_string(), attributes, relationships: None, links: None }
+    }
+
+    fn documentation(root_docs: Option<&str>, included: Vec<Data>) -> Documentation {
+        Documentation { data: data(root_docs), included, meta: HashMap::new(), links: None }
+    }
+
+    #[test]
+    fn a_crate_with_no_items_has_zero_percent_coverage() {
+        let documentation = documentation(None, Vec::new());
+        assert_eq!(coverage_percentage(&documentation), 0);
+    }
+
+    #[test]
+    fn a_fully_documented_crate_has_full_coverage() {
+        let documentation = documentation(Some("The crate."), vec![data(Some("A thing."))]);
+        assert_eq!(coverage_percentage(&documentation), 100);
+    }
+
+    #[test]
+    fn coverage_is_the_share_of_items_with_docs() {
+        let documentation = documentation(None, vec![data(Some("A thing.")), data(None), data(None)]);
+        assert_eq!(coverage_percentage(&documentation), 25);
+    }
+
+    #[test]
+    fn the_badge_color_tapers_from_green_to_red() {
+        assert_eq!(badge_color(100), "#4c1");
+        assert_eq!(badge_color(75), "#97ca00");
+        assert_eq!(badge_color(55), "#dfb317");
+        assert_eq!(badge_color(10), "#e05d44");
+    }
+
+    #[test]
+    fn the_rendered_svg_includes_the_percentage_and_is_well_formed() {
+        let svg = render_svg(87);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("87%"));
+        assert!(svg.contains("docs"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Detecting methods reachable through `Deref` coercion in a crate's own
+//! source, the way rustdoc's own "Methods from Deref&lt;Target=U&gt;"
+//! section does.
+//!
+//! What it finds is surfaced as a `meta.derefMethods` list, the same way
+//! [`crate::blanket_impls`] does for blanket impls. No `derefMethods`
+//! relationship is added to the type's own [`crate::json::Data`] node, even
+//! where the target's methods have method nodes of their own (see
+//! `walk_impls` in [`crate::json`]).
+//!
+//! Detected structurally with `syn`, not through real trait resolution: a
+//! `Deref` impl and target are only recognized when both the `Self` type
+//! and `Target` type are bare identifiers (no generics, no paths), and only
+//! `pub fn`s from a matching bare `impl SelfType { ... }` block are counted
+//! as methods. A `Target` naming a type from another crate contributes
+//! nothing, since there's no source to look its methods up in.
+//!
+//! `Target` is followed transitively (`A` derefs to `B` derefs to `C`
+//! contributes `C`'s methods to `A` too), stopping as soon as a type
+//! already seen in the chain comes up again, so a `Deref` cycle (accidental
+//! or, in a test fixture, deliberate) can't loop forever.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Serialize;
+use syn::visit::{self, Visit};
+
+use crate::error::*;
+
+/// The public methods one type gains through `Deref` coercion, following
+/// the whole chain (see the module docs).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DerefMethods {
+    /// The type that derefs to something else, e.g. `"Wrapper"`.
+    pub self_type: String,
+    /// Every method name reachable through the deref chain, in the order
+    /// each `Target` was reached.
+    pub methods: Vec<String>,
+}
+
+/// The bare identifier a type resolves to, if it's just `Name` (no path
+/// segments, no generic arguments) rather than something like `Box<T>` or
+/// `other::Name`.
+fn as_bare_ident(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() && type_path.path.segments.len() == 1 => {
+            let segment = &type_path.path.segments[0];
+            matches!(segment.arguments, syn::PathArguments::None).then(|| segment.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct DerefCollector {
+    /// `Self` type name -> `Target` type name, one entry per `impl Deref`.
+    deref_targets: HashMap<String, String>,
+    /// Type name -> its `pub fn` method names, one entry per bare inherent
+    /// impl block (later blocks for the same type append to the same list).
+    inherent_methods: HashMap<String, Vec<String>>,
+}
+
+impl<'ast> Visit<'ast> for DerefCollector {
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let self_type = as_bare_ident(&node.self_ty);
+
+        match (&node.trait_, &self_type) {
+            (Some((_, trait_path, _)), Some(self_type)) if trait_path.segments.last().is_some_and(|s| s.ident == "Deref") => {
+                let target = node.items.iter().find_map(|item| match item {
+                    syn::ImplItem::Type(assoc_type) if assoc_type.ident == "Target" => as_bare_ident(&assoc_type.ty),
+                    _ => None,
+                });
+                if let Some(target) = target {
+                    self.deref_targets.insert(self_type.clone(), target);
+                }
+            }
+            (None, Some(self_type)) => {
+                let methods = self.inherent_methods.entry(self_type.clone()).or_default();
+                for item in &node.items {
+                    if let syn::ImplItem::Fn(method) = item {
+                        if matches!(method.vis, syn::Visibility::Public(_)) {
+                            methods.push(method.sig.ident.to_string());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        visit::visit_item_impl(self, node);
+    }
+}
+
+/// Parse `entry_path` with `syn` and return every type's [`DerefMethods`],
+/// skipping any type whose deref chain contributes no methods at all.
+pub fn find_deref_methods(entry_path: &Path) -> Result<Vec<DerefMethods>> {
+    let source = fs::read_to_string(entry_path).chain_err(|| format!("failed to read '{}'", entry_path.display()))?;
+    let file = syn::parse_file(&source).chain_err(|| format!("failed to parse '{}' with syn", entry_path.display()))?;
+
+    let mut collector = DerefCollector::default();
+    collector.visit_file(&file);
+
+    let mut results = Vec::new();
+    for self_type in collector.deref_targets.keys() {
+        let mut methods = Vec::new();
+        let mut seen = HashSet::new();
+        seen.insert(self_type.clone());
+
+        let mut current = self_type.clone();
+        while let Some(target) = collector.deref_targets.get(&current) {
+            if !seen.insert(target.clone()) {
+                break;
+            }
+            if let Some(target_methods) = collector.inherent_methods.get(target) {
+                for method in target_methods {
+                    if !methods.contains(method) {
+                        methods.push(method.clone());
+                    }
+                }
+            }
+            current = target.clone();
+        }
+
+        if !methods.is_empty() {
+            results.push(DerefMethods { self_type: self_type.clone(), methods });
+        }
+    }
+
+    results.sort_by(|a, b| a.self_type.cmp(&b.self_type));
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(source: &str) -> Vec<DerefMethods> {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, source).unwrap();
+        find_deref_methods(&entry_path).unwrap()
+    }
+
+    #[test]
+    fn a_deref_impls_target_methods_are_reachable() {
+        let methods = find(
+            "struct Wrapper;\nstruct Inner;\nimpl std::ops::Deref for Wrapper { type Target = Inner; fn deref(&self) -> &Inner { unimplemented!() } }\nimpl Inner { pub fn greet(&self) {} }\n",
+        );
+
+        assert_eq!(methods, vec![DerefMethods { self_type: "Wrapper".to_string(), methods: vec!["greet".to_string()] }]);
+    }
+
+    #[test]
+    fn private_methods_are_not_counted() {
+        let methods = find(
+            "struct Wrapper;\nstruct Inner;\nimpl std::ops::Deref for Wrapper { type Target = Inner; fn deref(&self) -> &Inner { unimplemented!() } }\nimpl Inner { fn hidden(&self) {} }\n",
+        );
+
+        assert!(methods.is_empty());
+    }
+
+    #[test]
+    fn a_type_with_no_deref_impl_has_no_deref_methods() {
+        let methods = find("struct Widget;\nimpl Widget { pub fn greet(&self) {} }\n");
+        assert!(methods.is_empty());
+    }
+
+    #[test]
+    fn a_deref_chain_is_followed_transitively() {
+        let methods = find(
+            "struct A;\nstruct B;\nstruct C;\n\
+             impl std::ops::Deref for A { type Target = B; fn deref(&self) -> &B { unimplemented!() } }\n\
+             impl std::ops::Deref for B { type Target = C; fn deref(&self) -> &C { unimplemented!() } }\n\
+             impl B { pub fn from_b(&self) {} }\n\
+             impl C { pub fn from_c(&self) {} }\n",
+        );
+
+        let a = methods.iter().find(|m| m.self_type == "A").unwrap();
+        assert_eq!(a.methods, vec!["from_b".to_string(), "from_c".to_string()]);
+    }
+
+    #[test]
+    fn a_deref_cycle_does_not_loop_forever() {
+        let methods = find(
+            "struct A;\nstruct B;\n\
+             impl std::ops::Deref for A { type Target = B; fn deref(&self) -> &B { unimplemented!() } }\n\
+             impl std::ops::Deref for B { type Target = A; fn deref(&self) -> &A { unimplemented!() } }\n\
+             impl B { pub fn from_b(&self) {} }\n",
+        );
+
+        let a = methods.iter().find(|m| m.self_type == "A").unwrap();
+        assert_eq!(a.methods, vec!["from_b".to_string()]);
+    }
+
+    #[test]
+    fn a_target_from_another_crate_contributes_nothing() {
+        let methods = find(
+            "struct Wrapper;\nimpl std::ops::Deref for Wrapper { type Target = String; fn deref(&self) -> &String { unimplemented!() } }\n",
+        );
+        assert!(methods.is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Comparing two builds' [`Documentation`] to report what changed between
+//! them, e.g. for the "since last build" summary `rustdoc build` prints to
+//! stderr when it's about to overwrite an existing `data.json`.
+
+use std::collections::HashMap;
+
+use crate::json::{Data, Documentation};
+
+/// How a single item differs between an old and a new [`Documentation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single item's change, identified the same way [`Data::id`] already is.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub id: String,
+    pub ty: String,
+    pub name: Option<String>,
+    pub kind: ChangeKind,
+}
+
+fn item_name(data: &Data) -> Option<String> {
+    data.attributes.get("name").and_then(|v| v.as_str()).map(str::to_string)
+}
+
+fn by_id(documentation: &Documentation) -> HashMap<&str, &Data> {
+    documentation.included.iter().map(|data| (data.id.as_str(), data)).collect()
+}
+
+/// Diff two builds' documentation, returning every item added, removed, or
+/// changed in `new_docs.included` relative to `old_docs.included`. `data`
+/// (the crate root item itself) isn't compared, since a crate never stops
+/// describing itself between builds.
+///
+/// An item counts as "changed" if any of its `attributes` differ, so a
+/// signature edit, a doc comment tweak, or a renamed parameter are each
+/// reported; a change to `relationships` or `links` alone (e.g. from
+/// [`crate::json::limit_items`] truncating a different set of items on a
+/// later build) is not.
+pub fn diff_documentation(old_docs: &Documentation, new_docs: &Documentation) -> Vec<Change> {
+    let old_items = by_id(old_docs);
+    let new_items = by_id(new_docs);
+
+    let mut changes = Vec::new();
+
+    for (id, data) in &new_items {
+        match old_items.get(id) {
+            None => changes.push(Change {
+                id: (*id).to_string(),
+                ty: data.ty.clone(),
+                name: item_name(data),
+                kind: ChangeKind::Added,
+            }),
+            Some(old_data) if old_data.attributes != data.attributes => changes.push(Change {
+                id: (*id).to_string(),
+                ty: data.ty.clone(),
+                name: item_name(data),
+                kind: ChangeKind::Changed,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (id, data) in &old_items {
+        if !new_items.contains_key(id) {
+            changes.push(Change {
+                id: (*id).to_string(),
+                ty: data.ty.clone(),
+                name: item_name(data),
+                kind: ChangeKind::Removed,
+            });
+        }
+    }
+
+    changes
+}
+
+/// A short human-readable summary of `changes`, e.g. `"3 added, 1 removed, 2
+/// changed"`. Empty when there are no changes at all.
+pub fn summarize(changes: &[Change]) -> String {
+    let added = changes.iter().filter(|change| change.kind == ChangeKind::Added).count();
+    let removed = changes.iter().filter(|change| change.kind == ChangeKind::Removed).count();
+    let changed = changes.iter().filter(|change| change.kind == ChangeKind::Changed).count();
+
+    let mut parts = Vec::new();
+    if added > 0 {
+        parts.push(format!("{} added", added));
+    }
+    if removed > 0 {
+        parts.push(format!("{} removed", removed));
+    }
+    if changed > 0 {
+        parts.push(format!("{} changed", changed));
+    }
+
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn data(id: &str, name: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), Value::String(name.to_string()));
+        Data {
+            id: id.to_string(),
+            ty: "function".to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    fn documentation(items: Vec<Data>) -> Documentation {
+        Documentation {
+            data: data("crate", "crate"),
+            included: items,
+            meta: HashMap::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_an_added_item() {
+        let old = documentation(vec![]);
+        let new = documentation(vec![data("a", "foo")]);
+
+        let changes = diff_documentation(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Added);
+        assert_eq!(changes[0].id, "a");
+    }
+
+    #[test]
+    fn detects_a_removed_item() {
+        let old = documentation(vec![data("a", "foo")]);
+        let new = documentation(vec![]);
+
+        let changes = diff_documentation(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Removed);
+    }
+
+    #[test]
+    fn detects_a_changed_item() {
+        let old = documentation(vec![data("a", "foo")]);
+        let new = documentation(vec![data("a", "bar")]);
+
+        let changes = diff_documentation(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+    }
+
+    #[test]
+    fn an_identical_item_is_not_a_change() {
+        let old = documentation(vec![data("a", "foo")]);
+        let new = documentation(vec![data("a", "foo")]);
+
+        assert!(diff_documentation(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn summarize_lists_only_nonzero_categories() {
+        let changes = vec![
+            Change {
+                id: "a".to_string(),
+                ty: "function".to_string(),
+                name: None,
+                kind: ChangeKind::Added,
+            },
+            Change {
+                id: "b".to_string(),
+                ty: "function".to_string(),
+                name: None,
+                kind: ChangeKind::Added,
+            },
+        ];
+
+        assert_eq!(summarize(&changes), "2 added");
+    }
+
+    #[test]
+    fn summarize_is_empty_with_no_changes() {
+        assert_eq!(summarize(&[]), "");
+    }
+}
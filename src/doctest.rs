@@ -0,0 +1,131 @@
+//! Loading crate-level doc test settings from `doctest.toml`, the fallback
+//! for `#![doc(test(...))]` attributes.
+//!
+//! `rls_analysis::Def` (0.18.3) doesn't expose attribute data at all (the
+//! same gap noted on [`crate::json::parse_doc_cfg`] and
+//! [`crate::redirects::load_redirects`]), so `#![doc(test(no_crate_inject))]`
+//! and `#![doc(test(attr(...)))]` can't be read from analysis data yet.
+//! Until they can, the same settings are read from an explicit
+//! `doctest.toml` file at the crate root.
+
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Deserialize;
+
+use crate::error::*;
+
+/// Crate-level settings for compiling doc test examples.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DoctestConfig {
+    /// Equivalent to `#![doc(test(no_crate_inject))]`: don't automatically
+    /// bring the crate itself into scope for every example.
+    ///
+    /// Nothing in `test::compile_tests` injects the crate into scope yet
+    /// (examples are compiled standalone, with no `--extern` wiring back to
+    /// the crate under test), so this flag has no effect until that's
+    /// added; it's read now so a crate that opts out ahead of time doesn't
+    /// need to touch `doctest.toml` again once it does.
+    #[serde(default)]
+    pub no_crate_inject: bool,
+
+    /// Equivalent to `#![doc(test(attr(...)))]`: extra inner attributes
+    /// (e.g. `"deny(warnings)"`) prepended to every compiled example.
+    #[serde(default)]
+    pub attrs: Vec<String>,
+
+    /// Names of dev crates (already a dependency of the crate being
+    /// documented, e.g. `tokio` or `serde_json`) to build and pass to every
+    /// compiled example via `--extern`, so an async crate's examples don't
+    /// each have to spell out their own runtime boilerplate. See
+    /// [`crate::cargo::build_extern_crate`] for how each one is built.
+    #[serde(default)]
+    pub inject: Vec<String>,
+
+    /// The crate being documented is `#![no_std]`: examples are never
+    /// wrapped in the default `Result<(), Box<dyn std::error::Error>>`
+    /// `main` a `?`-using example would otherwise get (see
+    /// [`crate::test::preprocess`]), since `std::error::Error` isn't
+    /// available to reach for.
+    #[serde(default)]
+    pub no_std: bool,
+
+    /// Extra source prepended verbatim before every compiled example when
+    /// [`DoctestConfig::no_std`] is set, e.g. a `#[panic_handler]` and
+    /// whatever entry point the crate's target platform needs, since this
+    /// crate has no reasonable default of its own to supply one. Ignored
+    /// when `no_std` isn't set.
+    #[serde(default)]
+    pub harness: Option<String>,
+}
+
+/// Load `doctest.toml` from `crate_root`, if present. A missing file isn't
+/// an error: most crates don't have one, and get the default settings.
+pub fn load_doctest_config(crate_root: &Path) -> Result<DoctestConfig> {
+    let path = crate_root.join("doctest.toml");
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(DoctestConfig::default()),
+    };
+
+    toml::from_str(&contents).chain_err(|| format!("failed to parse '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_settings_from_the_crate_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("doctest.toml"),
+            "no_crate_inject = true\nattrs = [\"deny(warnings)\"]\n",
+        )
+        .unwrap();
+
+        let config = load_doctest_config(dir.path()).unwrap();
+        assert!(config.no_crate_inject);
+        assert_eq!(config.attrs, vec!["deny(warnings)".to_string()]);
+        assert!(config.inject.is_empty());
+    }
+
+    #[test]
+    fn loads_injected_crates_from_the_crate_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("doctest.toml"), "inject = [\"tokio\", \"serde_json\"]\n").unwrap();
+
+        let config = load_doctest_config(dir.path()).unwrap();
+        assert_eq!(config.inject, vec!["tokio".to_string(), "serde_json".to_string()]);
+    }
+
+    #[test]
+    fn missing_file_is_the_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_doctest_config(dir.path()).unwrap();
+        assert!(!config.no_crate_inject);
+        assert!(config.attrs.is_empty());
+    }
+
+    #[test]
+    fn loads_no_std_and_harness_from_the_crate_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("doctest.toml"),
+            "no_std = true\nharness = \"#[panic_handler]\\nfn panic(_: &core::panic::PanicInfo) -> ! { loop {} }\"\n",
+        )
+        .unwrap();
+
+        let config = load_doctest_config(dir.path()).unwrap();
+        assert!(config.no_std);
+        assert!(config.harness.unwrap().contains("panic_handler"));
+    }
+
+    #[test]
+    fn malformed_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("doctest.toml"), "not valid toml =").unwrap();
+        assert!(load_doctest_config(dir.path()).is_err());
+    }
+}
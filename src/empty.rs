@@ -0,0 +1,90 @@
+//! Detecting a crate whose generated documentation has nothing useful in
+//! it, so `meta.empty` can flag it instead of leaving a frontend to render
+//! a blank page with no explanation.
+
+use serde_derive::Serialize;
+
+use crate::json::Documentation;
+
+/// Why [`detect`] considered a crate's documentation empty.
+///
+/// There's no `NoPublicItems` variant: `rls_analysis::Def` (see
+/// [`crate::json::limit_items`]'s doc comment) carries no visibility field,
+/// so this crate can't yet tell a `pub` item from a private one, only
+/// whether analysis found anything at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Reason {
+    /// `included` has nothing in it: analysis found no items at all.
+    NoItems,
+    /// The crate has items, but none of them (nor the crate root itself)
+    /// have a doc comment.
+    NoDocs,
+}
+
+pub(crate) fn has_docs(data: &crate::json::Data) -> bool {
+    data.attributes
+        .get("docs")
+        .and_then(|value| value.as_str())
+        .map(|docs| !docs.trim().is_empty())
+        .unwrap_or(false)
+}
+
+/// Whether `documentation` is empty, and why. `None` when it has at least
+/// one item with docs.
+pub fn detect(documentation: &Documentation) -> Option<Reason> {
+    if documentation.included.is_empty() {
+        return Some(Reason::NoItems);
+    }
+
+    let any_docs = std::iter::once(&documentation.data).chain(documentation.included.iter()).any(has_docs);
+
+    if !any_docs {
+        return Some(Reason::NoDocs);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn data(ty: &str, docs: Option<&str>) -> Data {
+        let mut attributes = HashMap::new();
+        if let Some(docs) = docs {
+            attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        }
+        Data { id: ty.to_string(), ty: ty.to_string(), attributes, relationships: None, links: None }
+    }
+
+    #[test]
+    fn a_crate_with_no_included_items_has_no_items() {
+        let documentation = Documentation { data: data("crate", None), included: Vec::new(), meta: HashMap::new(), links: None };
+        assert_eq!(detect(&documentation), Some(Reason::NoItems));
+    }
+
+    #[test]
+    fn a_crate_with_items_but_no_docs_anywhere_has_no_docs() {
+        let documentation = Documentation {
+            data: data("crate", None),
+            included: vec![data("struct", None), data("struct", Some("   "))],
+            meta: HashMap::new(),
+            links: None,
+        };
+        assert_eq!(detect(&documentation), Some(Reason::NoDocs));
+    }
+
+    #[test]
+    fn a_crate_with_at_least_one_doc_comment_is_not_empty() {
+        let documentation = Documentation {
+            data: data("crate", None),
+            included: vec![data("struct", Some("Does a thing."))],
+            meta: HashMap::new(),
+            links: None,
+        };
+        assert_eq!(detect(&documentation), None);
+    }
+}
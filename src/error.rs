@@ -0,0 +1,63 @@
+//! The error types used throughout the crate.
+//!
+//! We use `error-chain` so that lower-level errors (`io::Error`,
+//! `serde_json::Error`, cargo's own errors) can be wrapped with the
+//! higher-level context that actually helps a user understand what
+//! `rustdoc` was doing when things went wrong.
+
+error_chain! {
+    foreign_links {
+        Io(::std::io::Error);
+        Json(::serde_json::Error);
+    }
+
+    errors {
+        /// The requested crate could not be found in the analysis data.
+        CrateErr(crate_name: String) {
+            description("crate not found")
+            display("crate '{}' not found in analysis data", crate_name)
+        }
+
+        /// `cargo` (or `rustc`) exited with a non-zero status.
+        Cargo(command: String) {
+            description("cargo command failed")
+            display("`{}` did not run successfully", command)
+        }
+
+        /// Save-analysis data could not be loaded.
+        Analysis(reason: String) {
+            description("analysis error")
+            display("failed to load analysis data: {}", reason)
+        }
+
+        /// A compiled doc test binary exited with a non-zero status.
+        DocTest(binary: String) {
+            description("doc test failed")
+            display("doc test '{}' failed", binary)
+        }
+
+        /// `cargo` refused to run with `--locked` because `Cargo.lock` is
+        /// out of date with `Cargo.toml`.
+        LockfileDrift(detail: String) {
+            description("Cargo.lock is out of date")
+            display(
+                "documentation build is not reproducible: Cargo.lock is out of date with Cargo.toml.\n{}\nrun `cargo update` (or `cargo check`) without `--locked` to refresh it, then commit the result",
+                detail
+            )
+        }
+
+        /// `cargo check` ran successfully, but no save-analysis JSON turned
+        /// up where we told it to write it, so there's nothing to
+        /// `reload()`. Reported separately from [`ErrorKind::CrateErr`]
+        /// (which means analysis exists but not for the crate we asked
+        /// about), since the fix here is usually toolchain- or
+        /// build-related rather than a naming mismatch.
+        EmptyAnalysis(dir: ::std::path::PathBuf) {
+            description("no analysis data produced")
+            display(
+                "no save-analysis data found under '{}'; check that the crate (and any build script) compiled cleanly, and that this toolchain still supports `-Z save-analysis`",
+                dir.display()
+            )
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Building the `examples.json` artifact: an index of a crate's `examples/`
+//! directory, so a frontend can render an Examples tab without cloning the
+//! crate to read the files itself.
+//!
+//! Discovery goes through cargo metadata's example targets (rather than
+//! just listing `examples/*.rs`), so an example that's disabled behind
+//! `required-features` is still indexed, and its features are known.
+
+use std::fs;
+
+use cargo_metadata::Package;
+use serde_derive::Serialize;
+
+use crate::error::*;
+
+/// One `examples/` file, ready to be listed in a frontend's Examples tab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExampleEntry {
+    pub name: String,
+    pub file: String,
+    pub docs: String,
+    #[serde(rename = "requiredFeatures")]
+    pub required_features: Vec<String>,
+}
+
+/// Index every `examples/` target in `package`, pairing each with its
+/// top-level (`//!`) doc comment and the features it requires.
+///
+/// A target whose source file can't be read (removed since `cargo metadata`
+/// ran, say) is skipped rather than failing the whole index.
+pub fn build_example_index(package: &Package) -> Result<Vec<ExampleEntry>> {
+    let mut entries = Vec::new();
+
+    for target in &package.targets {
+        if !target.kind.iter().any(|kind| kind == "example") {
+            continue;
+        }
+
+        let contents = match fs::read_to_string(&target.src_path) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+
+        entries.push(ExampleEntry {
+            name: target.name.clone(),
+            file: target.src_path.to_string(),
+            docs: leading_doc_comment(&contents),
+            required_features: target.required_features.clone(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// The file's leading run of `//!` lines, with the `//!` marker and one
+/// space of indentation stripped, joined back into a single string.
+fn leading_doc_comment(contents: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in contents.lines() {
+        match line.trim_start().strip_prefix("//!") {
+            Some(rest) => lines.push(rest.strip_prefix(' ').unwrap_or(rest)),
+            None => break,
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_with_example(dir: &std::path::Path, contents: &str, required_features: &[&str]) -> Package {
+        let src_path = dir.join("examples").join("basic.rs");
+        fs::create_dir_all(src_path.parent().unwrap()).unwrap();
+        fs::write(&src_path, contents).unwrap();
+
+        serde_json::from_value(serde_json::json!({
+            "name": "test-crate",
+            "version": "0.1.0",
+            "id": "test-crate 0.1.0 (path+file:///tmp/test-crate)",
+            "dependencies": [],
+            "targets": [
+                {
+                    "name": "basic",
+                    "kind": ["example"],
+                    "src_path": src_path,
+                    "required-features": required_features,
+                },
+            ],
+            "features": {},
+            "manifest_path": dir.join("Cargo.toml"),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn indexes_an_example_with_a_doc_comment_and_required_features() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = package_with_example(
+            dir.path(),
+            "//! Demonstrates the basic API.\n//! Second line.\n\nfn main() {}\n",
+            &["extra"],
+        );
+
+        let entries = build_example_index(&package).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "basic");
+        assert_eq!(entries[0].docs, "Demonstrates the basic API.\nSecond line.");
+        assert_eq!(entries[0].required_features, vec!["extra".to_string()]);
+    }
+
+    #[test]
+    fn an_example_without_a_doc_comment_has_empty_docs() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = package_with_example(dir.path(), "fn main() {}\n", &[]);
+
+        let entries = build_example_index(&package).unwrap();
+
+        assert_eq!(entries[0].docs, "");
+    }
+
+    #[test]
+    fn non_example_targets_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src").join("main.rs");
+        fs::create_dir_all(src_path.parent().unwrap()).unwrap();
+        fs::write(&src_path, "fn main() {}\n").unwrap();
+
+        let package: Package = serde_json::from_value(serde_json::json!({
+            "name": "test-crate",
+            "version": "0.1.0",
+            "id": "test-crate 0.1.0 (path+file:///tmp/test-crate)",
+            "dependencies": [],
+            "targets": [
+                {"name": "test-crate", "kind": ["bin"], "src_path": src_path},
+            ],
+            "features": {},
+            "manifest_path": dir.path().join("Cargo.toml"),
+        }))
+        .unwrap();
+
+        assert!(build_example_index(&package).unwrap().is_empty());
+    }
+}
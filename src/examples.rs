@@ -0,0 +1,214 @@
+//! Extracting the fenced ```rust code blocks embedded in an item's doc
+//! comments.
+//!
+//! Each block is extracted in two forms: `displayed`, with rustdoc's `# `
+//! hidden-line convention applied (what a reader sees when the example
+//! renders), and `compiled`, with those lines kept (since they usually
+//! provide setup the reader isn't meant to see, but the compiler still
+//! needs). [`crate::json::build_data`] uses the former for an item's
+//! `examples` attribute; [`crate::test::find_tests`] uses the latter to
+//! actually run the example.
+//!
+//! [`find_examples`] is a plain line-based scanner rather than a real
+//! Markdown parser, so besides the fixed cases in `tests` it's also
+//! exercised against arbitrary input by the `proptest` module below: it
+//! should never panic, on CRLF or otherwise pathological docs (nested
+//! fences, an unterminated block). An unterminated fence (docs cut off
+//! mid-block) is simply dropped rather than treated as an example, since
+//! there's no closing fence to know where it would have ended.
+
+/// A single fenced code block extracted from an item's docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    /// The example as a reader would see it rendered: `# ` hidden lines
+    /// removed.
+    pub displayed: String,
+    /// The example as it needs to compile: `# ` hidden lines kept, with
+    /// their `# ` marker stripped.
+    pub compiled: String,
+}
+
+/// Whether a fenced code block's info string (the text right after the
+/// opening ` ``` `, e.g. `rust,ignore` or `edition2018`) marks it as a Rust
+/// example, matching rustdoc's own fence syntax: no language tag at all
+/// defaults to Rust, and `rust` may be followed (or, since it's the
+/// default, replaced) by a comma-separated list of attributes such as
+/// `ignore`, `no_run`, `should_panic`, `compile_fail`, or an
+/// `edition20xx` marker. Any other language tag (`text`, `python`, ...) is
+/// not Rust.
+fn is_rust_fence(info: &str) -> bool {
+    let info = info.trim();
+    if info.is_empty() {
+        return true;
+    }
+
+    info.split(',').map(str::trim).all(|attr| {
+        matches!(attr, "rust" | "ignore" | "no_run" | "should_panic" | "compile_fail" | "edition2015" | "edition2018" | "edition2021")
+    })
+}
+
+/// Find every fenced `rust` code block in `docs`.
+pub fn find_examples(docs: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut in_block = false;
+    let mut displayed = String::new();
+    let mut compiled = String::new();
+
+    for line in docs.lines() {
+        let trimmed = line.trim_start();
+
+        if !in_block {
+            if trimmed.starts_with("```") {
+                let lang = trimmed.trim_start_matches('`');
+                if is_rust_fence(lang) {
+                    in_block = true;
+                    displayed.clear();
+                    compiled.clear();
+                }
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            in_block = false;
+            examples.push(Example {
+                displayed: displayed.clone(),
+                compiled: compiled.clone(),
+            });
+            continue;
+        }
+
+        if trimmed.starts_with("# ") || trimmed == "#" {
+            let hidden = trimmed.trim_start_matches('#').trim_start();
+            compiled.push_str(hidden);
+            compiled.push('\n');
+            continue;
+        }
+
+        displayed.push_str(line);
+        displayed.push('\n');
+        compiled.push_str(line);
+        compiled.push('\n');
+    }
+
+    examples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_a_single_fenced_block() {
+        let docs = "Does a thing.\n\n```rust\nlet x = 1;\nassert_eq!(x, 1);\n```\n";
+        let examples = find_examples(docs);
+        assert_eq!(
+            examples,
+            vec![Example {
+                displayed: "let x = 1;\nassert_eq!(x, 1);\n".to_string(),
+                compiled: "let x = 1;\nassert_eq!(x, 1);\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn hides_marked_lines_from_the_displayed_version_but_keeps_them_compiled() {
+        let docs = "```rust\n# let x = 1;\nassert_eq!(x, 1);\n```\n";
+        let examples = find_examples(docs);
+        assert_eq!(examples[0].displayed, "assert_eq!(x, 1);\n".to_string());
+        assert_eq!(examples[0].compiled, "let x = 1;\nassert_eq!(x, 1);\n".to_string());
+    }
+
+    #[test]
+    fn ignores_non_rust_fences() {
+        let docs = "```text\nnot rust\n```\n";
+        assert!(find_examples(docs).is_empty());
+    }
+
+    #[test]
+    fn extracts_a_comma_separated_ignore_fence() {
+        let docs = "```rust,ignore\nlet x = 1;\n```\n";
+        let examples = find_examples(docs);
+        assert_eq!(examples.len(), 1);
+    }
+
+    #[test]
+    fn extracts_a_bare_edition_marker_fence() {
+        let docs = "```edition2018\nlet x = 1;\n```\n";
+        let examples = find_examples(docs);
+        assert_eq!(examples.len(), 1);
+    }
+
+    #[test]
+    fn ignores_a_fence_that_mixes_a_known_attribute_with_an_unknown_one() {
+        let docs = "```rust,fancy\nlet x = 1;\n```\n";
+        assert!(find_examples(docs).is_empty());
+    }
+
+    #[test]
+    fn is_rust_fence_recognizes_bare_and_comma_separated_attributes() {
+        assert!(is_rust_fence(""));
+        assert!(is_rust_fence("rust"));
+        assert!(is_rust_fence("ignore"));
+        assert!(is_rust_fence("rust,ignore"));
+        assert!(is_rust_fence("rust, no_run"));
+        assert!(is_rust_fence("should_panic,edition2018"));
+        assert!(is_rust_fence("edition2015"));
+    }
+
+    #[test]
+    fn is_rust_fence_rejects_unknown_languages() {
+        assert!(!is_rust_fence("text"));
+        assert!(!is_rust_fence("python"));
+        assert!(!is_rust_fence("trust"));
+        assert!(!is_rust_fence("rust,fancy"));
+    }
+
+    #[test]
+    fn handles_crlf_line_endings() {
+        let docs = "```rust\r\nlet x = 1;\r\nassert_eq!(x, 1);\r\n```\r\n";
+        let examples = find_examples(docs);
+        assert_eq!(examples[0].displayed, "let x = 1;\nassert_eq!(x, 1);\n".to_string());
+    }
+
+    #[test]
+    fn an_unterminated_block_is_dropped_rather_than_returned() {
+        let docs = "```rust\nlet x = 1;\n";
+        assert!(find_examples(docs).is_empty());
+    }
+
+    #[test]
+    fn a_second_block_after_prose_is_extracted_separately() {
+        // `find_examples` doesn't parse Rust: any line starting with ```
+        // after trimming ends the open block, same as a real Markdown
+        // renderer would, so the prose between the two blocks is in neither.
+        let docs = "```rust\nlet s = \"nested\";\n```\n\nmore text\n```rust\nlet y = 2;\n```\n";
+        let examples = find_examples(docs);
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[1].displayed, "let y = 2;\n".to_string());
+    }
+}
+
+/// Property-based tests guarding against panics on malformed or adversarial
+/// Markdown, the kind `cargo fuzz` (see `fuzz/fuzz_targets/find_examples.rs`)
+/// looks for continuously; these just check the same property on the inputs
+/// `proptest` finds cheapest to generate.
+#[cfg(test)]
+mod proptest_tests {
+    use super::find_examples;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn never_panics_on_arbitrary_input(docs in ".*") {
+            let _ = find_examples(&docs);
+        }
+
+        #[test]
+        fn never_panics_on_arbitrary_fenced_blocks(
+            docs in r"(```(rust)?\r?\n([^`]|`[^`]|``[^`])*\r?\n```\r?\n)*"
+        ) {
+            let _ = find_examples(&docs);
+        }
+    }
+}
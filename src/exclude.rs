@@ -0,0 +1,49 @@
+//! Excluding a specific item, and everything nested under it, from
+//! generated documentation despite being `pub` — for staging an API that
+//! isn't ready to document yet.
+//!
+//! Two ways to mark an item excluded: listing its path in
+//! [`crate::Config::exclude`] (handy for excluding something from the
+//! outside, without touching the crate's source), or leaving an
+//! `<!-- rustdoc:skip -->` marker in its doc comment (handy for the crate's
+//! own author, who already has the doc comment open). [`crate::json`]'s
+//! `walk` checks both before recursing into an item, so a module marked
+//! either way takes every item nested under it with it.
+
+/// The literal marker [`is_marked_skip`] looks for in a doc comment.
+const SKIP_MARKER: &str = "<!-- rustdoc:skip -->";
+
+/// Whether `qualname` (already normalized, see
+/// [`crate::json::normalize_qualname`]) is listed in `exclude_paths`.
+pub fn is_excluded_path(qualname: &str, exclude_paths: &[String]) -> bool {
+    exclude_paths.iter().any(|excluded| excluded == qualname)
+}
+
+/// Whether `docs` (an item's raw [`rls_analysis::Def::docs`]) carries the
+/// `<!-- rustdoc:skip -->` marker.
+pub fn is_marked_skip(docs: &str) -> bool {
+    docs.contains(SKIP_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_an_exact_path_in_the_exclude_list() {
+        let exclude = vec!["my_crate::internal".to_string()];
+        assert!(is_excluded_path("my_crate::internal", &exclude));
+        assert!(!is_excluded_path("my_crate::internal::Widget", &exclude));
+    }
+
+    #[test]
+    fn an_empty_exclude_list_excludes_nothing() {
+        assert!(!is_excluded_path("my_crate::internal", &[]));
+    }
+
+    #[test]
+    fn finds_the_skip_marker_anywhere_in_the_docs() {
+        assert!(is_marked_skip("A widget.\n\n<!-- rustdoc:skip -->\n"));
+        assert!(!is_marked_skip("A widget."));
+    }
+}
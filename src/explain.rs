@@ -0,0 +1,226 @@
+//! Looking up and rendering a single item from previously generated
+//! [`Documentation`], for the binary's `rustdoc explain` subcommand and any
+//! other consumer that wants a one-off answer instead of the whole graph.
+
+use serde_json::Value;
+
+use crate::error::*;
+use crate::json::{Data, Documentation};
+
+/// How many near-miss suggestions [`find_item`] offers on a failed lookup.
+const SUGGESTION_COUNT: usize = 3;
+
+/// The format [`explain`] renders an item in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainFormat {
+    Json,
+    Markdown,
+    Text,
+}
+
+impl ExplainFormat {
+    /// Parse a `--format` value, e.g. `"md"`.
+    pub fn parse(value: &str) -> Result<ExplainFormat> {
+        match value {
+            "json" => Ok(ExplainFormat::Json),
+            "md" | "markdown" => Ok(ExplainFormat::Markdown),
+            "text" => Ok(ExplainFormat::Text),
+            other => Err(format!("unsupported --format value '{}'; expected 'json', 'md', or 'text'", other).into()),
+        }
+    }
+}
+
+/// Find the item in `documentation` whose qualname or name matches `query`
+/// exactly, checking the crate's own `data` first, then everything in
+/// `included`.
+///
+/// On a miss, the error message lists the [`SUGGESTION_COUNT`] items whose
+/// qualname is closest to `query` by edit distance, so a typo (`explain
+/// my_crate::Widgt`) points at what was probably meant instead of just
+/// failing.
+pub fn find_item<'a>(documentation: &'a Documentation, query: &str) -> Result<&'a Data> {
+    let all_data: Vec<&Data> = std::iter::once(&documentation.data).chain(documentation.included.iter()).collect();
+
+    if let Some(found) = all_data.iter().find(|data| matches_query(data, query)) {
+        return Ok(found);
+    }
+
+    let mut by_distance: Vec<(&Data, usize)> = all_data
+        .iter()
+        .map(|&data| (data, edit_distance(query, qualname_of(data))))
+        .collect();
+    by_distance.sort_by_key(|(_, distance)| *distance);
+
+    let suggestions: Vec<String> = by_distance.into_iter().take(SUGGESTION_COUNT).map(|(data, _)| qualname_of(data).to_string()).collect();
+
+    Err(if suggestions.is_empty() {
+        format!("no item named '{}' found", query).into()
+    } else {
+        format!("no item named '{}' found; did you mean one of: {}?", query, suggestions.join(", ")).into()
+    })
+}
+
+/// Whether `data`'s qualname or name attribute matches `query` exactly.
+fn matches_query(data: &Data, query: &str) -> bool {
+    qualname_of(data) == query || name_of(data) == Some(query)
+}
+
+fn qualname_of(data: &Data) -> &str {
+    data.attributes.get("qualname").and_then(Value::as_str).unwrap_or("")
+}
+
+fn name_of(data: &Data) -> Option<&str> {
+    data.attributes.get("name").and_then(Value::as_str)
+}
+
+/// The Levenshtein edit distance between `a` and `b`, used to rank
+/// [`find_item`]'s miss suggestions. No dedicated string-distance dependency
+/// exists in this crate, so this is the textbook dynamic-programming
+/// implementation (see [`crate::metrics::count_syllables`] for a similarly
+/// small from-scratch heuristic).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &a_char) in a.iter().enumerate() {
+        let mut previous_diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            let cost = usize::from(a_char != b_char);
+            let current = std::cmp::min(std::cmp::min(above + 1, row[j] + 1), previous_diagonal + cost);
+            previous_diagonal = above;
+            row[j + 1] = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Render `data`'s docs, signature, and relations in `format`.
+pub fn explain(data: &Data, format: ExplainFormat) -> Result<String> {
+    match format {
+        ExplainFormat::Json => Ok(serde_json::to_string_pretty(data)?),
+        ExplainFormat::Markdown => Ok(render_markdown(data)),
+        ExplainFormat::Text => Ok(render_text(data)),
+    }
+}
+
+fn render_markdown(data: &Data) -> String {
+    let mut out = format!("# {}\n\n`{}` ({})\n", name_of(data).unwrap_or(&data.id), qualname_of(data), data.ty);
+
+    let docs = data.attributes.get("docs").and_then(Value::as_str).unwrap_or("");
+    if !docs.is_empty() {
+        out.push('\n');
+        out.push_str(docs);
+        out.push('\n');
+    }
+
+    if let Some(relations) = render_relations(data) {
+        out.push_str("\n## Relations\n\n");
+        out.push_str(&relations);
+    }
+
+    out
+}
+
+fn render_text(data: &Data) -> String {
+    let mut out = format!("{} ({})\n{}\n", qualname_of(data), data.ty, "-".repeat(qualname_of(data).len() + data.ty.len() + 3));
+
+    let docs = data.attributes.get("docs").and_then(Value::as_str).unwrap_or("");
+    if !docs.is_empty() {
+        out.push('\n');
+        out.push_str(docs);
+        out.push('\n');
+    }
+
+    if let Some(relations) = render_relations(data) {
+        out.push_str("\nRelations:\n");
+        out.push_str(&relations);
+    }
+
+    out
+}
+
+fn render_relations(data: &Data) -> Option<String> {
+    let relationships = data.relationships.as_ref()?;
+    if relationships.is_empty() {
+        return None;
+    }
+
+    let mut out = String::new();
+    for (kind, members) in relationships.iter() {
+        let ids: Vec<&str> = members.as_slice().iter().map(|datum| datum.id.as_str()).collect();
+        out.push_str(&format!("- {}: {}\n", kind, ids.join(", ")));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{Datum, Relationships};
+    use std::collections::HashMap;
+
+    fn item(id: &str, ty: &str, name: &str, qualname: &str, docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), Value::String(name.to_string()));
+        attributes.insert("qualname".to_string(), Value::String(qualname.to_string()));
+        attributes.insert("docs".to_string(), Value::String(docs.to_string()));
+        Data {
+            id: id.to_string(),
+            ty: ty.to_string(),
+            attributes,
+            relationships: None,
+            links: None,
+        }
+    }
+
+    fn documentation_with(items: Vec<Data>) -> Documentation {
+        let mut items = items.into_iter();
+        let data = items.next().expect("at least one item");
+        Documentation { data, included: items.collect(), meta: HashMap::new(), links: None }
+    }
+
+    #[test]
+    fn finds_an_item_by_qualname() {
+        let documentation = documentation_with(vec![item("1", "struct", "Widget", "my_crate::Widget", "docs")]);
+        let found = find_item(&documentation, "my_crate::Widget").unwrap();
+        assert_eq!(found.id, "1");
+    }
+
+    #[test]
+    fn finds_an_item_by_bare_name() {
+        let documentation = documentation_with(vec![item("1", "struct", "Widget", "my_crate::Widget", "docs")]);
+        let found = find_item(&documentation, "Widget").unwrap();
+        assert_eq!(found.id, "1");
+    }
+
+    #[test]
+    fn a_miss_suggests_the_closest_qualnames() {
+        let documentation = documentation_with(vec![item("1", "struct", "Widget", "my_crate::Widget", "docs")]);
+        let error = find_item(&documentation, "my_crate::Widgt").unwrap_err();
+        assert!(error.to_string().contains("my_crate::Widget"));
+    }
+
+    #[test]
+    fn renders_docs_and_relations_as_text() {
+        let mut widget = item("1", "struct", "Widget", "my_crate::Widget", "A widget.");
+        let mut relationships = Relationships::default();
+        relationships.add_child("methods", Datum { id: "2".to_string(), ty: "function".to_string() });
+        widget.relationships = Some(relationships);
+
+        let rendered = explain(&widget, ExplainFormat::Text).unwrap();
+        assert!(rendered.contains("my_crate::Widget"));
+        assert!(rendered.contains("A widget."));
+        assert!(rendered.contains("methods: 2"));
+    }
+
+    #[test]
+    fn renders_as_json() {
+        let widget = item("1", "struct", "Widget", "my_crate::Widget", "A widget.");
+        let rendered = explain(&widget, ExplainFormat::Json).unwrap();
+        assert!(rendered.contains("\"my_crate::Widget\""));
+    }
+}
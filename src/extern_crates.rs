@@ -0,0 +1,187 @@
+//! Recording which of the crate's own dependencies are actually mentioned
+//! by a documented item's `qualname` or `docs`, so a frontend can render a
+//! "Dependencies" navigation group with links to each one's own
+//! documentation.
+//!
+//! Telling "this type comes from crate X" apart from "this type merely
+//! shares a name with something in crate X" would need the compiler's own
+//! name resolution; `rls_analysis::Def` (0.18.3) exposes neither a
+//! defining-crate field nor attribute tokens to read it back from (the
+//! same gap noted on [`crate::json::parse_doc_cfg`]). Instead this looks
+//! for a dependency's own module path (`serde_json::`) as a plain
+//! substring of an item's `qualname` or `docs`, which catches the common
+//! case of a dependency's type named directly without needing real
+//! resolution.
+
+use cargo_metadata::{Metadata, Package};
+use serde_derive::Serialize;
+
+use crate::json::Documentation;
+
+/// A dependency mentioned by at least one documented item, with a docs.rs
+/// URL when its resolved version is known.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternCrate {
+    pub name: String,
+    pub version: Option<String>,
+    #[serde(rename = "docsUrl")]
+    pub docs_url: Option<String>,
+}
+
+/// The docs.rs URL for `name`'s `version`, e.g. `serde-json` `1.0.100`
+/// becomes `https://docs.rs/serde-json/1.0.100/serde_json/`.
+fn docs_rs_url(name: &str, version: &str) -> String {
+    format!("https://docs.rs/{name}/{version}/{}/", name.replace('-', "_"))
+}
+
+/// The resolved version of `metadata`'s package named `name`, if it's
+/// among the crates actually resolved for this build.
+fn resolved_version(metadata: &Metadata, name: &str) -> Option<String> {
+    metadata
+        .packages
+        .iter()
+        .find(|package| package.name == name)
+        .map(|package| package.version.to_string())
+}
+
+/// Whether any of `documentation`'s items mention `module_path` (a
+/// dependency's crate name with `-` normalized to `_`, followed by `::`)
+/// in their `qualname` or `docs`.
+fn is_mentioned(documentation: &Documentation, module_path: &str) -> bool {
+    std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .any(|data| {
+            ["qualname", "docs"].iter().any(|key| {
+                data.attributes
+                    .get(*key)
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|text| text.contains(module_path))
+            })
+        })
+}
+
+/// Every dependency of `package` mentioned somewhere in `documentation`,
+/// paired with its resolved version and docs.rs URL when known.
+pub fn find_referenced_crates(documentation: &Documentation, metadata: &Metadata, package: &Package) -> Vec<ExternCrate> {
+    let mut found: Vec<ExternCrate> = package
+        .dependencies
+        .iter()
+        .map(|dependency| dependency.name.clone())
+        .filter(|name| is_mentioned(documentation, &format!("{}::", name.replace('-', "_"))))
+        .map(|name| {
+            let version = resolved_version(metadata, &name);
+            let docs_url = version.as_deref().map(|version| docs_rs_url(&name, version));
+            ExternCrate { name, version, docs_url }
+        })
+        .collect();
+
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    found.dedup_by(|a, b| a.name == b.name);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn package_depending_on(dir: &std::path::Path, dependency_names: &[&str]) -> Package {
+        let dependencies: Vec<_> = dependency_names
+            .iter()
+            .map(|name| serde_json::json!({"name": name, "req": "*", "kind": null, "optional": false, "uses_default_features": true, "features": []}))
+            .collect();
+
+        serde_json::from_value(serde_json::json!({
+            "name": "test-crate",
+            "version": "0.1.0",
+            "id": "test-crate 0.1.0 (path+file:///tmp/test-crate)",
+            "dependencies": dependencies,
+            "targets": [],
+            "features": {},
+            "manifest_path": dir.join("Cargo.toml"),
+        }))
+        .unwrap()
+    }
+
+    fn metadata_with(package: &Package, resolved: &[(&str, &str)]) -> Metadata {
+        let mut packages = vec![serde_json::to_value(package).unwrap()];
+        for (name, version) in resolved {
+            packages.push(serde_json::json!({
+                "name": name,
+                "version": version,
+                "id": format!("{} {} (registry+https://github.com/rust-lang/crates.io-index)", name, version),
+                "source": "registry+https://github.com/rust-lang/crates.io-index",
+                "dependencies": [],
+                "targets": [],
+                "features": {},
+                "manifest_path": "/dev/null/Cargo.toml",
+            }));
+        }
+
+        serde_json::from_value(serde_json::json!({
+            "packages": packages,
+            "workspace_members": [],
+            "resolve": null,
+            "workspace_root": "/tmp",
+            "target_directory": "/tmp/target",
+            "version": 1,
+        }))
+        .unwrap()
+    }
+
+    fn documentation_mentioning(qualname: &str) -> Documentation {
+        let mut attributes = HashMap::new();
+        attributes.insert("qualname".to_string(), serde_json::Value::String(qualname.to_string()));
+        attributes.insert("docs".to_string(), serde_json::Value::String(String::new()));
+
+        Documentation {
+            data: Data { attributes, ..Data::default() },
+            included: Vec::new(),
+            meta: HashMap::new(),
+            links: None,
+        }
+    }
+
+    #[test]
+    fn a_dependency_named_in_a_qualname_is_referenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = package_depending_on(dir.path(), &["serde-json"]);
+        let metadata = metadata_with(&package, &[("serde-json", "1.0.100")]);
+        let documentation = documentation_mentioning("my_crate::foo -> serde_json::Value");
+
+        let found = find_referenced_crates(&documentation, &metadata, &package);
+
+        assert_eq!(
+            found,
+            vec![ExternCrate {
+                name: "serde-json".to_string(),
+                version: Some("1.0.100".to_string()),
+                docs_url: Some("https://docs.rs/serde-json/1.0.100/serde_json/".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn a_dependency_never_mentioned_is_not_included() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = package_depending_on(dir.path(), &["serde-json"]);
+        let metadata = metadata_with(&package, &[("serde-json", "1.0.100")]);
+        let documentation = documentation_mentioning("my_crate::foo");
+
+        assert!(find_referenced_crates(&documentation, &metadata, &package).is_empty());
+    }
+
+    #[test]
+    fn an_unresolved_dependency_has_no_docs_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = package_depending_on(dir.path(), &["serde-json"]);
+        let metadata = metadata_with(&package, &[]);
+        let documentation = documentation_mentioning("serde_json::Value");
+
+        let found = find_referenced_crates(&documentation, &metadata, &package);
+
+        assert_eq!(found[0].version, None);
+        assert_eq!(found[0].docs_url, None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Alternative serializations of [`Documentation`], for consumers that want
+//! something smaller (MessagePack) or more human-editable (YAML) than the
+//! default `data.json`.
+//!
+//! [`Documentation::from_path`] and [`Documentation::from_reader`] read a
+//! previously serialized `Documentation` back in, auto-detecting its format
+//! and checking its [`FORMAT_VERSION`]. The `rustdoc` binary uses
+//! `from_reader` to load a previous build's output back (for `explain`,
+//! `check`, `serve`, and its "since last build" summary); inside the
+//! library, [`crate::test`] always works from the in-memory
+//! `Documentation` a fresh [`crate::build`] call just returned, not one read
+//! back off disk. They're also here for an embedder that persists `data.json`
+//! (or `.yaml`/`.msgpack`) and reloads it later, so it doesn't have to
+//! hand-roll the same format detection and version check.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::error::*;
+use crate::json::Documentation;
+
+/// A format [`Documentation`] can be serialized to, or (via
+/// [`EmitFormat::detect_from_extension`]) recognized by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitFormat {
+    Json,
+    Yaml,
+    MessagePack,
+}
+
+impl EmitFormat {
+    /// Parse a `--emit` value, e.g. `"yaml"`.
+    pub fn parse(value: &str) -> Result<EmitFormat> {
+        match value {
+            "json" => Ok(EmitFormat::Json),
+            "yaml" => Ok(EmitFormat::Yaml),
+            "msgpack" => Ok(EmitFormat::MessagePack),
+            other => Err(format!(
+                "unsupported --emit format '{}'; expected 'json', 'yaml', or 'msgpack'",
+                other
+            )
+            .into()),
+        }
+    }
+
+    /// The file name `rustdoc build` writes to when `--output` isn't given.
+    pub fn default_file_name(self) -> &'static str {
+        match self {
+            EmitFormat::Json => "data.json",
+            EmitFormat::Yaml => "data.yaml",
+            EmitFormat::MessagePack => "data.msgpack",
+        }
+    }
+
+    /// Recognize a format from a file's extension, e.g. `"yml"` and
+    /// `"yaml"` both mapping to [`EmitFormat::Yaml`]. `None` for an
+    /// unrecognized or missing extension.
+    pub fn detect_from_extension(path: &Path) -> Option<EmitFormat> {
+        match path.extension().and_then(|ext| ext.to_str())? {
+            "json" => Some(EmitFormat::Json),
+            "yaml" | "yml" => Some(EmitFormat::Yaml),
+            "msgpack" => Some(EmitFormat::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// Serialize `documentation` as `format`.
+pub fn serialize(documentation: &Documentation, format: EmitFormat) -> Result<Vec<u8>> {
+    match format {
+        EmitFormat::Json => Ok(serde_json::to_string_pretty(documentation)?.into_bytes()),
+        EmitFormat::Yaml => serde_yaml::to_string(documentation)
+            .map(String::into_bytes)
+            .chain_err(|| "failed to serialize documentation as YAML"),
+        EmitFormat::MessagePack => {
+            rmp_serde::to_vec(documentation).chain_err(|| "failed to serialize documentation as MessagePack")
+        }
+    }
+}
+
+/// Deserialize documentation from `bytes` in the given `format`. Used by
+/// [`Documentation::from_reader`] and [`Documentation::from_path`], which
+/// also check [`FORMAT_VERSION`] on the result; call this directly instead
+/// if a mismatched version should be tolerated.
+pub fn deserialize(bytes: &[u8], format: EmitFormat) -> Result<Documentation> {
+    match format {
+        EmitFormat::Json => serde_json::from_slice(bytes).chain_err(|| "failed to parse documentation as JSON"),
+        EmitFormat::Yaml => serde_yaml::from_slice(bytes).chain_err(|| "failed to parse documentation as YAML"),
+        EmitFormat::MessagePack => {
+            rmp_serde::from_slice(bytes).chain_err(|| "failed to parse documentation as MessagePack")
+        }
+    }
+}
+
+/// The `data.json` shape version this build of `rustdoc` writes, stamped
+/// into `meta.formatVersion` by [`crate::json::create_documentation`].
+/// Bump this whenever a change to `Documentation`'s shape could silently
+/// misread as something else, rather than fail outright, under an old
+/// consumer's assumptions.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Check `documentation.meta.formatVersion` against [`FORMAT_VERSION`],
+/// erroring on a mismatch instead of letting a caller work with data that
+/// silently doesn't mean what it expects. Documentation with no
+/// `formatVersion` at all (written before this field existed) is treated
+/// as compatible rather than rejected.
+pub fn check_version(documentation: &Documentation) -> Result<()> {
+    let found = match documentation.meta.get("formatVersion").and_then(Value::as_u64) {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    if found != FORMAT_VERSION {
+        return Err(format!(
+            "documentation was written by format version {}, but this build of rustdoc reads version {}",
+            found, FORMAT_VERSION
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_supported_emit_value() {
+        assert_eq!(EmitFormat::parse("json").unwrap(), EmitFormat::Json);
+        assert_eq!(EmitFormat::parse("yaml").unwrap(), EmitFormat::Yaml);
+        assert_eq!(EmitFormat::parse("msgpack").unwrap(), EmitFormat::MessagePack);
+    }
+
+    #[test]
+    fn rejects_an_unknown_emit_value() {
+        assert!(EmitFormat::parse("toml").is_err());
+    }
+
+    #[test]
+    fn detects_yaml_from_either_extension() {
+        assert_eq!(
+            EmitFormat::detect_from_extension(Path::new("data.yaml")),
+            Some(EmitFormat::Yaml)
+        );
+        assert_eq!(
+            EmitFormat::detect_from_extension(Path::new("data.yml")),
+            Some(EmitFormat::Yaml)
+        );
+    }
+
+    #[test]
+    fn an_unrecognized_extension_is_not_detected() {
+        assert_eq!(EmitFormat::detect_from_extension(Path::new("data.toml")), None);
+    }
+
+    #[test]
+    fn round_trips_through_each_format() {
+        let documentation = Documentation::default();
+
+        let json = serialize(&documentation, EmitFormat::Json).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Documentation>(&json).unwrap().data.ty,
+            documentation.data.ty
+        );
+
+        let yaml = serialize(&documentation, EmitFormat::Yaml).unwrap();
+        assert_eq!(
+            serde_yaml::from_slice::<Documentation>(&yaml).unwrap().data.ty,
+            documentation.data.ty
+        );
+
+        let msgpack = serialize(&documentation, EmitFormat::MessagePack).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<Documentation>(&msgpack).unwrap().data.ty,
+            documentation.data.ty
+        );
+    }
+
+    #[test]
+    fn documentation_with_no_format_version_is_accepted() {
+        check_version(&Documentation::default()).unwrap();
+    }
+
+    #[test]
+    fn a_matching_format_version_is_accepted() {
+        let mut documentation = Documentation::default();
+        documentation
+            .meta
+            .insert("formatVersion".to_string(), Value::from(FORMAT_VERSION));
+
+        check_version(&documentation).unwrap();
+    }
+
+    #[test]
+    fn a_mismatched_format_version_is_rejected() {
+        let mut documentation = Documentation::default();
+        documentation
+            .meta
+            .insert("formatVersion".to_string(), Value::from(FORMAT_VERSION + 1));
+
+        assert!(check_version(&documentation).is_err());
+    }
+}
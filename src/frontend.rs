@@ -0,0 +1,295 @@
+//! Discovering and version-checking external "frontend" plugins: binaries
+//! installable via `cargo install` and named `cargo-doc-frontend-<name>`
+//! (mirroring cargo's own `cargo-<subcommand>` discovery convention), which
+//! render this crate's generated documentation into something browsable.
+//!
+//! This crate stays intentionally frontend-agnostic (see its own top-level
+//! doc comment) — this module only finds what's installed on `PATH` and
+//! confirms it understands the `data.json` shape (see
+//! [`crate::format::FORMAT_VERSION`]) about to be handed to it, refusing
+//! with an upgrade message instead of shipping data a stale frontend would
+//! silently misread.
+
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::command::ProcessRunner;
+use crate::error::*;
+use crate::format::FORMAT_VERSION;
+use crate::json::Documentation;
+
+/// The prefix every discoverable frontend binary's file name starts with.
+const FRONTEND_PREFIX: &str = "cargo-doc-frontend-";
+
+/// A frontend binary found on `PATH`, and what it reported about itself in
+/// response to being queried with `--version --data-format` (see
+/// [`query_frontend`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontendInfo {
+    /// The part of the binary's file name after [`FRONTEND_PREFIX`], e.g.
+    /// `"html"` for `cargo-doc-frontend-html`.
+    pub name: String,
+    pub path: PathBuf,
+    /// Whatever version string the binary printed on the first line of its
+    /// `--version --data-format` output.
+    pub version: String,
+    /// The `data.json` format version the binary printed on the second
+    /// line, i.e. the newest [`FORMAT_VERSION`] it understands.
+    pub data_format_version: u64,
+}
+
+impl FrontendInfo {
+    /// Whether this frontend declared support for the `data.json` shape
+    /// this build of `rustdoc` actually writes.
+    pub fn is_compatible(&self) -> bool {
+        self.data_format_version == FORMAT_VERSION
+    }
+}
+
+/// A `cargo-doc-frontend-*` binary found on `PATH`, along with the result of
+/// querying it (see [`query_frontend`]). Kept even on failure, so `rustdoc
+/// frontend list` can report a frontend that's present but unresponsive
+/// instead of silently omitting it.
+pub struct DiscoveredFrontend {
+    pub name: String,
+    pub path: PathBuf,
+    pub info: Result<FrontendInfo>,
+}
+
+/// Every directory in the current process's `PATH`, in order.
+fn path_dirs() -> Vec<PathBuf> {
+    env::var_os("PATH").map(|path| env::split_paths(&path).collect()).unwrap_or_default()
+}
+
+/// The frontend name a binary's file name declares, if it starts with
+/// [`FRONTEND_PREFIX`] (e.g. `"cargo-doc-frontend-html"` -> `Some("html")`,
+/// and `"cargo-doc-frontend-html.exe"` -> `Some("html")` on Windows). `None`
+/// for anything else, including a bare `cargo-doc-frontend-` with nothing
+/// after the prefix.
+fn frontend_name(file_name: &str) -> Option<&str> {
+    let stem = file_name.strip_suffix(std::env::consts::EXE_SUFFIX).unwrap_or(file_name);
+    let name = stem.strip_prefix(FRONTEND_PREFIX)?;
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Find every `cargo-doc-frontend-*` binary directly inside `dirs`, without
+/// querying any of them yet. Split out from [`discover_frontend_paths`] so a
+/// test can point it at a fixture directory instead of the real `PATH`.
+fn discover_frontend_paths_in(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut found = Vec::new();
+
+    for dir in dirs {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(std::result::Result::ok) {
+            let file_name = entry.file_name();
+            if let Some(name) = file_name.to_str().and_then(frontend_name) {
+                found.push((name.to_string(), entry.path()));
+            }
+        }
+    }
+
+    found
+}
+
+/// Find every `cargo-doc-frontend-*` binary on the real `PATH`.
+fn discover_frontend_paths() -> Vec<(String, PathBuf)> {
+    discover_frontend_paths_in(&path_dirs())
+}
+
+/// Run `path --version --data-format`, parsing its first stdout line as the
+/// frontend's own version string and its second as the `data.json` format
+/// version it understands.
+fn query_frontend(name: &str, path: &Path, runner: &dyn ProcessRunner) -> Result<FrontendInfo> {
+    let mut command = Command::new(path);
+    command.arg("--version").arg("--data-format");
+
+    let output = runner.run(&mut command).chain_err(|| format!("failed to run '{}'", path.display()))?;
+    if !output.status.success() {
+        return Err(format!("'{}' exited unsuccessfully when queried for its version", path.display()).into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+
+    let version = lines
+        .next()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .ok_or_else(|| format!("'{}' printed no version", path.display()))?
+        .to_string();
+
+    let data_format_version = lines
+        .next()
+        .map(str::trim)
+        .ok_or_else(|| format!("'{}' printed no data format version", path.display()))?
+        .parse()
+        .chain_err(|| format!("'{}' printed a non-numeric data format version", path.display()))?;
+
+    Ok(FrontendInfo { name: name.to_string(), path: path.to_path_buf(), version, data_format_version })
+}
+
+/// Find and query every frontend on `PATH` (see [`discover_frontend_paths`]
+/// and [`query_frontend`]). `runner` is how each one gets spawned (see
+/// [`crate::command::ProcessRunner`]), so this can be exercised in a test
+/// with fake process outcomes.
+pub fn discover_frontends(runner: &dyn ProcessRunner) -> Vec<DiscoveredFrontend> {
+    discover_frontend_paths()
+        .into_iter()
+        .map(|(name, path)| {
+            let info = query_frontend(&name, &path, runner);
+            DiscoveredFrontend { name, path, info }
+        })
+        .collect()
+}
+
+/// Refuse to hand data to `info` if it declared a data format version other
+/// than [`FORMAT_VERSION`], with a message naming the package to reinstall
+/// rather than a generic mismatch error.
+pub fn check_compatible(info: &FrontendInfo) -> Result<()> {
+    if info.is_compatible() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "{prefix}{name} v{version} understands data format {found}, but this build of rustdoc writes format {expected}; run `cargo install --force {prefix}{name}` to update it",
+        prefix = FRONTEND_PREFIX,
+        name = info.name,
+        version = info.version,
+        found = info.data_format_version,
+        expected = FORMAT_VERSION,
+    )
+    .into())
+}
+
+/// Find the `cargo-doc-frontend-<name>` binary on `PATH`, confirm it
+/// declares support for [`FORMAT_VERSION`] (see [`check_compatible`]), and
+/// pipe `documentation` to its stdin as JSON.
+///
+/// Unlike [`crate::post_process::run`], the frontend's stdout isn't read
+/// back as documentation: a frontend's job is to render, not to transform,
+/// so nothing here waits for output shaped like a [`Documentation`].
+///
+/// `runner` is used for the version query only; the actual send bypasses it
+/// the same way [`crate::post_process::run`] bypasses
+/// [`crate::command::CommandBridge`], since it needs to write to the
+/// child's stdin rather than just collect its output.
+pub fn send_to_frontend(name: &str, documentation: &Documentation, runner: &dyn ProcessRunner) -> Result<()> {
+    let (_, path) = discover_frontend_paths()
+        .into_iter()
+        .find(|(found_name, _)| found_name == name)
+        .ok_or_else(|| format!("no frontend named '{}{}' found on PATH", FRONTEND_PREFIX, name))?;
+
+    let info = query_frontend(name, &path, runner)?;
+    check_compatible(&info)?;
+
+    let input = serde_json::to_vec(documentation)?;
+
+    let mut child = Command::new(&path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("failed to run '{}'", path.display()))?;
+
+    child
+        .stdin
+        .take()
+        .expect("child was spawned with a piped stdin")
+        .write_all(&input)
+        .chain_err(|| format!("failed to write documentation to '{}'", path.display()))?;
+
+    let status = child.wait().chain_err(|| format!("failed to run '{}'", path.display()))?;
+    if !status.success() {
+        return Err(format!("'{}' did not run successfully", path.display()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::testing::FakeProcessRunner;
+
+    #[test]
+    fn recognizes_a_plain_frontend_name() {
+        assert_eq!(frontend_name("cargo-doc-frontend-html"), Some("html"));
+    }
+
+    #[test]
+    fn rejects_a_bare_prefix_with_no_name() {
+        assert_eq!(frontend_name("cargo-doc-frontend-"), None);
+    }
+
+    #[test]
+    fn rejects_an_unrelated_file_name() {
+        assert_eq!(frontend_name("cargo-build"), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn strips_the_exe_suffix_on_windows() {
+        assert_eq!(frontend_name("cargo-doc-frontend-html.exe"), Some("html"));
+    }
+
+    #[test]
+    fn discover_frontend_paths_in_finds_prefixed_binaries_across_dirs() {
+        let dir_a = tempfile::tempdir().unwrap();
+        let dir_b = tempfile::tempdir().unwrap();
+        std::fs::write(dir_a.path().join("cargo-doc-frontend-html"), "").unwrap();
+        std::fs::write(dir_a.path().join("cargo-build"), "").unwrap();
+        std::fs::write(dir_b.path().join("cargo-doc-frontend-tui"), "").unwrap();
+
+        let mut found = discover_frontend_paths_in(&[dir_a.path().to_path_buf(), dir_b.path().to_path_buf()]);
+        found.sort();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, "html");
+        assert_eq!(found[1].0, "tui");
+    }
+
+    #[test]
+    fn query_frontend_parses_version_and_data_format() {
+        let runner = FakeProcessRunner::new(vec![(0, b"1.2.3\n1\n".to_vec())]);
+        let info = query_frontend("html", Path::new("/usr/bin/cargo-doc-frontend-html"), &runner).unwrap();
+
+        assert_eq!(info.name, "html");
+        assert_eq!(info.version, "1.2.3");
+        assert_eq!(info.data_format_version, 1);
+    }
+
+    #[test]
+    fn query_frontend_errors_on_a_non_numeric_data_format_line() {
+        let runner = FakeProcessRunner::new(vec![(0, b"1.2.3\nnot-a-number\n".to_vec())]);
+        assert!(query_frontend("html", Path::new("cargo-doc-frontend-html"), &runner).is_err());
+    }
+
+    #[test]
+    fn query_frontend_errors_when_the_binary_exits_unsuccessfully() {
+        let runner = FakeProcessRunner::new(vec![(1, Vec::new())]);
+        assert!(query_frontend("html", Path::new("cargo-doc-frontend-html"), &runner).is_err());
+    }
+
+    #[test]
+    fn check_compatible_accepts_a_matching_data_format_version() {
+        let info = FrontendInfo { name: "html".to_string(), path: PathBuf::from("cargo-doc-frontend-html"), version: "1.0.0".to_string(), data_format_version: FORMAT_VERSION };
+        assert!(info.is_compatible());
+        assert!(check_compatible(&info).is_ok());
+    }
+
+    #[test]
+    fn check_compatible_rejects_a_stale_frontend_with_an_upgrade_message() {
+        let info = FrontendInfo { name: "html".to_string(), path: PathBuf::from("cargo-doc-frontend-html"), version: "1.0.0".to_string(), data_format_version: FORMAT_VERSION + 1 };
+        assert!(!info.is_compatible());
+        let error = check_compatible(&info).unwrap_err();
+        assert!(error.to_string().contains("cargo install --force cargo-doc-frontend-html"));
+    }
+}
@@ -0,0 +1,200 @@
+//! A graph view over a `Documentation`.
+//!
+//! Several features (linting for dangling relationships, diffing two
+//! builds, building a module tree for a frontend's nav) all boil down to
+//! walking `Documentation`'s items and their relationships. Rather than
+//! have each of them re-walk the relationship `HashMap`s by hand, this
+//! module converts a `Documentation` into a `petgraph` graph once, and
+//! offers traversal helpers on top of it.
+
+use std::collections::HashMap;
+
+use petgraph::algo::is_cyclic_directed;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::Bfs;
+
+use crate::json::{Data, Documentation};
+
+/// A `Documentation`, converted into a graph of its items.
+///
+/// Nodes are documents (the crate's own `data`, plus everything in
+/// `included`), identified by their JSON-API id. Edges point from an item to
+/// the items referenced by one of its relationships, labeled with the
+/// relationship's name (e.g. `"modules"`, `"structs"`).
+pub struct DocGraph {
+    graph: DiGraph<String, String>,
+    index_by_id: HashMap<String, NodeIndex>,
+}
+
+impl DocGraph {
+    /// Build a graph from every document in `documentation`.
+    pub fn new(documentation: &Documentation) -> DocGraph {
+        let mut graph = DiGraph::new();
+        let mut index_by_id = HashMap::new();
+
+        let all_data: Vec<&Data> = std::iter::once(&documentation.data)
+            .chain(documentation.included.iter())
+            .collect();
+
+        for data in &all_data {
+            let index = graph.add_node(data.id.clone());
+            index_by_id.insert(data.id.clone(), index);
+        }
+
+        for data in &all_data {
+            let relationships = match &data.relationships {
+                Some(relationships) => relationships,
+                None => continue,
+            };
+
+            let from = index_by_id[&data.id];
+
+            for (kind, data_member) in relationships.iter() {
+                for datum in data_member.as_slice() {
+                    if let Some(&to) = index_by_id.get(&datum.id) {
+                        graph.add_edge(from, to, kind.to_string());
+                    }
+                }
+            }
+        }
+
+        DocGraph { graph, index_by_id }
+    }
+
+    /// Whether the relationship graph contains a cycle.
+    ///
+    /// The item hierarchy should always be a DAG; a cycle here means a
+    /// generation bug produced a relationship pointing back at an ancestor.
+    pub fn is_cyclic(&self) -> bool {
+        is_cyclic_directed(&self.graph)
+    }
+
+    /// Breadth-first traversal of every item reachable from `root` (a
+    /// JSON-API id), following relationships outward. Returns the ids
+    /// visited, in traversal order; `root` itself is included first.
+    pub fn traverse_from(&self, root: &str) -> Vec<String> {
+        let start = match self.index_by_id.get(root) {
+            Some(&index) => index,
+            None => return Vec::new(),
+        };
+
+        let mut bfs = Bfs::new(&self.graph, start);
+        let mut visited = Vec::new();
+        while let Some(index) = bfs.next(&self.graph) {
+            visited.push(self.graph[index].clone());
+        }
+        visited
+    }
+
+    /// Every edge in the graph, as `(from id, relationship kind, to id)`.
+    pub fn edges(&self) -> Vec<(&str, &str, &str)> {
+        self.graph
+            .raw_edges()
+            .iter()
+            .map(|edge| {
+                (
+                    self.graph[edge.source()].as_str(),
+                    edge.weight.as_str(),
+                    self.graph[edge.target()].as_str(),
+                )
+            })
+            .collect()
+    }
+
+    /// The id of every item in the graph.
+    pub fn node_ids(&self) -> impl Iterator<Item = &str> {
+        self.graph.node_weights().map(String::as_str)
+    }
+
+    /// The number of items in the graph.
+    pub fn len(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Whether the graph has no items.
+    pub fn is_empty(&self) -> bool {
+        self.graph.node_count() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{Datum, Relationships};
+    use std::collections::HashMap;
+
+    fn data(id: &str, ty: &str, children: Option<(&str, Vec<&str>, &str)>) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), id.into());
+
+        let relationships = children.map(|(kind, ids, child_ty)| {
+            let mut relationships = Relationships::default();
+            for id in ids {
+                relationships.add_child(
+                    kind,
+                    Datum {
+                        id: id.to_string(),
+                        ty: child_ty.to_string(),
+                    },
+                );
+            }
+            relationships
+        });
+
+        Data {
+            id: id.to_string(),
+            ty: ty.to_string(),
+            attributes,
+            relationships,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn traverses_modules_breadth_first() {
+        let documentation = Documentation {
+            data: data("crate", "crate", Some(("modules", vec!["a", "b"], "module"))),
+            included: vec![
+                data("a", "module", None),
+                data("b", "module", None),
+            ],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let graph = DocGraph::new(&documentation);
+        assert_eq!(graph.len(), 3);
+
+        let visited = graph.traverse_from("crate");
+        assert_eq!(visited[0], "crate");
+        let mut rest = visited[1..].to_vec();
+        rest.sort();
+        assert_eq!(rest, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn unknown_root_traverses_to_nothing() {
+        let documentation = Documentation {
+            data: data("crate", "crate", None),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let graph = DocGraph::new(&documentation);
+        assert!(graph.traverse_from("does-not-exist").is_empty());
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let documentation = Documentation {
+            data: data("crate", "crate", Some(("modules", vec!["a"], "module"))),
+            included: vec![data("a", "module", Some(("modules", vec!["crate"], "crate")))],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let graph = DocGraph::new(&documentation);
+        assert!(graph.is_cyclic());
+    }
+}
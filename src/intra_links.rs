@@ -0,0 +1,148 @@
+//! Resolving intra-crate absolute doc links (`` `crate_name::Widget` ``,
+//! written the same way rustdoc's own intra-doc links are) against the
+//! crate's own items, including ones declared *after* the item whose docs
+//! link to them.
+//!
+//! A forward reference isn't actually special here: [`crate::json::walk`]
+//! collects every item into `Documentation::included` before
+//! `create_documentation` returns, so by the time this runs, an item
+//! declared at the bottom of the file is in the same lookup table as one
+//! declared at the top. This is a single pass over already-complete data,
+//! not a second walk of the source order.
+//!
+//! Like [`crate::staleness`], resolution is a plain substring match against
+//! `qualname` text, not real path resolution (`rls_analysis::Def` 0.18.3
+//! has nothing better to offer); a path mentioned only in prose can produce
+//! a false positive, and one written through a `use`-introduced alias
+//! rather than its full path won't be recognized at all.
+
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::json::Documentation;
+
+/// One resolved intra-crate doc link found in an item's docs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocLink {
+    /// The path as written in the docs, e.g. `"my_crate::Widget"`.
+    pub path: String,
+    /// The resource id of the item it resolves to.
+    pub target: String,
+}
+
+/// Every `crate_name::...` token in `text`, stopping each one at the first
+/// character that couldn't be part of a path.
+fn crate_paths(text: &str, crate_name: &str) -> Vec<String> {
+    let prefix = format!("{}::", crate_name);
+    let mut found = Vec::new();
+    let mut offset = 0;
+
+    while let Some(relative_start) = text[offset..].find(&prefix) {
+        let start = offset + relative_start;
+        let end = text[start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .map(|relative_end| start + relative_end)
+            .unwrap_or(text.len());
+
+        found.push(text[start..end].to_string());
+        offset = end.max(start + prefix.len());
+    }
+
+    found
+}
+
+/// Every item's `qualname`, mapped to its resource id.
+fn qualname_index(documentation: &Documentation) -> HashMap<&str, &str> {
+    std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .filter_map(|data| {
+            let qualname = data.attributes.get("qualname").and_then(|v| v.as_str())?;
+            Some((qualname, data.id.as_str()))
+        })
+        .collect()
+}
+
+/// Scan every item's `docs` for a `crate_name::...` path matching another
+/// item's `qualname`, and record what it resolves to in a `docLinks`
+/// attribute (skipped when nothing resolved).
+pub fn resolve_doc_links(documentation: &mut Documentation, crate_name: &str) {
+    let index: HashMap<String, String> =
+        qualname_index(documentation).into_iter().map(|(qualname, id)| (qualname.to_string(), id.to_string())).collect();
+
+    let items = std::iter::once(&mut documentation.data).chain(documentation.included.iter_mut());
+    for data in items {
+        let docs = match data.attributes.get("docs").and_then(|v| v.as_str()) {
+            Some(docs) => docs.to_string(),
+            None => continue,
+        };
+
+        let mut links: Vec<DocLink> = crate_paths(&docs, crate_name)
+            .into_iter()
+            .filter_map(|path| index.get(&path).map(|target| DocLink { path, target: target.clone() }))
+            .collect();
+        links.sort_by(|a, b| (&a.path, &a.target).cmp(&(&b.path, &b.target)));
+        links.dedup();
+
+        if !links.is_empty() {
+            data.attributes.insert("docLinks".to_string(), serde_json::to_value(&links).unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap as Map;
+
+    fn data(id: &str, qualname: &str, docs: &str) -> Data {
+        let mut attributes = Map::new();
+        attributes.insert("qualname".to_string(), serde_json::Value::String(qualname.to_string()));
+        attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        Data { id: id.to_string(), ty: "function".to_string(), attributes, relationships: None, links: None }
+    }
+
+    #[test]
+    fn a_forward_reference_to_an_item_declared_later_still_resolves() {
+        let mut documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("make_widget", "my_crate::make_widget", "See [`my_crate::Widget`].\n"), data("widget", "my_crate::Widget", "")],
+            meta: Map::new(),
+            links: None,
+        };
+
+        resolve_doc_links(&mut documentation, "my_crate");
+
+        let links = documentation.included[0].attributes.get("docLinks").unwrap();
+        assert_eq!(links, &serde_json::json!([{ "path": "my_crate::Widget", "target": "widget" }]));
+    }
+
+    #[test]
+    fn an_unresolvable_path_is_left_out() {
+        let mut documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("item", "my_crate::item", "See [`my_crate::Missing`].\n")],
+            meta: Map::new(),
+            links: None,
+        };
+
+        resolve_doc_links(&mut documentation, "my_crate");
+
+        assert!(!documentation.included[0].attributes.contains_key("docLinks"));
+    }
+
+    #[test]
+    fn docs_with_no_crate_paths_are_left_untouched() {
+        let mut documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("item", "my_crate::item", "Just some prose.\n")],
+            meta: Map::new(),
+            links: None,
+        };
+
+        resolve_doc_links(&mut documentation, "my_crate");
+
+        assert!(!documentation.included[0].attributes.contains_key("docLinks"));
+    }
+}
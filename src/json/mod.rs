@@ -0,0 +1,1305 @@
+//! Converting analysis data into the JSON-API-shaped documents that make up
+//! a crate's generated documentation.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use rls_analysis::{Def, DefKind, Id, Span};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::analysis::Analysis;
+use crate::error::*;
+use crate::examples::find_examples;
+use crate::relationship_kinds;
+use crate::write::{prune_stale, write_if_changed};
+
+/// A single JSON-API "resource object": one documented item.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Data {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub attributes: HashMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationships: Option<Relationships>,
+    /// `{"self": "<url>"}`, populated when `--base-url` is set (see
+    /// `crate::links`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<HashMap<String, String>>,
+}
+
+/// An item's JSON-API relationships, keyed by name (e.g. `"modules"`,
+/// `"usedBy"`), each holding a `{"data": ...}` object.
+///
+/// Serializes to exactly the shape it replaces
+/// (`HashMap<String, HashMap<String, VecOrData>>`); the typed accessors just
+/// keep a caller from building an invalid one, e.g. an inner map with a key
+/// other than `"data"`, or a to-many relationship holding a bare `Datum`.
+///
+/// Serialization order follows [`relationship_kinds::RELATIONSHIP_KINDS`]
+/// (any other key sorts alphabetically after them), so a frontend gets a
+/// stable section order instead of whatever a `HashMap` happens to iterate
+/// in; see [`Relationships`]'s manual `Serialize` impl below.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Relationships(HashMap<String, RelationshipEntry>);
+
+impl serde::Serialize for Relationships {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut ordered: Vec<(&str, &RelationshipEntry)> = self.0.iter().map(|(key, entry)| (key.as_str(), entry)).collect();
+        ordered.sort_by_key(|(key, _)| relationship_kinds::rank(key));
+
+        let mut map = serializer.serialize_map(Some(ordered.len()))?;
+        for (key, entry) in ordered {
+            map.serialize_entry(key, entry)?;
+        }
+        map.end()
+    }
+}
+
+/// A single relationship's JSON-API object: `{"data": ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationshipEntry {
+    pub data: VecOrData,
+}
+
+impl Relationships {
+    /// Whether there are no relationships at all.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Add `child` to the to-many relationship named `kind`, creating it
+    /// (empty) first if this is its first member.
+    pub fn add_child(&mut self, kind: impl Into<String>, child: Datum) {
+        let entry = self
+            .0
+            .entry(kind.into())
+            .or_insert_with(|| RelationshipEntry { data: VecOrData::Vec(Vec::new()) });
+        match &mut entry.data {
+            VecOrData::Vec(members) => members.push(child),
+            VecOrData::Data(existing) => entry.data = VecOrData::Vec(vec![existing.clone(), child]),
+        }
+    }
+
+    /// Set the to-one `parent` relationship, e.g. a method's parent
+    /// struct/enum.
+    pub fn set_parent(&mut self, parent: Datum) {
+        self.0.insert("parent".to_string(), RelationshipEntry { data: VecOrData::Data(parent) });
+    }
+
+    /// The `data` member of the relationship named `kind`, if it has one.
+    pub fn get(&self, kind: &str) -> Option<&VecOrData> {
+        self.0.get(kind).map(|entry| &entry.data)
+    }
+
+    /// Every relationship, as `(kind, data)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &VecOrData)> {
+        self.0.iter().map(|(kind, entry)| (kind.as_str(), &entry.data))
+    }
+
+    /// Drop any member of any relationship whose id isn't in `keep`, then
+    /// drop any relationship left with nothing in it.
+    pub fn retain(&mut self, keep: &HashSet<String>) {
+        for entry in self.0.values_mut() {
+            if let VecOrData::Vec(members) = &mut entry.data {
+                members.retain(|datum| keep.contains(&datum.id));
+            }
+        }
+
+        self.0.retain(|_, entry| match &entry.data {
+            VecOrData::Vec(members) => !members.is_empty(),
+            VecOrData::Data(datum) => keep.contains(&datum.id),
+        });
+    }
+}
+
+/// The value of a relationship's `data` member: either a single resource
+/// identifier (a "to-one" relationship, like a method's parent) or a list of
+/// them (a "to-many" relationship, like a module's items).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VecOrData {
+    Vec(Vec<Datum>),
+    Data(Datum),
+}
+
+impl VecOrData {
+    /// The members of this relationship, regardless of whether it's a
+    /// to-one or a to-many relationship.
+    pub fn as_slice(&self) -> Vec<&Datum> {
+        match self {
+            VecOrData::Vec(data) => data.iter().collect(),
+            VecOrData::Data(data) => vec![data],
+        }
+    }
+}
+
+/// A bare resource identifier, as used inside a relationship.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Datum {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// The full documentation generated for a crate.
+///
+/// This is a JSON-API document: `data` is the crate itself, and every other
+/// documented item lives in `included`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Documentation {
+    pub data: Data,
+    pub included: Vec<Data>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub meta: HashMap<String, Value>,
+    /// `{"self": "<base-url>"}`, populated when `--base-url` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<HashMap<String, String>>,
+}
+
+impl Documentation {
+    /// Load previously serialized documentation from `path`, auto-detecting
+    /// its format from the extension (see
+    /// [`crate::format::EmitFormat::detect_from_extension`]).
+    pub fn from_path(path: &Path) -> Result<Documentation> {
+        let format = crate::format::EmitFormat::detect_from_extension(path).ok_or_else(|| {
+            format!("couldn't tell what format '{}' is in from its extension", path.display())
+        })?;
+        let file = fs::File::open(path).chain_err(|| format!("failed to open '{}'", path.display()))?;
+        Documentation::from_reader(file, format)
+    }
+
+    /// Deserialize documentation from `reader` in the given `format`,
+    /// checking its format version against
+    /// [`crate::format::FORMAT_VERSION`]; see [`crate::format`].
+    pub fn from_reader<R: std::io::Read>(mut reader: R, format: crate::format::EmitFormat) -> Result<Documentation> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let documentation = crate::format::deserialize(&bytes, format)?;
+        crate::format::check_version(&documentation)?;
+        Ok(documentation)
+    }
+}
+
+/// Turn an analysis `Id` into the string we use as a JSON-API resource id.
+///
+/// `rls_analysis::Id` doesn't expose its inner value, so we fall back to its
+/// `Debug` output and strip the punctuation `Id(..)` leaves behind.
+pub(crate) fn def_id(id: Id) -> String {
+    format!("{:?}", id).replace(|c: char| !c.is_ascii_alphanumeric(), "")
+}
+
+/// The JSON-API `type` we emit for a given `DefKind`, or `None` for kinds we
+/// don't document (yet).
+fn kind_str(kind: DefKind) -> Option<&'static str> {
+    match kind {
+        DefKind::Mod => Some("module"),
+        DefKind::Struct | DefKind::Tuple | DefKind::StructVariant | DefKind::TupleVariant => {
+            Some("struct")
+        }
+        DefKind::Union => Some("union"),
+        DefKind::Enum => Some("enum"),
+        DefKind::Trait => Some("trait"),
+        DefKind::Function | DefKind::ForeignFunction => Some("function"),
+        DefKind::Method => Some("method"),
+        DefKind::Static | DefKind::ForeignStatic => Some("static"),
+        DefKind::Const => Some("constant"),
+        DefKind::Type => Some("type"),
+        _ => None,
+    }
+}
+
+fn build_data(id: Id, def: &Def) -> Option<Data> {
+    let ty = kind_str(def.kind)?;
+
+    let mut attributes = HashMap::new();
+    attributes.insert("name".to_string(), Value::String(def.name.clone()));
+    // The fully qualified name, e.g. `my_crate::module::Thing`, normalized
+    // (see `normalize_qualname`) so a nightly feature's symbol hashes and
+    // `{{impl}}` segments don't leak into it. Ids are only stable within a
+    // single analysis session, so `merge::merge_platforms` uses this (paired
+    // with an item's type) to recognize the same item across separately
+    // generated per-target documentation; normalizing it here also means
+    // that recognition doesn't break on a hash that happened to come out
+    // differently between two builds of the same item.
+    attributes.insert(
+        "qualname".to_string(),
+        Value::String(normalize_qualname(&def.qualname)),
+    );
+    attributes.insert("docs".to_string(), Value::String(def.docs.clone()));
+    // The displayed form of each fenced ```rust code block in `docs` (`# `
+    // hidden lines removed); the form actually compiled by
+    // `test::find_tests` isn't included here, since it's redundant with
+    // `docs` for anyone not running the examples.
+    attributes.insert(
+        "examples".to_string(),
+        Value::Array(
+            find_examples(&def.docs)
+                .into_iter()
+                .map(|example| Value::String(example.displayed))
+                .collect(),
+        ),
+    );
+    attributes.insert(
+        "span".to_string(),
+        serde_json::json!({
+            "file": def.span.file.to_string_lossy(),
+            "lineStart": def.span.range.row_start.0,
+            "lineEnd": def.span.range.row_end.0,
+        }),
+    );
+    // `rls_analysis::Def` (0.18.3) doesn't carry attribute data, so we have
+    // no way to tell a `#[deprecated]` item from any other yet; this is
+    // wired up so `test::find_tests`'s `--skip-deprecated-doctests` support
+    // works as soon as that data is available.
+    attributes.insert("deprecated".to_string(), Value::Bool(false));
+    if matches!(def.kind, DefKind::ForeignFunction | DefKind::ForeignStatic) {
+        attributes.insert("abi".to_string(), Value::String(extract_abi(&def.value)));
+    }
+    if matches!(def.kind, DefKind::Function | DefKind::ForeignFunction | DefKind::Method) {
+        attributes.insert("signature".to_string(), Value::String(def.value.clone()));
+        attributes.insert(
+            "notableTraits".to_string(),
+            Value::Array(
+                notable_traits(&def.value)
+                    .into_iter()
+                    .map(|trait_name| Value::String(trait_name.to_string()))
+                    .collect(),
+            ),
+        );
+
+        let is_async = is_async_fn(&def.value);
+        attributes.insert("asyncness".to_string(), Value::Bool(is_async));
+        attributes.insert(
+            "returnType".to_string(),
+            Value::String(display_return_type(&def.value, is_async)),
+        );
+    }
+
+    Some(Data {
+        id: def_id(id),
+        ty: ty.to_string(),
+        attributes,
+        relationships: None,
+        ..Default::default()
+    })
+}
+
+/// Parse a `#[doc(cfg(...))]` (or `#[cfg_attr(docsrs, doc(cfg(...)))]`)
+/// attribute's inner `cfg(...)` predicate into the structured value we'd
+/// emit as an item's `cfg` attribute, e.g. `feature = "x"` becomes
+/// `{"feature": "x"}`.
+///
+/// `rls_analysis::Def` (0.18.3) doesn't expose attribute tokens at all, so
+/// nothing in this crate can call this with real data yet; it's provided so
+/// a `cfg` attribute can be wired into [`build_data`] as soon as that data
+/// is available, without downstream frontends needing to change how they
+/// read it.
+pub fn parse_doc_cfg(predicate: &str) -> Option<Value> {
+    let predicate = predicate.trim();
+
+    if predicate.is_empty() {
+        return None;
+    }
+
+    for (key, json_key) in [("feature", "feature"), ("target_os", "targetOs")] {
+        if let Some(rest) = predicate.strip_prefix(key) {
+            let value = rest.trim().trim_start_matches('=').trim().trim_matches('"');
+            return Some(serde_json::json!({ json_key: value }));
+        }
+    }
+
+    Some(serde_json::json!({ "raw": predicate }))
+}
+
+/// Traits upstream rustdoc calls out with its "notable trait" ⓘ popover when
+/// they show up in a function's return type, paired with the label we emit
+/// for each.
+const NOTABLE_TRAITS: &[(&str, &str)] = &[
+    ("Iterator", "Iterator"),
+    ("Future", "Future"),
+    ("Read", "Read"),
+    ("Write", "Write"),
+];
+
+/// Guess which of [`NOTABLE_TRAITS`] a function's return type implements,
+/// from its raw signature text.
+///
+/// `rls_analysis::Def` (0.18.3) doesn't expose a structured return type or
+/// impl data, only the signature's raw `value` text (e.g. `fn foo() -> impl
+/// Iterator<Item = u32>`), so this looks for a notable trait's name as a
+/// whole word after the `->` rather than resolving the type properly. That
+/// catches `impl Trait` and directly named `dyn Trait`/std types (`std::io::
+/// Read`), but misses a return type that implements one of these traits
+/// without naming it, like a private iterator struct.
+fn notable_traits(signature: &str) -> Vec<&'static str> {
+    let return_type = match signature.split_once("->") {
+        Some((_, return_type)) => return_type,
+        None => return Vec::new(),
+    };
+
+    NOTABLE_TRAITS
+        .iter()
+        .filter(|(name, _)| contains_word(return_type, name))
+        .map(|(_, label)| *label)
+        .collect()
+}
+
+/// Whether `word` appears in `text` as a whole word, i.e. not as part of a
+/// longer identifier like `Already` inside `AlreadyRead`.
+fn contains_word(text: &str, word: &str) -> bool {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_').any(|token| token == word)
+}
+
+/// Whether a function's raw signature text declares it `async`.
+///
+/// `rls_analysis::Def` (0.18.3) has no dedicated field for this, so, like
+/// [`notable_traits`], this just looks at [`rls_analysis::Def::value`]'s raw
+/// text; a signature this crate doesn't otherwise document (a macro-
+/// generated one with unusual leading tokens) could in principle slip past
+/// this check, but every signature `rustc` itself emits starts with `async`
+/// right where this looks for it.
+fn is_async_fn(signature: &str) -> bool {
+    signature.trim_start().starts_with("async ")
+}
+
+/// The function's return type, exactly as written after `->` (or `()` if
+/// there's no `->` at all), de-sugaring an `async fn`'s implicit
+/// `impl Future<Output = T>` since `Def::value` reports an async function's
+/// literal, sugared return type (`T`) the same as any other function's.
+fn display_return_type(signature: &str, is_async: bool) -> String {
+    let return_type = match signature.split_once("->") {
+        Some((_, return_type)) => return_type.trim(),
+        None => "()",
+    };
+
+    if is_async {
+        format!("impl Future<Output = {}>", return_type)
+    } else {
+        return_type.to_string()
+    }
+}
+
+/// A foreign function or static's ABI, parsed out of its raw signature text
+/// (e.g. `extern "system" fn ...`), defaulting to `"C"` — the ABI rustc
+/// itself assumes for an `extern { ... }` block with no string literal.
+fn extract_abi(signature: &str) -> String {
+    signature
+        .split_once("extern")
+        .and_then(|(_, rest)| rest.trim_start().strip_prefix('"'))
+        .and_then(|rest| rest.split_once('"'))
+        .map(|(abi, _)| abi.to_string())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// Tidy a `Def::qualname` for display and for use as a doctest name,
+/// stripping the noise nightly-only features leave in it: a monomorphization
+/// symbol hash suffix (`::h1a2b3c4d5e6f7089`) and anonymous `{{impl}}`
+/// segments (from an inherent impl or a `macro_rules!`-generated item
+/// save-analysis can't otherwise name).
+///
+/// Only segments matching those two shapes are touched; every other segment
+/// is passed through untouched, since dropping a real user identifier here
+/// would be worse than missing an unfamiliar variant of either shape.
+pub fn normalize_qualname(qualname: &str) -> String {
+    qualname
+        .split("::")
+        .filter_map(|segment| {
+            if is_symbol_hash(segment) {
+                None
+            } else if segment == "{{impl}}" {
+                Some("<impl>")
+            } else {
+                Some(segment)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Whether `segment` looks like a `rustc` symbol hash, e.g. `h1a2b3c4d5e6f708`.
+fn is_symbol_hash(segment: &str) -> bool {
+    segment.len() > 1
+        && segment.starts_with('h')
+        && segment[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn to_relationships(grouped: HashMap<String, Vec<Datum>>) -> Option<Relationships> {
+    if grouped.is_empty() {
+        return None;
+    }
+
+    let mut relationships = Relationships::default();
+    for (kind, members) in grouped {
+        for member in members {
+            relationships.add_child(kind.clone(), member);
+        }
+    }
+    Some(relationships)
+}
+
+/// Find every impl block for `type_id` (see
+/// [`rls_analysis::AnalysisHost::find_impls`]) and document its methods,
+/// pushing each into `included` with its `parent` relationship set to
+/// `parent` (the struct/enum/union being walked), and returning a `Datum`
+/// for each so the caller can record them under that type's own `"methods"`
+/// relationship, alongside the raw impl spans themselves so the caller can
+/// cross-reference them against a trait's own impl spans (see
+/// [`apply_implementations`]).
+///
+/// Trait default methods aren't picked up here: `rls_analysis` (0.18.3)
+/// indexes `impls` by concrete self type, not by trait, so a trait's own
+/// un-overridden methods have no impl block to walk, and they're simply
+/// dropped.
+///
+/// A method whose path is listed in `exclude`, or whose own doc comment
+/// carries the `<!-- rustdoc:skip -->` marker (see [`crate::exclude`]), is
+/// left out the same way an excluded top-level item is in [`walk`].
+fn walk_impls(analysis: &Analysis, type_id: Id, parent: Datum, exclude: &[String], included: &mut Vec<Data>) -> Result<(Vec<Datum>, Vec<Span>)> {
+    let impl_spans = analysis.host.find_impls(type_id).map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    let mut methods = Vec::new();
+    for span in &impl_spans {
+        let impl_id = match analysis.host.id(span) {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+
+        let children = analysis
+            .for_each_child_def(impl_id, |child_id, def| (child_id, def.clone()))
+            .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+        for (child_id, def) in children {
+            if def.kind != DefKind::Method {
+                continue;
+            }
+            let qualname = normalize_qualname(&def.qualname);
+            if crate::exclude::is_excluded_path(&qualname, exclude) || crate::exclude::is_marked_skip(&def.docs) {
+                continue;
+            }
+            if let Some(mut data) = build_data(child_id, &def) {
+                let mut relationships = Relationships::default();
+                relationships.set_parent(parent.clone());
+                data.relationships = Some(relationships);
+                methods.push(Datum { id: data.id.clone(), ty: data.ty.clone() });
+                included.push(data);
+            }
+        }
+    }
+
+    Ok((methods, impl_spans))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn walk(
+    analysis: &Analysis,
+    id: Id,
+    exclude: &[String],
+    included: &mut Vec<Data>,
+    relationships: &mut HashMap<String, Vec<Datum>>,
+    function_signatures: &mut Vec<(String, String)>,
+    skipped_with_docs: &mut Vec<String>,
+    type_impls: &mut Vec<(Datum, Vec<Span>)>,
+    trait_impls: &mut Vec<(Datum, Vec<Span>)>,
+) -> Result<()> {
+    let children = analysis
+        .for_each_child_def(id, |child_id, def| (child_id, def.clone()))
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    for (child_id, def) in children {
+        let qualname = normalize_qualname(&def.qualname);
+        if crate::exclude::is_excluded_path(&qualname, exclude) || crate::exclude::is_marked_skip(&def.docs) {
+            continue;
+        }
+
+        match def.kind {
+            DefKind::Mod => {
+                if let Some(data) = build_data(child_id, &def) {
+                    relationships
+                        .entry(relationship_kinds::plural(&data.ty))
+                        .or_default()
+                        .push(Datum {
+                            id: data.id.clone(),
+                            ty: data.ty.clone(),
+                        });
+                    included.push(data);
+                }
+                walk(
+                    analysis,
+                    child_id,
+                    exclude,
+                    included,
+                    relationships,
+                    function_signatures,
+                    skipped_with_docs,
+                    type_impls,
+                    trait_impls,
+                )?;
+            }
+            DefKind::Struct | DefKind::Tuple | DefKind::Union | DefKind::Enum => {
+                if let Some(mut data) = build_data(child_id, &def) {
+                    let parent = Datum { id: data.id.clone(), ty: data.ty.clone() };
+                    let (methods, impl_spans) = walk_impls(analysis, child_id, parent.clone(), exclude, included)?;
+                    if !methods.is_empty() {
+                        let data_relationships = data.relationships.get_or_insert_with(Relationships::default);
+                        for method in methods {
+                            data_relationships.add_child("methods", method);
+                        }
+                    }
+                    type_impls.push((parent.clone(), impl_spans));
+                    relationships
+                        .entry(relationship_kinds::plural(&data.ty))
+                        .or_default()
+                        .push(parent);
+                    included.push(data);
+                }
+            }
+            DefKind::Trait => {
+                if let Some(data) = build_data(child_id, &def) {
+                    let datum = Datum { id: data.id.clone(), ty: data.ty.clone() };
+                    let impl_spans = analysis.host.find_impls(child_id).map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+                    trait_impls.push((datum.clone(), impl_spans));
+                    relationships.entry(relationship_kinds::plural(&data.ty)).or_default().push(datum);
+                    included.push(data);
+                }
+            }
+            DefKind::Function | DefKind::ForeignFunction => {
+                if let Some(data) = build_data(child_id, &def) {
+                    relationships
+                        .entry(relationship_kinds::plural(&data.ty))
+                        .or_default()
+                        .push(Datum {
+                            id: data.id.clone(),
+                            ty: data.ty.clone(),
+                        });
+                    function_signatures.push((data.id.clone(), def.value.clone()));
+                    included.push(data);
+                }
+            }
+            DefKind::ForeignStatic => {
+                if let Some(data) = build_data(child_id, &def) {
+                    relationships
+                        .entry(relationship_kinds::plural(&data.ty))
+                        .or_default()
+                        .push(Datum {
+                            id: data.id.clone(),
+                            ty: data.ty.clone(),
+                        });
+                    included.push(data);
+                }
+            }
+            _ => {
+                if !def.docs.trim().is_empty() {
+                    skipped_with_docs.push(format!("{} ({:?})", normalize_qualname(&def.qualname), def.kind));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reverse-reference pass: for each documented struct/enum/union/trait,
+/// record a `usedBy` relationship listing every documented function whose
+/// signature mentions its name, e.g. `fn parse(input: &str) -> Widget`
+/// making `Widget` `usedBy` `parse`. This is a "find usages in the API"
+/// view rustdoc itself doesn't offer.
+///
+/// Matched the same way [`notable_traits`] matches a return type: against
+/// [`rls_analysis::Def::value`]'s raw signature text, since `rls_analysis`
+/// (0.18.3) exposes no structured type-reference data to check against
+/// instead. This can both miss real usages (a type only reachable through a
+/// type alias) and over-match (a same-named type from an unrelated crate,
+/// or a generic parameter that happens to share a documented type's name).
+fn apply_used_by(included: &mut [Data], function_signatures: &[(String, String)]) {
+    let types: Vec<(String, String)> = included
+        .iter()
+        .filter(|data| matches!(data.ty.as_str(), "struct" | "enum" | "union" | "trait"))
+        .filter_map(|data| {
+            data.attributes
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|name| (data.id.clone(), name.to_string()))
+        })
+        .collect();
+
+    let mut used_by: HashMap<String, Vec<Datum>> = HashMap::new();
+    for (type_id, type_name) in &types {
+        for (function_id, signature) in function_signatures {
+            if contains_word(signature, type_name) {
+                used_by.entry(type_id.clone()).or_default().push(Datum {
+                    id: function_id.clone(),
+                    ty: "function".to_string(),
+                });
+            }
+        }
+    }
+
+    for data in included.iter_mut() {
+        if let Some(functions) = used_by.remove(&data.id) {
+            let relationships = data.relationships.get_or_insert_with(Relationships::default);
+            for function in functions {
+                relationships.add_child("usedBy", function);
+            }
+        }
+    }
+}
+
+/// Reverse-reference pass: for each documented struct/enum/union and each
+/// documented trait, record an `implementations` relationship on both sides
+/// wherever the two share an impl block.
+///
+/// `rls_analysis` (0.18.3) exposes no direct way to ask "what trait does
+/// this impl block implement" — `find_impls` only answers "what impl
+/// blocks touch this id", from either the self type's or the trait's side
+/// of the relation, and pushes the exact same [`Span`] onto both sides when
+/// an impl block implements a trait (see [`walk`], which calls it for
+/// every struct/enum/union and every trait it walks). So a type and a
+/// trait are matched here by looking for a span in common between their
+/// two `find_impls` results, rather than by anything more direct.
+fn apply_implementations(included: &mut [Data], type_impls: &[(Datum, Vec<Span>)], trait_impls: &[(Datum, Vec<Span>)]) {
+    let mut extra: HashMap<String, Vec<Datum>> = HashMap::new();
+
+    for (type_datum, type_spans) in type_impls {
+        for (trait_datum, trait_spans) in trait_impls {
+            if type_spans.iter().any(|span| trait_spans.contains(span)) {
+                extra.entry(type_datum.id.clone()).or_default().push(trait_datum.clone());
+                extra.entry(trait_datum.id.clone()).or_default().push(type_datum.clone());
+            }
+        }
+    }
+
+    for data in included.iter_mut() {
+        if let Some(members) = extra.remove(&data.id) {
+            let relationships = data.relationships.get_or_insert_with(Relationships::default);
+            for member in members {
+                relationships.add_child("implementations", member);
+            }
+        }
+    }
+}
+
+/// Find the module def named `target_qualname` (already normalized, see
+/// [`normalize_qualname`]) among `id`'s descendants, searching modules
+/// depth-first since a target path can be nested arbitrarily deep.
+fn find_module(analysis: &Analysis, id: Id, target_qualname: &str) -> Result<Option<(Id, Def)>> {
+    let children = analysis
+        .for_each_child_def(id, |child_id, def| (child_id, def.clone()))
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    for (child_id, def) in children {
+        if def.kind != DefKind::Mod {
+            continue;
+        }
+        if normalize_qualname(&def.qualname) == target_qualname {
+            return Ok(Some((child_id, def)));
+        }
+        if let Some(found) = find_module(analysis, child_id, target_qualname)? {
+            return Ok(Some(found));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Walk the analysis data for `crate_name` and produce its `Documentation`.
+///
+/// If `root` is set (e.g. `"submodule::inner"`), only that module's subtree
+/// is walked, and it becomes `Documentation::data` itself instead of the
+/// crate. This is meant for previewing one area of a large crate without
+/// paying for a full build; a `documentedRoot` entry is added to `meta` so a
+/// consumer can tell the result isn't the whole crate.
+///
+/// Item ids stay exactly what [`def_id`] always produces (an opaque,
+/// per-analysis-session identifier, unrelated to path depth), so there's no
+/// "full" id to make relative here; only `data`'s own role as the walk's
+/// starting point changes.
+///
+/// Also returns a description of every item found with a doc comment on a
+/// kind [`walk`] doesn't emit (e.g. a local, or a kind not yet listed in
+/// [`kind_str`]), so a caller can warn that those docs won't make it into
+/// the output rather than letting them silently disappear.
+///
+/// `exclude` lists item paths (see [`crate::exclude`]) to leave out of the
+/// walk entirely, along with everything nested under them; an item marked
+/// `<!-- rustdoc:skip -->` in its own doc comment is left out the same way
+/// without needing to be listed.
+pub fn create_documentation(
+    analysis: &Analysis,
+    crate_name: &str,
+    root: Option<&str>,
+    exclude: &[String],
+) -> Result<(Documentation, Vec<String>)> {
+    let roots = analysis
+        .host
+        .def_roots()
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    let (crate_id, _) = roots
+        .into_iter()
+        .find(|(_, name)| name == crate_name)
+        .ok_or_else(|| ErrorKind::CrateErr(crate_name.to_string()))?;
+
+    let crate_def = analysis
+        .get_def(crate_id)
+        .map_err(|e| ErrorKind::Analysis(format!("{:?}", e)))?;
+
+    let (start_id, start_def) = match root {
+        None => (crate_id, crate_def),
+        Some(root) => {
+            let target_qualname = format!("{}::{}", crate_name, root);
+            find_module(analysis, crate_id, &target_qualname)?
+                .ok_or_else(|| format!("no module '{}' found in crate '{}'", root, crate_name))?
+        }
+    };
+
+    let mut included = Vec::new();
+    let mut relationships = HashMap::new();
+    let mut function_signatures = Vec::new();
+    let mut skipped_with_docs = Vec::new();
+    let mut type_impls = Vec::new();
+    let mut trait_impls = Vec::new();
+    walk(
+        analysis,
+        start_id,
+        exclude,
+        &mut included,
+        &mut relationships,
+        &mut function_signatures,
+        &mut skipped_with_docs,
+        &mut type_impls,
+        &mut trait_impls,
+    )?;
+    apply_used_by(&mut included, &function_signatures);
+    apply_implementations(&mut included, &type_impls, &trait_impls);
+
+    let mut data = build_data(start_id, &start_def).unwrap_or_else(|| Data {
+        id: def_id(start_id),
+        ty: "crate".to_string(),
+        attributes: HashMap::new(),
+        relationships: None,
+        ..Default::default()
+    });
+    if root.is_none() {
+        data.ty = "crate".to_string();
+    }
+    data.relationships = to_relationships(relationships);
+
+    let mut meta = HashMap::new();
+    meta.insert(
+        "formatVersion".to_string(),
+        Value::Number(crate::format::FORMAT_VERSION.into()),
+    );
+    if let Some(root) = root {
+        meta.insert("documentedRoot".to_string(), Value::String(root.to_string()));
+    }
+
+    let documentation = Documentation {
+        data,
+        included,
+        meta,
+        ..Default::default()
+    };
+
+    if cfg!(debug_assertions) {
+        let errors = crate::validate::check_consistency(&documentation);
+        assert!(errors.is_empty(), "generated documentation failed its own consistency check: {:#?}", errors);
+    }
+
+    Ok((documentation, skipped_with_docs))
+}
+
+/// Truncate any `docs` attribute longer than `max_size` bytes, writing the
+/// full text to a side file under `docs_dir` and pointing to it with a
+/// `docsRef` attribute.
+///
+/// This keeps the main `data.json` small for crates with huge module docs
+/// (often from `include_str!`), while still making the full text available
+/// to frontends willing to fetch it lazily. Side files are only rewritten
+/// when their contents change, and side files left behind by items that no
+/// longer split (or no longer exist) are removed, so repeated builds into
+/// the same `docs_dir` never accumulate stale files.
+///
+/// Returns a description of every item that got split (its id and original
+/// size), so a caller can warn about crates whose generated code (often
+/// `include_str!`) is bloating every downstream artifact.
+pub fn split_long_docs(
+    documentation: &mut Documentation,
+    docs_dir: &Path,
+    max_size: usize,
+) -> Result<Vec<String>> {
+    let items: Vec<&mut Data> = std::iter::once(&mut documentation.data)
+        .chain(documentation.included.iter_mut())
+        .collect();
+
+    let mut kept = HashSet::new();
+    let mut offenders = Vec::new();
+
+    for data in items {
+        let docs = match data.attributes.get("docs").and_then(Value::as_str) {
+            Some(docs) => docs.to_string(),
+            None => continue,
+        };
+
+        if docs.len() <= max_size {
+            continue;
+        }
+
+        fs::create_dir_all(docs_dir)?;
+
+        let file_name = format!("{}.txt", data.id);
+        write_if_changed(&docs_dir.join(&file_name), docs.as_bytes())?;
+        kept.insert(OsString::from(&file_name));
+
+        offenders.push(format!("{} ({} bytes)", data.id, docs.len()));
+
+        let (truncated, _) = docs.split_at(floor_char_boundary(&docs, max_size));
+        data.attributes
+            .insert("docs".to_string(), Value::String(truncated.to_string()));
+        data.attributes.insert(
+            "docsRef".to_string(),
+            Value::String(format!("docs/{}", file_name)),
+        );
+    }
+
+    prune_stale(docs_dir, &kept)?;
+
+    Ok(offenders)
+}
+
+/// Truncate `documentation.included` to at most `max_items`, so a
+/// pathological crate (huge generated code) produces usable partial docs
+/// instead of a `data.json` too big to load. Modules are kept ahead of
+/// everything else, so a caller browsing by directory structure still gets
+/// a complete module tree even when individual items within it are cut;
+/// beyond that, items are kept in the order `walk` found them.
+///
+/// This can't prioritize *public* modules specifically: `rls_analysis::Def`
+/// (0.18.3) carries no visibility field, so (like every other item `walk`
+/// documents) there's no way to tell a `pub mod` from a private one yet.
+///
+/// Every remaining item's relationships (and the crate's own) are filtered
+/// to drop any reference to an item this cut, so the result never points at
+/// an id that isn't actually in `included`. A `truncated` entry is added to
+/// `meta` recording how much was dropped.
+pub fn limit_items(documentation: &mut Documentation, max_items: usize) {
+    let total = documentation.included.len();
+    if total <= max_items {
+        return;
+    }
+
+    let mut prioritized: Vec<&Data> = documentation.included.iter().collect();
+    prioritized.sort_by_key(|data| if data.ty == "module" { 0 } else { 1 });
+    let keep: HashSet<String> = prioritized
+        .into_iter()
+        .take(max_items)
+        .map(|data| data.id.clone())
+        .collect();
+
+    documentation.included.retain(|data| keep.contains(&data.id));
+
+    for data in std::iter::once(&mut documentation.data).chain(documentation.included.iter_mut()) {
+        retain_relationships(data, &keep);
+    }
+
+    documentation.meta.insert(
+        "truncated".to_string(),
+        serde_json::json!({
+            "maxItems": max_items,
+            "totalItems": total,
+            "droppedItems": total - documentation.included.len(),
+        }),
+    );
+}
+
+/// Drop any relationship member of `data` that doesn't survive in `keep`,
+/// removing the relationship entirely once it has nothing left.
+fn retain_relationships(data: &mut Data, keep: &HashSet<String>) {
+    let relationships = match &mut data.relationships {
+        Some(relationships) => relationships,
+        None => return,
+    };
+
+    relationships.retain(keep);
+
+    if relationships.is_empty() {
+        data.relationships = None;
+    }
+}
+
+/// Like the unstable `str::floor_char_boundary`: the largest index `<= len`
+/// that lands on a UTF-8 character boundary, so truncating never panics on
+/// multi-byte characters.
+fn floor_char_boundary(s: &str, len: usize) -> usize {
+    if len >= s.len() {
+        return s.len();
+    }
+
+    let mut index = len;
+    while index > 0 && !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path_round_trips_through_each_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let documentation = Documentation {
+            data: plain_data("crate", "crate"),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        for (extension, format) in [
+            ("json", crate::format::EmitFormat::Json),
+            ("yaml", crate::format::EmitFormat::Yaml),
+            ("msgpack", crate::format::EmitFormat::MessagePack),
+        ] {
+            let path = dir.path().join(format!("data.{}", extension));
+            let bytes = crate::format::serialize(&documentation, format).unwrap();
+            fs::write(&path, bytes).unwrap();
+
+            let loaded = Documentation::from_path(&path).unwrap();
+            assert_eq!(loaded.data.id, documentation.data.id);
+        }
+    }
+
+    #[test]
+    fn from_path_rejects_an_unrecognized_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        fs::write(&path, b"whatever").unwrap();
+
+        assert!(Documentation::from_path(&path).is_err());
+    }
+
+    #[test]
+    fn from_reader_rejects_a_mismatched_format_version() {
+        let mut documentation = Documentation {
+            data: plain_data("crate", "crate"),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+        documentation.meta.insert(
+            "formatVersion".to_string(),
+            Value::from(crate::format::FORMAT_VERSION + 1),
+        );
+
+        let bytes = crate::format::serialize(&documentation, crate::format::EmitFormat::Json).unwrap();
+        assert!(Documentation::from_reader(bytes.as_slice(), crate::format::EmitFormat::Json).is_err());
+    }
+
+    fn data_with_docs(id: &str, docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("docs".to_string(), Value::String(docs.to_string()));
+        Data {
+            id: id.to_string(),
+            ty: "module".to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn leaves_short_docs_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut documentation = Documentation {
+            data: data_with_docs("crate", "short"),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let offenders = split_long_docs(&mut documentation, dir.path(), 100).unwrap();
+
+        assert_eq!(
+            documentation.data.attributes.get("docs").unwrap(),
+            &Value::String("short".to_string())
+        );
+        assert!(!documentation.data.attributes.contains_key("docsRef"));
+        assert!(offenders.is_empty());
+    }
+
+    #[test]
+    fn splits_long_docs_into_a_side_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let long_docs = "a".repeat(200);
+        let mut documentation = Documentation {
+            data: data_with_docs("crate", &long_docs),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let offenders = split_long_docs(&mut documentation, dir.path(), 100).unwrap();
+
+        let truncated = documentation.data.attributes.get("docs").unwrap().as_str().unwrap();
+        assert_eq!(truncated.len(), 100);
+
+        let docs_ref = documentation.data.attributes.get("docsRef").unwrap().as_str().unwrap();
+        let full = fs::read_to_string(dir.path().join(docs_ref.trim_start_matches("docs/"))).unwrap();
+        assert_eq!(full, long_docs);
+
+        assert_eq!(offenders, vec!["crate (200 bytes)".to_string()]);
+    }
+
+    #[test]
+    fn parses_a_feature_predicate() {
+        assert_eq!(
+            parse_doc_cfg(r#"feature = "async""#),
+            Some(serde_json::json!({ "feature": "async" }))
+        );
+    }
+
+    #[test]
+    fn parses_a_target_os_predicate() {
+        assert_eq!(
+            parse_doc_cfg(r#"target_os = "linux""#),
+            Some(serde_json::json!({ "targetOs": "linux" }))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_raw_for_unrecognized_predicates() {
+        assert_eq!(
+            parse_doc_cfg("unix"),
+            Some(serde_json::json!({ "raw": "unix" }))
+        );
+    }
+
+    #[test]
+    fn empty_predicate_is_none() {
+        assert_eq!(parse_doc_cfg("   "), None);
+    }
+
+    #[test]
+    fn detects_an_impl_iterator_return_type() {
+        assert_eq!(
+            notable_traits("fn foo() -> impl Iterator<Item = u32>"),
+            vec!["Iterator"]
+        );
+    }
+
+    #[test]
+    fn detects_a_qualified_read_return_type() {
+        assert_eq!(notable_traits("fn foo() -> std::io::Read"), vec!["Read"]);
+    }
+
+    #[test]
+    fn detects_more_than_one_notable_trait() {
+        let traits = notable_traits("fn foo() -> Box<dyn Read + Write>");
+        assert!(traits.contains(&"Read"));
+        assert!(traits.contains(&"Write"));
+    }
+
+    #[test]
+    fn does_not_match_a_notable_trait_name_as_a_substring() {
+        assert_eq!(notable_traits("fn foo() -> AlreadyRead"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn plain_return_types_have_no_notable_traits() {
+        assert_eq!(notable_traits("fn foo() -> u32"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn a_signature_without_a_return_type_has_no_notable_traits() {
+        assert_eq!(notable_traits("fn foo()"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn an_async_fn_signature_is_detected_as_async() {
+        assert!(is_async_fn("async fn foo() -> Widget"));
+    }
+
+    #[test]
+    fn a_plain_fn_signature_is_not_async() {
+        assert!(!is_async_fn("fn foo() -> Widget"));
+    }
+
+    #[test]
+    fn does_not_mistake_a_name_starting_with_async_for_the_keyword() {
+        assert!(!is_async_fn("fn asyncish_helper()"));
+    }
+
+    #[test]
+    fn an_async_fns_return_type_is_desugared_to_an_impl_future() {
+        assert_eq!(
+            display_return_type("async fn foo() -> Widget", true),
+            "impl Future<Output = Widget>"
+        );
+    }
+
+    #[test]
+    fn an_async_fn_with_no_return_type_desugars_to_a_unit_future() {
+        assert_eq!(display_return_type("async fn foo()", true), "impl Future<Output = ()>");
+    }
+
+    #[test]
+    fn a_plain_fns_return_type_is_left_as_written() {
+        assert_eq!(display_return_type("fn foo() -> Widget", false), "Widget");
+    }
+
+    #[test]
+    fn strips_a_symbol_hash_suffix() {
+        assert_eq!(
+            normalize_qualname("my_crate::Thing::foo::h1a2b3c4d5e6f708"),
+            "my_crate::Thing::foo"
+        );
+    }
+
+    #[test]
+    fn renders_an_anonymous_impl_segment_readably() {
+        assert_eq!(
+            normalize_qualname("my_crate::{{impl}}::foo"),
+            "my_crate::<impl>::foo"
+        );
+    }
+
+    #[test]
+    fn leaves_an_ordinary_qualname_untouched() {
+        assert_eq!(normalize_qualname("my_crate::module::Thing"), "my_crate::module::Thing");
+    }
+
+    #[test]
+    fn does_not_mistake_a_word_starting_with_h_for_a_hash() {
+        assert_eq!(normalize_qualname("my_crate::hello"), "my_crate::hello");
+    }
+
+    fn type_data(id: &str, ty: &str, name: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), Value::String(name.to_string()));
+        Data {
+            id: id.to_string(),
+            ty: ty.to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_a_function_that_mentions_a_type_in_its_signature() {
+        let mut included = vec![type_data("widget", "struct", "Widget")];
+        let signatures = vec![("make_widget".to_string(), "fn make_widget() -> Widget".to_string())];
+
+        apply_used_by(&mut included, &signatures);
+
+        let relationships = included[0].relationships.as_ref().unwrap();
+        let usages = relationships.get("usedBy").unwrap().as_slice();
+        assert_eq!(usages[0].id, "make_widget");
+        assert_eq!(usages[0].ty, "function");
+    }
+
+    #[test]
+    fn a_type_name_used_as_a_substring_does_not_count() {
+        let mut included = vec![type_data("widget", "struct", "Widget")];
+        let signatures = vec![("make_gadget".to_string(), "fn make_gadget() -> SuperWidgetFactory".to_string())];
+
+        apply_used_by(&mut included, &signatures);
+
+        assert!(included[0].relationships.is_none());
+    }
+
+    #[test]
+    fn a_type_with_no_mentions_gets_no_used_by_relationship() {
+        let mut included = vec![type_data("widget", "struct", "Widget")];
+        let signatures = vec![("do_thing".to_string(), "fn do_thing() -> u32".to_string())];
+
+        apply_used_by(&mut included, &signatures);
+
+        assert!(included[0].relationships.is_none());
+    }
+
+    fn plain_data(id: &str, ty: &str) -> Data {
+        Data {
+            id: id.to_string(),
+            ty: ty.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn under_the_limit_is_left_untouched() {
+        let mut documentation = Documentation {
+            included: vec![plain_data("a", "function"), plain_data("b", "function")],
+            ..Default::default()
+        };
+
+        limit_items(&mut documentation, 5);
+
+        assert_eq!(documentation.included.len(), 2);
+        assert!(!documentation.meta.contains_key("truncated"));
+    }
+
+    #[test]
+    fn modules_are_kept_ahead_of_other_items_when_truncating() {
+        let mut documentation = Documentation {
+            included: vec![
+                plain_data("fn_a", "function"),
+                plain_data("mod_a", "module"),
+                plain_data("fn_b", "function"),
+            ],
+            ..Default::default()
+        };
+
+        limit_items(&mut documentation, 1);
+
+        assert_eq!(documentation.included.len(), 1);
+        assert_eq!(documentation.included[0].id, "mod_a");
+    }
+
+    #[test]
+    fn truncation_is_recorded_in_meta() {
+        let mut documentation = Documentation {
+            included: vec![plain_data("a", "function"), plain_data("b", "function")],
+            ..Default::default()
+        };
+
+        limit_items(&mut documentation, 1);
+
+        assert_eq!(
+            documentation.meta.get("truncated").unwrap(),
+            &serde_json::json!({"maxItems": 1, "totalItems": 2, "droppedItems": 1})
+        );
+    }
+
+    #[test]
+    fn dangling_relationship_members_are_dropped_along_with_their_item() {
+        let mut kept = type_data("widget", "struct", "Widget");
+        let mut relationships = Relationships::default();
+        relationships.add_child(
+            "usedBy",
+            Datum {
+                id: "make_widget".to_string(),
+                ty: "function".to_string(),
+            },
+        );
+        kept.relationships = Some(relationships);
+
+        let mut documentation = Documentation {
+            included: vec![kept, plain_data("make_widget", "function")],
+            ..Default::default()
+        };
+
+        limit_items(&mut documentation, 1);
+
+        assert_eq!(documentation.included.len(), 1);
+        assert!(documentation.included[0].relationships.is_none());
+    }
+}
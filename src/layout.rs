@@ -0,0 +1,256 @@
+//! Capturing `#[repr(...)]` attributes, and (behind [`Config::layout`]) the
+//! compiled size and alignment, of a crate's structs/enums/unions.
+//!
+//! `rls_analysis::Def` (0.18.3) exposes no attribute tokens at all (see the
+//! same gap noted in [`crate::json::build_data`]), so `repr` is captured the
+//! same way [`crate::reconcile`] fills its own analysis gap: by parsing the
+//! crate's entry file with `syn` directly and matching what it finds back to
+//! [`Documentation`] items by name.
+//!
+//! Size and alignment can't come from `syn` at all — they depend on the
+//! compiler's actual layout algorithm, which `syn` (a syntax-only parser)
+//! has no way to run. Getting a real answer means asking `rustc`: this
+//! generates a throwaway binary crate depending on the target crate by path
+//! (mirroring [`crate::test::compile_and_run_with_cargo`]) that prints every
+//! probed type's `size_of`/`align_of`, then runs it with `cargo run` and
+//! parses its output. A generic struct/enum/union can't be probed this way
+//! without knowing what to instantiate it with, and `syn` doesn't tell us
+//! anything about a type's actual usage sites, so any item with generic
+//! parameters is simply left out of the probe rather than guessed at.
+
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Serialize;
+use syn::visit::{self, Visit};
+use syn::Visibility;
+
+use crate::command::ProcessRunner;
+use crate::error::*;
+use crate::json::Documentation;
+
+/// A `struct`/`enum`/`union` found in the crate's own source, with whatever
+/// `#[repr(...)]` attribute it carries (if any) and whether it has generic
+/// parameters (which rules it out of [`probe_layouts`]).
+struct ReprItem {
+    name: String,
+    repr: Option<String>,
+    has_generics: bool,
+}
+
+/// The compiled size and alignment (in bytes) of one documented type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeLayout {
+    pub name: String,
+    pub size: usize,
+    pub align: usize,
+}
+
+#[derive(Default)]
+struct ReprCollector {
+    items: Vec<ReprItem>,
+}
+
+impl ReprCollector {
+    fn record(&mut self, vis: &Visibility, name: &syn::Ident, has_generics: bool, attrs: &[syn::Attribute]) {
+        if !matches!(vis, Visibility::Public(_)) {
+            return;
+        }
+
+        let repr = attrs.iter().find(|attr| attr.path().is_ident("repr")).and_then(|attr| match &attr.meta {
+            syn::Meta::List(list) => Some(list.tokens.to_string()),
+            _ => None,
+        });
+
+        self.items.push(ReprItem { name: name.to_string(), repr, has_generics });
+    }
+}
+
+impl<'ast> Visit<'ast> for ReprCollector {
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        self.record(&node.vis, &node.ident, !node.generics.params.is_empty(), &node.attrs);
+        visit::visit_item_struct(self, node);
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        self.record(&node.vis, &node.ident, !node.generics.params.is_empty(), &node.attrs);
+        visit::visit_item_enum(self, node);
+    }
+
+    fn visit_item_union(&mut self, node: &'ast syn::ItemUnion) {
+        self.record(&node.vis, &node.ident, !node.generics.params.is_empty(), &node.attrs);
+        visit::visit_item_union(self, node);
+    }
+}
+
+/// Parse `entry_path` with `syn` and collect every public struct/enum/union
+/// it defines.
+fn collect_repr_items(entry_path: &Path) -> Result<Vec<ReprItem>> {
+    let source = fs::read_to_string(entry_path).chain_err(|| format!("failed to read '{}'", entry_path.display()))?;
+    let file = syn::parse_file(&source).chain_err(|| format!("failed to parse '{}' with syn", entry_path.display()))?;
+
+    let mut collector = ReprCollector::default();
+    collector.visit_file(&file);
+    Ok(collector.items)
+}
+
+/// Parse `entry_path` and set a `repr` attribute on every matching
+/// struct/enum/union in `documentation` that carries a `#[repr(...)]`.
+/// Items with no `#[repr(...)]` (the common case: the compiler's default
+/// layout) are left untouched rather than given an explicit `"rust"` value,
+/// since `syn` can't tell a deliberate default from one no one thought
+/// about.
+pub fn apply_repr_attributes(documentation: &mut Documentation, entry_path: &Path) -> Result<()> {
+    let items = collect_repr_items(entry_path)?;
+
+    for data in documentation.included.iter_mut() {
+        if !matches!(data.ty.as_str(), "struct" | "enum" | "union") {
+            continue;
+        }
+
+        let name = match data.attributes.get("name").and_then(|v| v.as_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if let Some(repr) = items.iter().find(|item| item.name == name).and_then(|item| item.repr.clone()) {
+            data.attributes.insert("repr".to_string(), serde_json::Value::String(repr));
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a throwaway binary crate into `package_dir` that prints the size
+/// and alignment of every non-generic public struct/enum/union defined in
+/// `entry_path` (a `crate_name::Name` path per type), then build and run it
+/// with `cargo run`, parsing its stdout back into [`TypeLayout`]s.
+pub fn probe_layouts(
+    entry_path: &Path,
+    package_dir: &Path,
+    crate_manifest_path: &Path,
+    crate_name: &str,
+    offline: bool,
+    runner: &dyn ProcessRunner,
+) -> Result<Vec<TypeLayout>> {
+    let names: Vec<String> = collect_repr_items(entry_path)?
+        .into_iter()
+        .filter(|item| !item.has_generics)
+        .map(|item| item.name)
+        .collect();
+
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let crate_dir = crate_manifest_path
+        .parent()
+        .ok_or_else(|| format!("'{}' has no parent directory", crate_manifest_path.display()))?;
+
+    fs::create_dir_all(package_dir.join("src"))?;
+    fs::write(
+        package_dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"rustdoc-layout-probe\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n[[bin]]\nname = \"probe\"\npath = \"src/main.rs\"\n\n[dependencies]\n{} = {{ path = {:?} }}\n",
+            crate_name, crate_dir
+        ),
+    )?;
+
+    let prints: String = names
+        .iter()
+        .map(|name| {
+            format!(
+                "    println!(\"{{}} {{}} {{}}\", \"{name}\", std::mem::size_of::<{crate_name}::{name}>(), std::mem::align_of::<{crate_name}::{name}>());\n",
+                name = name,
+                crate_name = crate_name
+            )
+        })
+        .collect();
+    fs::write(package_dir.join("src").join("main.rs"), format!("fn main() {{\n{}}}\n", prints))?;
+
+    let mut command = std::process::Command::new("cargo");
+    command.arg("run").arg("--quiet").arg("--manifest-path").arg(package_dir.join("Cargo.toml"));
+    if offline {
+        command.arg("--offline");
+    }
+
+    let description = "cargo run (layout probe)".to_string();
+    let output = runner.run(&mut command).chain_err(|| ErrorKind::Cargo(description.clone()))?;
+    if !output.status.success() {
+        return Err(ErrorKind::Cargo(description).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?.to_string();
+            let size = parts.next()?.parse().ok()?;
+            let align = parts.next()?.parse().ok()?;
+            Some(TypeLayout { name, size, align })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::testing::FakeProcessRunner;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn struct_data(name: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), serde_json::Value::String(name.to_string()));
+        Data { id: name.to_string(), ty: "struct".to_string(), attributes, relationships: None, links: None }
+    }
+
+    #[test]
+    fn a_repr_c_struct_gets_a_repr_attribute() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, "#[repr(C)]\npub struct Widget {\n    pub x: u32,\n}\n").unwrap();
+
+        let mut documentation = Documentation { data: Data::default(), included: vec![struct_data("Widget")], meta: HashMap::new(), links: None };
+        apply_repr_attributes(&mut documentation, &entry_path).unwrap();
+
+        assert_eq!(documentation.included[0].attributes.get("repr").unwrap(), "C");
+    }
+
+    #[test]
+    fn a_struct_with_no_repr_attribute_is_left_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, "pub struct Widget {\n    pub x: u32,\n}\n").unwrap();
+
+        let mut documentation = Documentation { data: Data::default(), included: vec![struct_data("Widget")], meta: HashMap::new(), links: None };
+        apply_repr_attributes(&mut documentation, &entry_path).unwrap();
+
+        assert!(!documentation.included[0].attributes.contains_key("repr"));
+    }
+
+    #[test]
+    fn a_generic_struct_is_left_out_of_the_probe() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, "pub struct Wrapper<T> {\n    pub value: T,\n}\n").unwrap();
+
+        let items = collect_repr_items(&entry_path).unwrap();
+        assert!(items[0].has_generics);
+    }
+
+    #[test]
+    fn probe_layouts_parses_a_fake_cargo_runs_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        fs::write(&entry_path, "pub struct Widget {\n    pub x: u32,\n}\n").unwrap();
+
+        let manifest_path = dir.path().join("Cargo.toml");
+        fs::write(&manifest_path, "[package]\nname = \"my_crate\"\n").unwrap();
+
+        let runner = FakeProcessRunner::new(vec![(0, b"Widget 4 4\n".to_vec())]);
+        let layouts = probe_layouts(&entry_path, &dir.path().join("probe"), &manifest_path, "my_crate", false, &runner).unwrap();
+
+        assert_eq!(layouts, vec![TypeLayout { name: "Widget".to_string(), size: 4, align: 4 }]);
+    }
+}
@@ -0,0 +1,625 @@
+//! `rustdoc` is an experimental replacement for the documentation generator
+//! built into `rustc`.
+//!
+//! Instead of rendering HTML directly, it walks a crate's `save-analysis`
+//! data into a JSON-API-shaped [`json::Documentation`], which a separate
+//! frontend (or any other consumer) turns into a documentation site, a
+//! search index, a linter, or anything else.
+//!
+//! The three entry points a caller typically wants are [`build`], which
+//! generates a crate's `Documentation`, [`test`], which runs its doc
+//! examples, and [`Config::open_docs`], which opens a previous build's
+//! output in a browser.
+
+// `error_chain`'s generated code trips the `unexpected_cfgs` lint on current
+// rustc; the crate hasn't been updated to declare it since it predates
+// `-Z check-cfg`.
+#![allow(unexpected_cfgs)]
+
+#[macro_use]
+extern crate error_chain;
+
+pub mod analysis;
+pub mod analysis_debug;
+pub mod analysis_stats;
+pub mod artifacts;
+pub mod badges;
+pub mod blanket_impls;
+#[cfg(feature = "cli")]
+pub mod browser;
+pub mod budget;
+pub mod cargo;
+pub mod color;
+pub mod command;
+pub mod config;
+pub mod coverage_badge;
+pub mod deref_methods;
+pub mod diff;
+pub mod doctest;
+pub mod empty;
+pub mod error;
+pub mod example_index;
+pub mod examples;
+pub mod exclude;
+pub mod explain;
+pub mod extern_crates;
+pub mod format;
+pub mod frontend;
+pub mod graph;
+pub mod intra_links;
+pub mod json;
+pub mod layout;
+pub mod license;
+pub mod links;
+pub mod lock;
+pub mod merge;
+pub mod metrics;
+pub mod module_graph;
+pub mod observer;
+pub mod post_process;
+pub mod reconcile;
+pub mod redirects;
+pub mod relationship_kinds;
+pub mod report;
+pub mod selftest;
+#[cfg(feature = "api-server")]
+pub mod server;
+pub mod sitemap;
+pub mod source;
+pub mod source_pages;
+pub mod staleness;
+pub mod stats;
+pub mod summary;
+pub mod test;
+pub mod ui;
+pub mod validate;
+pub mod write;
+
+use std::path::Path;
+
+pub use crate::artifacts::{Artifact, ArtifactKind, Artifacts};
+pub use crate::config::Config;
+pub use crate::error::{Error, ErrorKind, Result};
+pub use crate::json::Documentation;
+pub use crate::observer::BuildObserver;
+
+use crate::observer::NullObserver;
+
+/// Generate the raw, unmerged [`Documentation`] for `config`'s crate,
+/// optionally cross-checked for `target_triple` instead of the host.
+///
+/// This is the shared core of [`build`] and [`build_for_targets`]; it
+/// doesn't apply any of `config`'s post-processing options (doc splitting,
+/// source embedding, timings), since [`build_for_targets`] needs to merge
+/// several of these together first.
+fn build_one(
+    config: &Config,
+    ui: &ui::Ui,
+    timings: &mut ui::Timings,
+    observer: &dyn BuildObserver,
+    target_triple: Option<&str>,
+) -> Result<Documentation> {
+    let task = ui.start_task("Reading cargo metadata");
+    observer.on_phase_start("Reading cargo metadata");
+    let metadata = cargo::metadata(&config.manifest_path, config.offline, config.locked)?;
+    if let Some(task) = task {
+        timings.record("metadata", task.finish("Read cargo metadata"));
+    }
+
+    let package = metadata
+        .root_package()
+        .ok_or_else(|| "no root package found in cargo metadata".to_string())?;
+    let target = cargo::target_from_metadata(package)?;
+
+    let manifest_dir = config
+        .manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+
+    let analysis_dir = match &config.analysis_dir {
+        Some(analysis_dir) => analysis_dir.clone(),
+        None => {
+            let analysis_dir = cargo::analysis_dir_for_target(metadata.target_directory.as_std_path(), target_triple);
+
+            let task = ui.start_task("Generating analysis");
+            observer.on_phase_start("Generating analysis");
+            let session = cargo::AnalysisSession::new(
+                config.manifest_path.clone(),
+                analysis_dir.clone(),
+                target_triple.map(str::to_string),
+                config.check_env.clone(),
+                config.show_cargo_output,
+                config.offline,
+                config.locked,
+                config.color,
+            );
+            session.ensure_generated()?;
+            if let Some(task) = task {
+                timings.record("analysis", task.finish("Generated analysis"));
+            }
+
+            analysis_dir
+        }
+    };
+
+    let task = ui.start_task("Loading analysis data");
+    observer.on_phase_start("Loading analysis data");
+    let analysis = analysis::Analysis::generate(manifest_dir, &analysis_dir)?;
+    if let Some(task) = task {
+        timings.record("load", task.finish("Loaded analysis data"));
+    }
+
+    if config.analysis_debug {
+        let entries = analysis_debug::dump(&analysis, &target.name)?;
+        std::fs::create_dir_all(config.output_path())?;
+        let debug_path = config.output_path().join("analysis-debug.json");
+        write::write_if_changed(&debug_path, serde_json::to_string_pretty(&entries)?.as_bytes())?;
+        observer.on_artifact_written(&debug_path);
+    }
+
+    let task = ui.start_task("Building documentation");
+    observer.on_phase_start("Building documentation");
+    let (mut documentation, skipped_with_docs) =
+        json::create_documentation(&analysis, &target.name, config.root.as_deref(), &config.exclude)?;
+    observer.on_item_documented(&documentation.data);
+    for item in &documentation.included {
+        observer.on_item_documented(item);
+    }
+    if let Some(task) = task {
+        timings.record("json", task.finish("Built documentation"));
+    }
+
+    if config.verbose && !skipped_with_docs.is_empty() {
+        ui.info(&format!(
+            "warning: {} item(s) have doc comments that won't be rendered (unsupported item kind):\n{}",
+            skipped_with_docs.len(),
+            skipped_with_docs.iter().map(|item| format!("  {}", item)).collect::<Vec<_>>().join("\n"),
+        ));
+    }
+
+    if config.debug_analysis_stats {
+        ui.info(&analysis.stats().summary());
+    }
+
+    if let Some(recorded) = cargo::read_rustc_version(&analysis_dir) {
+        if let Ok(current) = cargo::rustc_version() {
+            if current != recorded {
+                ui.info(&format!(
+                    "warning: this analysis was generated by a different compiler than the one currently on PATH:\n  generated with: {}\n  currently on PATH: {}",
+                    recorded, current,
+                ));
+            }
+        }
+        documentation
+            .meta
+            .insert("compiler".to_string(), serde_json::Value::String(recorded));
+    }
+
+    Ok(documentation)
+}
+
+/// Apply `config`'s post-processing options to `documentation`: splitting
+/// long docs, embedding source snippets, and recording timings.
+fn postprocess(
+    config: &Config,
+    ui: &ui::Ui,
+    timings: &ui::Timings,
+    observer: &dyn BuildObserver,
+    documentation: &mut Documentation,
+) -> Result<()> {
+    let manifest_dir = config
+        .manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+
+    let metadata = cargo::metadata(&config.manifest_path, config.offline, config.locked)?;
+
+    if let Some(max_docs_size) = config.max_docs_size {
+        let docs_dir = config.output_path().join("docs");
+        let offenders = json::split_long_docs(documentation, &docs_dir, max_docs_size)?;
+        observer.on_artifact_written(&docs_dir);
+
+        if !offenders.is_empty() {
+            ui.info(&format!(
+                "warning: {} item(s) exceeded the {}-byte docs size limit and were truncated:\n{}",
+                offenders.len(),
+                max_docs_size,
+                offenders.iter().map(|item| format!("  {}", item)).collect::<Vec<_>>().join("\n"),
+            ));
+        }
+    }
+
+    if let Some(max_items) = config.max_items {
+        json::limit_items(documentation, max_items);
+    }
+
+    let generated_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    if config.include_source {
+        source::embed_source_snippets(documentation, manifest_dir)?;
+
+        let footer = if config.stamp_license {
+            metadata
+                .root_package()
+                .map(|package| license::footer(package.license.as_deref(), generated_at))
+        } else {
+            None
+        };
+
+        let pages = source_pages::build_source_pages(documentation, manifest_dir, config.output_path(), footer.as_deref())?;
+        for page in &pages {
+            observer.on_artifact_written(page);
+        }
+    }
+
+    let redirects = redirects::load_redirects(manifest_dir)?;
+    if !redirects.is_empty() {
+        documentation
+            .meta
+            .insert("redirects".to_string(), serde_json::to_value(&redirects)?);
+    }
+
+    let badge_markers = badges::load_badge_markers(manifest_dir)?;
+    badges::apply_badges(documentation, &badge_markers);
+
+    summary::apply_summaries(documentation, config.summary_length);
+
+    if let Some(package) = metadata.root_package() {
+        let examples = example_index::build_example_index(package)?;
+        if !examples.is_empty() {
+            std::fs::create_dir_all(config.output_path())?;
+            let examples_path = config.output_path().join("examples.json");
+            write::write_if_changed(&examples_path, serde_json::to_string_pretty(&examples)?.as_bytes())?;
+            observer.on_artifact_written(&examples_path);
+        }
+
+        if config.check_completeness {
+            if let Ok(target) = cargo::target_from_metadata(package) {
+                let missing = reconcile::find_missing_items(documentation, target.src_path.as_std_path())?;
+                if !missing.is_empty() {
+                    std::fs::create_dir_all(config.output_path())?;
+                    let completeness_path = config.output_path().join("completeness.json");
+                    write::write_if_changed(&completeness_path, serde_json::to_string_pretty(&missing)?.as_bytes())?;
+                    observer.on_artifact_written(&completeness_path);
+                }
+            }
+        }
+
+        if let Ok(target) = cargo::target_from_metadata(package) {
+            let blanket_impls = blanket_impls::find_blanket_impls(target.src_path.as_std_path())?;
+            if !blanket_impls.is_empty() {
+                documentation
+                    .meta
+                    .insert("blanketImpls".to_string(), serde_json::to_value(&blanket_impls)?);
+            }
+
+            let deref_methods = deref_methods::find_deref_methods(target.src_path.as_std_path())?;
+            if !deref_methods.is_empty() {
+                documentation
+                    .meta
+                    .insert("derefMethods".to_string(), serde_json::to_value(&deref_methods)?);
+            }
+
+            layout::apply_repr_attributes(documentation, target.src_path.as_std_path())?;
+
+            if config.layout {
+                let package_dir = config.output_path().join("layout-probe");
+                let layouts = layout::probe_layouts(
+                    target.src_path.as_std_path(),
+                    &package_dir,
+                    &config.manifest_path,
+                    &package.name,
+                    config.offline,
+                    &command::SystemProcessRunner,
+                )?;
+
+                if !layouts.is_empty() {
+                    std::fs::create_dir_all(config.output_path())?;
+                    let layout_path = config.output_path().join("layout.json");
+                    write::write_if_changed(&layout_path, serde_json::to_string_pretty(&layouts)?.as_bytes())?;
+                    observer.on_artifact_written(&layout_path);
+                }
+            }
+        }
+
+        documentation
+            .data
+            .attributes
+            .insert("discovery".to_string(), cargo::discovery_metadata(package));
+
+        intra_links::resolve_doc_links(documentation, &package.name);
+
+        let extern_crates = extern_crates::find_referenced_crates(documentation, &metadata, package);
+        if !extern_crates.is_empty() {
+            documentation
+                .meta
+                .insert("externCrates".to_string(), serde_json::to_value(&extern_crates)?);
+        }
+
+        if config.check_stale_examples {
+            let stale = staleness::find_stale_references(documentation, &package.name);
+            if !stale.is_empty() {
+                std::fs::create_dir_all(config.output_path())?;
+                let stale_path = config.output_path().join("stale-examples.json");
+                write::write_if_changed(&stale_path, serde_json::to_string_pretty(&stale)?.as_bytes())?;
+                observer.on_artifact_written(&stale_path);
+            }
+        }
+
+        if config.stamp_license {
+            documentation
+                .meta
+                .insert("license".to_string(), serde_json::to_value(license::stamp(package.license.as_deref(), generated_at))?);
+        }
+    }
+
+    if let Some(base_url) = &config.base_url {
+        links::add_links(documentation, base_url)?;
+
+        std::fs::create_dir_all(config.output_path())?;
+        let sitemap_path = config.output_path().join("sitemap.xml");
+        write::write_if_changed(&sitemap_path, sitemap::build_sitemap(documentation).as_bytes())?;
+        observer.on_artifact_written(&sitemap_path);
+
+        let robots_path = config.output_path().join("robots.txt");
+        write::write_if_changed(&robots_path, sitemap::build_robots_txt(base_url).as_bytes())?;
+        observer.on_artifact_written(&robots_path);
+    }
+
+    let stats = stats::compute_stats(documentation);
+    documentation
+        .meta
+        .insert("stats".to_string(), serde_json::to_value(&stats)?);
+
+    if let Some(reason) = empty::detect(documentation) {
+        documentation
+            .meta
+            .insert("empty".to_string(), serde_json::json!({ "reason": reason }));
+
+        ui.info(&format!(
+            "warning: this crate's generated documentation is empty ({}); {}",
+            match reason {
+                empty::Reason::NoItems => "no items were found",
+                empty::Reason::NoDocs => "no item has a doc comment",
+            },
+            "see `meta.empty` in the generated documentation",
+        ));
+    }
+
+    if config.timings {
+        ui.info(&timings.summary());
+        std::fs::create_dir_all(config.output_path())?;
+        let timings_path = config.output_path().join("timings.json");
+        write::write_if_changed(&timings_path, serde_json::to_string_pretty(&timings)?.as_bytes())?;
+        observer.on_artifact_written(&timings_path);
+    }
+
+    if config.metrics {
+        std::fs::create_dir_all(config.output_path())?;
+        let metrics_path = config.output_path().join("metrics.json");
+        let metrics = metrics::compute_metrics(documentation);
+        write::write_if_changed(&metrics_path, serde_json::to_string_pretty(&metrics)?.as_bytes())?;
+        observer.on_artifact_written(&metrics_path);
+    }
+
+    if config.module_graph {
+        std::fs::create_dir_all(config.output_path())?;
+        let graph = module_graph::build_module_graph(documentation);
+
+        let dot_path = config.output_path().join("modules.dot");
+        write::write_if_changed(&dot_path, module_graph::to_dot(&graph).as_bytes())?;
+        observer.on_artifact_written(&dot_path);
+
+        let json_path = config.output_path().join("modules.json");
+        write::write_if_changed(&json_path, serde_json::to_string_pretty(&graph)?.as_bytes())?;
+        observer.on_artifact_written(&json_path);
+    }
+
+    if config.coverage_badge {
+        std::fs::create_dir_all(config.output_path())?;
+        let percentage = coverage_badge::coverage_percentage(documentation);
+        let badge_path = config.output_path().join("coverage-badge.svg");
+        write::write_if_changed(&badge_path, coverage_badge::render_svg(percentage).as_bytes())?;
+        observer.on_artifact_written(&badge_path);
+    }
+
+    let violations = budget::check(documentation, &config.budget)?;
+    if !violations.is_empty() {
+        for violation in &violations {
+            ui.info(&format!("warning: {}", violation));
+        }
+
+        documentation
+            .meta
+            .insert("budgetViolations".to_string(), serde_json::to_value(&violations)?);
+
+        if config.budget.deny {
+            return Err(format!("{} documentation budget violation(s) exceeded", violations.len()).into());
+        }
+    }
+
+    if let Some(command) = &config.post_process {
+        *documentation = post_process::run(command, documentation)?;
+    }
+
+    Ok(())
+}
+
+/// Generate the [`Documentation`] for the crate described by `config`.
+///
+/// If [`Config::timings`] is set, the wall-clock duration of each phase is
+/// written to `timings.json` under [`Config::output_path`].
+///
+/// [`Config::lock_policy`] is held for [`Config::output_path`] for the
+/// duration of the build, so a second build writing into the same
+/// directory doesn't interleave with this one. See [`lock`].
+pub fn build(config: &Config) -> Result<Documentation> {
+    build_with_observer(config, &NullObserver)
+}
+
+/// Like [`build`], but reports progress to `observer` as it goes, for
+/// embedders (a GUI wrapper, a language server) that want to show that
+/// progress themselves instead of waiting on the finished `Documentation`.
+pub fn build_with_observer(config: &Config, observer: &dyn BuildObserver) -> Result<Documentation> {
+    let _lock = lock::acquire(config.output_path(), config.lock_policy)?;
+    let ui = ui::Ui::new(config.quiet, config.color);
+    let mut timings = ui::Timings::new();
+
+    let mut documentation = build_one(config, &ui, &mut timings, observer, None)?;
+    postprocess(config, &ui, &timings, observer, &mut documentation)?;
+
+    Ok(documentation)
+}
+
+/// Generate [`Documentation`] for each of `targets`, merging the results
+/// with [`merge::merge_platforms`] so items only present on some targets
+/// are annotated with the `platforms` they were seen on.
+///
+/// With an empty `targets`, this is equivalent to [`build`] (documenting
+/// just the host).
+pub fn build_for_targets(config: &Config, targets: &[String]) -> Result<Documentation> {
+    build_for_targets_with_observer(config, targets, &NullObserver)
+}
+
+/// Like [`build_for_targets`], but reports progress to `observer` as it
+/// goes; see [`build_with_observer`].
+pub fn build_for_targets_with_observer(config: &Config, targets: &[String], observer: &dyn BuildObserver) -> Result<Documentation> {
+    if targets.is_empty() {
+        return build_with_observer(config, observer);
+    }
+
+    let _lock = lock::acquire(config.output_path(), config.lock_policy)?;
+    let ui = ui::Ui::new(config.quiet, config.color);
+    let mut timings = ui::Timings::new();
+
+    let mut by_target = Vec::with_capacity(targets.len());
+    for target in targets {
+        let documentation = build_one(config, &ui, &mut timings, observer, Some(target))?;
+        by_target.push((target.clone(), documentation));
+    }
+
+    let mut documentation = merge::merge_platforms(by_target);
+    postprocess(config, &ui, &timings, observer, &mut documentation)?;
+
+    Ok(documentation)
+}
+
+/// Run the doc examples contained in `documentation`.
+///
+/// Takes an already-built [`Documentation`] rather than a [`Config`] to
+/// build one from, so a caller that already has one in hand (from
+/// [`build`], [`build_with_observer`], or its own cache) never has to write
+/// it to [`Config::output_path`] and read it back just to test it; only
+/// the generated test sources and binaries touch disk, under `tests_dir`.
+///
+/// Crate-level doc test settings (see [`doctest::DoctestConfig`]) are loaded
+/// from `manifest_dir`. Any crates named in
+/// [`doctest::DoctestConfig::inject`] are built and, with
+/// [`test::TestBackend::Rustc`], made available to every example (see
+/// [`cargo::build_extern_crate`]); the Cargo backend doesn't pass them on.
+///
+/// Generated sources and binaries are written under `tests_dir` (see
+/// [`test::default_tests_dir`] for the usual choice), in a fixed layout:
+/// `tests_dir/src` for sources, `tests_dir/bin` (or `tests_dir/cargo-pkg`
+/// for [`test::TestBackend::Cargo`]) for what they compile to. Kept separate
+/// from [`Config::output_path`] so publishing generated documentation never
+/// drags test artifacts along with it. When `verbose` is set, this location
+/// is printed to stderr before anything is written.
+///
+/// Each example already runs in its own process (see
+/// [`test::compile_tests`]); `retries` additionally re-runs a failing one up
+/// to that many more times before giving up on it (see
+/// [`test::run_tests_with_outcomes`]), and every example's wall-clock
+/// duration is printed to stderr as it finishes. On failure, the first
+/// example that never passed has its captured stdout/stderr printed too.
+///
+/// When `skip_deprecated_doctests` is set, examples belonging to deprecated
+/// items aren't collected in the first place.
+///
+/// When `offline` is set, building each injected extern crate forwards
+/// `--offline` (see [`cargo::build_extern_crate`]).
+///
+/// When `locked` is set, building each injected extern crate (and, with
+/// [`test::TestBackend::Cargo`], reading the crate's own metadata) forwards
+/// `--locked`, failing with [`ErrorKind::LockfileDrift`] instead of silently
+/// updating `Cargo.lock`.
+///
+/// `backend` picks how examples are compiled and run; see
+/// [`test::TestBackend`].
+///
+/// `color` forwards `--color` to every `rustc`/`cargo` invocation this makes
+/// (building an injected extern crate, compiling or running examples); see
+/// [`color::ColorChoice`].
+#[allow(clippy::too_many_arguments)]
+pub fn test(
+    documentation: &Documentation,
+    tests_dir: &Path,
+    manifest_dir: &Path,
+    skip_deprecated_doctests: bool,
+    retries: u32,
+    offline: bool,
+    locked: bool,
+    verbose: bool,
+    backend: test::TestBackend,
+    color: color::ColorChoice,
+) -> Result<()> {
+    if verbose {
+        eprintln!("writing doc tests to '{}'", tests_dir.display());
+    }
+
+    // `tests_dir` is usually already under a gitignored `target/`, but a
+    // caller pointing it elsewhere (e.g. a shared CI cache directory)
+    // shouldn't have to remember to ignore it themselves.
+    std::fs::create_dir_all(tests_dir)?;
+    std::fs::write(tests_dir.join(".gitignore"), "*\n")?;
+
+    let doctest_config = doctest::load_doctest_config(manifest_dir)?;
+
+    let manifest_path = manifest_dir.join("Cargo.toml");
+    let mut extern_crates = Vec::with_capacity(doctest_config.inject.len());
+    for crate_name in &doctest_config.inject {
+        let rlib = cargo::build_extern_crate(&manifest_path, crate_name, offline, locked, color, &command::SystemProcessRunner)?;
+        extern_crates.push((crate_name.clone(), rlib));
+    }
+
+    let tests = test::find_tests(documentation, &doctest_config, skip_deprecated_doctests);
+
+    let outcomes = match backend {
+        test::TestBackend::Rustc => {
+            let sources = test::save_tests(&tests, &tests_dir.join("src"))?;
+            let binaries = test::compile_tests(&sources, &tests_dir.join("bin"), &extern_crates, color)?;
+            test::run_tests_with_outcomes(&binaries, retries, &command::SystemProcessRunner)
+        }
+        test::TestBackend::Cargo => {
+            let metadata = cargo::metadata(&manifest_path, offline, locked)?;
+            let package = metadata
+                .root_package()
+                .ok_or_else(|| "no root package found in `cargo metadata`".to_string())?;
+            test::compile_and_run_with_cargo(
+                &tests,
+                &tests_dir.join("cargo-pkg"),
+                &manifest_path,
+                &package.name,
+                offline,
+                color,
+                &command::SystemProcessRunner,
+            )?
+        }
+    };
+
+    for outcome in &outcomes {
+        eprintln!("{:>40}: {:.2?}", outcome.binary, outcome.duration);
+    }
+
+    match outcomes.into_iter().find(|outcome| !outcome.success) {
+        Some(failed) => {
+            eprint!("{}", failed.stdout);
+            eprint!("{}", failed.stderr);
+            Err(ErrorKind::DocTest(failed.binary).into())
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,60 @@
+//! Stamping generated artifacts with the crate's license expression and a
+//! generation timestamp, for organizations that require this on published
+//! documentation. See [`crate::Config::stamp_license`], which controls
+//! both the `meta.license` entry ([`stamp`]) and the HTML source-page
+//! footer ([`footer`]) built from the same two values.
+
+use serde_derive::Serialize;
+
+/// The `meta.license` entry: the crate's SPDX license expression (or
+/// `"unspecified"` when `Cargo.toml` doesn't declare one) and the Unix
+/// timestamp, in seconds, at which the documentation was generated.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LicenseStamp {
+    pub expression: String,
+    #[serde(rename = "generatedAt")]
+    pub generated_at: u64,
+}
+
+/// Build the [`LicenseStamp`] inserted into `meta.license`.
+pub fn stamp(license: Option<&str>, generated_at: u64) -> LicenseStamp {
+    LicenseStamp {
+        expression: license.unwrap_or("unspecified").to_string(),
+        generated_at,
+    }
+}
+
+/// Render the line appended to the footer of every generated HTML source
+/// page (see [`crate::source_pages::build_source_pages`]).
+pub fn footer(license: Option<&str>, generated_at: u64) -> String {
+    format!(
+        "Licensed under {}. Generated at {}.",
+        license.unwrap_or("unspecified"),
+        generated_at,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_the_declared_license_and_timestamp() {
+        assert_eq!(
+            stamp(Some("MIT OR Apache-2.0"), 1_700_000_000),
+            LicenseStamp { expression: "MIT OR Apache-2.0".to_string(), generated_at: 1_700_000_000 }
+        );
+    }
+
+    #[test]
+    fn falls_back_to_unspecified_when_no_license_is_declared() {
+        assert_eq!(stamp(None, 0).expression, "unspecified");
+    }
+
+    #[test]
+    fn the_footer_mentions_the_license_and_timestamp() {
+        let footer = footer(Some("MIT"), 1_700_000_000);
+        assert!(footer.contains("MIT"));
+        assert!(footer.contains("1700000000"));
+    }
+}
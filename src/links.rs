@@ -0,0 +1,125 @@
+//! Populating the JSON-API `links` member with a `self` URL for every item,
+//! plus a `linkTemplates` entry in `meta` mapping each resource type to an
+//! [RFC 6570](https://www.rfc-editor.org/rfc/rfc6570)-style template.
+//!
+//! This is opt-in (see `Config::base_url`): without a base URL there's
+//! nothing to build links from, so `Documentation` is left untouched.
+
+use std::collections::HashMap;
+
+use url::Url;
+
+use crate::error::*;
+use crate::json::Documentation;
+
+fn self_link(base_url: &Url, ty: &str, id: &str) -> String {
+    format!("{}{}s/{}", base_url, ty, id)
+}
+
+fn link_template(base_url: &Url, ty: &str) -> String {
+    format!("{}{}s/{{id}}", base_url, ty)
+}
+
+/// Set `documentation.links["self"]` to `base_url`, give every item (the
+/// crate itself and everything in `included`) a `links["self"]` of its own,
+/// and record a `linkTemplates` entry in `meta` for each distinct resource
+/// type seen, so a consumer can build a link to an id it only has a
+/// reference to (e.g. from a relationship) without hard-coding the scheme.
+pub fn add_links(documentation: &mut Documentation, base_url: &str) -> Result<()> {
+    let base_url = Url::parse(base_url).chain_err(|| format!("invalid base URL '{}'", base_url))?;
+
+    let mut links = HashMap::new();
+    links.insert("self".to_string(), base_url.to_string());
+    documentation.links = Some(links);
+
+    let mut templates = HashMap::new();
+
+    let items = std::iter::once(&mut documentation.data).chain(documentation.included.iter_mut());
+    for data in items {
+        templates
+            .entry(data.ty.clone())
+            .or_insert_with(|| link_template(&base_url, &data.ty));
+
+        let mut links = HashMap::new();
+        links.insert("self".to_string(), self_link(&base_url, &data.ty, &data.id));
+        data.links = Some(links);
+    }
+
+    documentation.meta.insert(
+        "linkTemplates".to_string(),
+        serde_json::to_value(&templates)?,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap as Map;
+
+    fn documentation() -> Documentation {
+        Documentation {
+            data: Data {
+                id: "crate".to_string(),
+                ty: "crate".to_string(),
+                attributes: Map::new(),
+                relationships: None,
+                ..Default::default()
+            },
+            included: vec![Data {
+                id: "abc123".to_string(),
+                ty: "struct".to_string(),
+                attributes: Map::new(),
+                relationships: None,
+                ..Default::default()
+            }],
+            meta: Map::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sets_the_documents_own_link() {
+        let mut documentation = documentation();
+        add_links(&mut documentation, "https://docs.example.com/").unwrap();
+        assert_eq!(
+            documentation.links.unwrap().get("self").unwrap(),
+            "https://docs.example.com/"
+        );
+    }
+
+    #[test]
+    fn sets_a_self_link_on_every_item() {
+        let mut documentation = documentation();
+        add_links(&mut documentation, "https://docs.example.com/").unwrap();
+
+        assert_eq!(
+            documentation.data.links.unwrap().get("self").unwrap(),
+            "https://docs.example.com/crates/crate"
+        );
+        assert_eq!(
+            documentation.included[0].links.as_ref().unwrap().get("self").unwrap(),
+            "https://docs.example.com/structs/abc123"
+        );
+    }
+
+    #[test]
+    fn records_a_template_per_resource_type() {
+        let mut documentation = documentation();
+        add_links(&mut documentation, "https://docs.example.com/").unwrap();
+
+        let templates = documentation.meta.get("linkTemplates").unwrap();
+        assert_eq!(
+            templates.get("struct").unwrap(),
+            "https://docs.example.com/structs/{id}"
+        );
+    }
+
+    #[test]
+    fn an_invalid_base_url_is_an_error() {
+        let mut documentation = documentation();
+        assert!(add_links(&mut documentation, "not a url").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Advisory locking of a build's output directory, so two builds writing
+//! into the same directory at once (an IDE save hook racing a manual CLI
+//! run) don't interleave their writes.
+//!
+//! The lock is advisory: it only protects against another process that
+//! goes through [`acquire`] too, not against something writing into the
+//! directory directly.
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use fs2::FileExt;
+
+use crate::error::*;
+
+/// The name of the lock file created inside a build's output directory.
+const LOCK_FILE_NAME: &str = ".rustdoc-lock";
+
+/// How to behave when the output directory is already locked by another
+/// build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Fail immediately if the directory is already locked.
+    Fail,
+    /// Block until the other build releases the lock.
+    Wait,
+    /// Don't lock at all.
+    Skip,
+}
+
+/// Held for as long as a build is writing into its output directory;
+/// releases the lock on drop.
+pub struct OutputLock(File);
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        let _ = self.0.unlock();
+    }
+}
+
+/// Acquire a lock on `output_dir` according to `policy`, creating
+/// `output_dir` first if it doesn't exist yet. Returns `None` without
+/// touching the filesystem when `policy` is [`LockPolicy::Skip`].
+pub fn acquire(output_dir: &Path, policy: LockPolicy) -> Result<Option<OutputLock>> {
+    if policy == LockPolicy::Skip {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(LOCK_FILE_NAME);
+    let file = File::create(&path).chain_err(|| format!("failed to create '{}'", path.display()))?;
+
+    match policy {
+        LockPolicy::Wait => file
+            .lock_exclusive()
+            .chain_err(|| format!("failed to lock '{}'", path.display()))?,
+        LockPolicy::Fail => file.try_lock_exclusive().map_err(|_| {
+            format!(
+                "'{}' is already locked by another build; pass --wait to wait for it, or --no-lock to skip locking",
+                path.display()
+            )
+        })?,
+        LockPolicy::Skip => unreachable!("returned above"),
+    }
+
+    Ok(Some(OutputLock(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_never_creates_a_lock_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("doc");
+
+        let lock = acquire(&output_dir, LockPolicy::Skip).unwrap();
+
+        assert!(lock.is_none());
+        assert!(!output_dir.exists());
+    }
+
+    #[test]
+    fn fail_errors_out_when_already_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let _held = acquire(dir.path(), LockPolicy::Fail).unwrap();
+
+        assert!(acquire(dir.path(), LockPolicy::Fail).is_err());
+    }
+
+    #[test]
+    fn dropping_the_lock_lets_a_later_build_acquire_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let held = acquire(dir.path(), LockPolicy::Fail).unwrap();
+        drop(held);
+
+        assert!(acquire(dir.path(), LockPolicy::Fail).unwrap().is_some());
+    }
+}
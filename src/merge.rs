@@ -0,0 +1,174 @@
+//! Merging per-target `Documentation` into a single platform-annotated view.
+//!
+//! Crates with platform-specific modules (a `unix` module and a `windows`
+//! module, say) only expose one platform's API to a single `cargo check`,
+//! since the other is `#[cfg]`'d out. Generating documentation once per
+//! target triple and merging the results here gives frontends the full
+//! picture, with a `platforms` attribute marking which targets each item
+//! that isn't universal was actually seen on.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::json::{Data, Documentation};
+
+/// The key used to recognize the same item across documentation generated
+/// in separate analysis sessions (one per target), since `rls_analysis`
+/// ids are only stable within a single session.
+type ItemKey = (String, String);
+
+fn item_key(data: &Data) -> ItemKey {
+    let qualname = data
+        .attributes
+        .get("qualname")
+        .and_then(Value::as_str)
+        .unwrap_or(&data.id);
+    (data.ty.clone(), qualname.to_string())
+}
+
+/// Merge `Documentation` generated for each of several target triples into
+/// one, annotating every item that wasn't seen on every target with a
+/// `platforms` attribute listing the targets it does appear on.
+///
+/// The crate-level `data` and its `relationships` are taken from whichever
+/// target was documented first; only `included` items are merged.
+pub fn merge_platforms(by_target: Vec<(String, Documentation)>) -> Documentation {
+    let target_count = by_target.len();
+
+    let mut crate_data = None;
+    let mut order = Vec::new();
+    let mut merged: HashMap<ItemKey, (Data, Vec<String>)> = HashMap::new();
+
+    for (target, documentation) in by_target {
+        if crate_data.is_none() {
+            crate_data = Some(documentation.data);
+        }
+
+        for data in documentation.included {
+            let key = item_key(&data);
+            match merged.get_mut(&key) {
+                Some((_, targets)) => targets.push(target.clone()),
+                None => {
+                    order.push(key.clone());
+                    merged.insert(key, (data, vec![target.clone()]));
+                }
+            }
+        }
+    }
+
+    let included = order
+        .into_iter()
+        .map(|key| {
+            let (mut data, targets) = merged.remove(&key).expect("key was just pushed to order");
+            if targets.len() < target_count {
+                data.attributes.insert(
+                    "platforms".to_string(),
+                    Value::Array(targets.into_iter().map(Value::String).collect()),
+                );
+            }
+            data
+        })
+        .collect();
+
+    Documentation {
+        data: crate_data.unwrap_or_else(|| Data {
+            id: "crate".to_string(),
+            ty: "crate".to_string(),
+            attributes: HashMap::new(),
+            relationships: None,
+            ..Default::default()
+        }),
+        included,
+        meta: HashMap::new(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, qualname: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("qualname".to_string(), Value::String(qualname.to_string()));
+        Data {
+            id: id.to_string(),
+            ty: "function".to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    fn crate_data() -> Data {
+        Data {
+            id: "crate".to_string(),
+            ty: "crate".to_string(),
+            attributes: HashMap::new(),
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn universal_items_get_no_platforms_attribute() {
+        let by_target = vec![
+            (
+                "x86_64-unknown-linux-gnu".to_string(),
+                Documentation {
+                    data: crate_data(),
+                    included: vec![item("a", "krate::shared")],
+                    meta: HashMap::new(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "x86_64-pc-windows-msvc".to_string(),
+                Documentation {
+                    data: crate_data(),
+                    included: vec![item("b", "krate::shared")],
+                    meta: HashMap::new(),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let merged = merge_platforms(by_target);
+
+        assert_eq!(merged.included.len(), 1);
+        assert!(!merged.included[0].attributes.contains_key("platforms"));
+    }
+
+    #[test]
+    fn platform_specific_items_are_annotated() {
+        let by_target = vec![
+            (
+                "x86_64-unknown-linux-gnu".to_string(),
+                Documentation {
+                    data: crate_data(),
+                    included: vec![item("a", "krate::unix::only")],
+                    meta: HashMap::new(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "x86_64-pc-windows-msvc".to_string(),
+                Documentation {
+                    data: crate_data(),
+                    included: vec![],
+                    meta: HashMap::new(),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let merged = merge_platforms(by_target);
+
+        assert_eq!(merged.included.len(), 1);
+        assert_eq!(
+            merged.included[0].attributes.get("platforms").unwrap(),
+            &Value::Array(vec![Value::String("x86_64-unknown-linux-gnu".to_string())])
+        );
+    }
+}
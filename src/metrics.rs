@@ -0,0 +1,155 @@
+//! Building the `metrics.json` artifact: per-item word count, doc-example
+//! count, and a readability score, so a documentation team can track
+//! coverage and quality across releases without re-deriving it from
+//! `data.json` themselves.
+
+use serde_derive::Serialize;
+
+use crate::examples::find_examples;
+use crate::json::{Data, Documentation};
+
+/// One item's computed documentation metrics.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemMetrics {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub name: Option<String>,
+    #[serde(rename = "wordCount")]
+    pub word_count: usize,
+    #[serde(rename = "exampleCount")]
+    pub example_count: usize,
+    /// The item's `docs` scored with [`flesch_reading_ease`]; `None` for an
+    /// item with no docs to score.
+    pub readability: Option<f64>,
+}
+
+/// Compute [`ItemMetrics`] for every documented item, the crate's own
+/// `data` first, followed by `included` in its existing order.
+pub fn compute_metrics(documentation: &Documentation) -> Vec<ItemMetrics> {
+    std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .map(item_metrics)
+        .collect()
+}
+
+fn item_metrics(data: &Data) -> ItemMetrics {
+    let docs = data.attributes.get("docs").and_then(|v| v.as_str()).unwrap_or("");
+    let name = data.attributes.get("name").and_then(|v| v.as_str()).map(str::to_string);
+
+    ItemMetrics {
+        id: data.id.clone(),
+        ty: data.ty.clone(),
+        name,
+        word_count: docs.split_whitespace().count(),
+        example_count: find_examples(docs).len(),
+        readability: flesch_reading_ease(docs),
+    }
+}
+
+/// The Flesch reading-ease score for `text`:
+/// `206.835 - 1.015 * (words / sentences) - 84.6 * (syllables / words)`.
+/// Higher scores mean easier to read; real-world English text usually
+/// falls between 0 and 100, though the formula isn't bounded to that range.
+///
+/// `None` for text with no words to score, e.g. an item with an empty
+/// `docs` attribute.
+///
+/// Syllables are counted with the same rough heuristic every other
+/// dependency-free Flesch implementation uses (count vowel groups, discount
+/// a trailing silent `e`), since this crate has no dedicated hyphenation
+/// dependency; it's meant as a rough trend indicator across releases, not
+/// an exact score.
+fn flesch_reading_ease(text: &str) -> Option<f64> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let sentences = text.chars().filter(|c| matches!(c, '.' | '!' | '?')).count().max(1);
+    let syllables: usize = words.iter().map(|word| count_syllables(word)).sum();
+
+    Some(206.835 - 1.015 * (words.len() as f64 / sentences as f64) - 84.6 * (syllables as f64 / words.len() as f64))
+}
+
+/// A rough syllable count for `word`: the number of vowel groups, with a
+/// trailing silent `e` discounted, floored at one syllable per word.
+fn count_syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+
+    let mut count = 0;
+    let mut previous_was_vowel = false;
+    for c in word.chars() {
+        let is_vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+        if is_vowel && !previous_was_vowel {
+            count += 1;
+        }
+        previous_was_vowel = is_vowel;
+    }
+
+    if word.ends_with('e') && count > 1 {
+        count -= 1;
+    }
+
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn data_with_docs(id: &str, docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("name".to_string(), id.into());
+        attributes.insert("docs".to_string(), docs.into());
+        Data {
+            id: id.to_string(),
+            ty: "function".to_string(),
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counts_words_and_examples() {
+        let docs = "A short function.\n\n```\nlet x = 1;\n```\n";
+        let documentation = Documentation {
+            data: data_with_docs("crate", ""),
+            included: vec![data_with_docs("f", docs)],
+            ..Default::default()
+        };
+
+        let metrics = compute_metrics(&documentation);
+        let item = metrics.iter().find(|m| m.id == "f").unwrap();
+        assert_eq!(item.word_count, docs.split_whitespace().count());
+        assert_eq!(item.example_count, 1);
+        assert!(item.readability.is_some());
+    }
+
+    #[test]
+    fn an_item_with_no_docs_gets_no_readability_score() {
+        let documentation = Documentation {
+            data: data_with_docs("crate", ""),
+            included: vec![data_with_docs("f", "")],
+            ..Default::default()
+        };
+
+        let metrics = compute_metrics(&documentation);
+        let item = metrics.iter().find(|m| m.id == "f").unwrap();
+        assert_eq!(item.word_count, 0);
+        assert_eq!(item.example_count, 0);
+        assert!(item.readability.is_none());
+    }
+
+    #[test]
+    fn simpler_text_scores_higher_than_denser_text() {
+        let simple = flesch_reading_ease("The cat sat on the mat. It was fun.").unwrap();
+        let dense = flesch_reading_ease(
+            "Notwithstanding the aforementioned considerations, implementation complexity increases substantially.",
+        )
+        .unwrap();
+
+        assert!(simple > dense);
+    }
+}
@@ -0,0 +1,77 @@
+//! Hooks for embedding `rustdoc` in a larger application (a GUI wrapper, a
+//! language server) that wants to show its own progress, or start consuming
+//! items as they're produced, rather than waiting on a finished
+//! [`crate::Documentation`].
+//!
+//! This is the programmatic counterpart to [`crate::ui::Ui`]: `Ui` drives
+//! the terminal spinner for the CLI, while a [`BuildObserver`] lets any
+//! other caller of [`crate::build_with_observer`] plug in its own reporting
+//! instead.
+
+use std::path::Path;
+
+use crate::json::Data;
+
+/// Callbacks invoked as a build proceeds.
+///
+/// Every method has a default no-op implementation, so an embedder only
+/// needs to override the ones it cares about.
+pub trait BuildObserver {
+    /// A build phase (e.g. "Generating analysis") has started.
+    fn on_phase_start(&self, _phase: &str) {}
+
+    /// A single item's [`Data`] has been produced.
+    fn on_item_documented(&self, _item: &Data) {}
+
+    /// A file has been written to disk, e.g. `data.json` or a split docs
+    /// side file.
+    fn on_artifact_written(&self, _path: &Path) {}
+
+    /// A non-fatal problem was encountered that doesn't stop the build.
+    ///
+    /// Nothing in [`crate::build_with_observer`] calls this: items analysis
+    /// walking (`json::walk`) can't build `Data` for are returned by
+    /// [`crate::json::create_documentation`] and, with
+    /// [`crate::Config::verbose`], printed through [`crate::ui::Ui`] instead
+    /// of being passed here. It's part of the trait so embedders can already
+    /// implement it.
+    fn on_warning(&self, _message: &str) {}
+}
+
+/// A [`BuildObserver`] that does nothing, used when a caller doesn't supply
+/// one of its own.
+pub struct NullObserver;
+
+impl BuildObserver for NullObserver {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        phases: RefCell<Vec<String>>,
+    }
+
+    impl BuildObserver for RecordingObserver {
+        fn on_phase_start(&self, phase: &str) {
+            self.phases.borrow_mut().push(phase.to_string());
+        }
+    }
+
+    #[test]
+    fn overridden_methods_are_called() {
+        let observer = RecordingObserver::default();
+        observer.on_phase_start("Generating analysis");
+        assert_eq!(observer.phases.into_inner(), vec!["Generating analysis".to_string()]);
+    }
+
+    #[test]
+    fn null_observer_ignores_every_event() {
+        let observer = NullObserver;
+        observer.on_phase_start("Generating analysis");
+        observer.on_warning("something to note");
+    }
+}
@@ -0,0 +1,93 @@
+//! Piping generated documentation through an external, user-provided
+//! command before it's written to disk, so an organization can inject
+//! custom attributes, strip internal modules, or rewrite links without
+//! forking this crate. See [`crate::Config::post_process`].
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::error::*;
+use crate::json::Documentation;
+
+/// Serialize `documentation` to JSON, pipe it through `command` (run via the
+/// platform shell, so it can be a full pipeline, e.g. `"jq '.data'"`), and
+/// deserialize its stdout back into a `Documentation`.
+///
+/// Runs outside [`crate::command::CommandBridge`]'s [`crate::command::ProcessRunner`]
+/// seam: that seam always runs a command with `Command::output`, which sets
+/// stdin to `Stdio::null`, but this needs to write JSON to the child's
+/// stdin. [`crate::cargo::generate_analysis`] is another subprocess call
+/// that bypasses the seam for a similar reason (it needs streaming stderr,
+/// not just a result once the child exits).
+pub fn run(command: &str, documentation: &Documentation) -> Result<Documentation> {
+    let input = serde_json::to_vec(documentation)?;
+
+    let shell = if cfg!(windows) { "cmd" } else { "sh" };
+    let shell_arg = if cfg!(windows) { "/C" } else { "-c" };
+
+    let mut child = Command::new(shell)
+        .arg(shell_arg)
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("failed to run post-process command '{}'", command))?;
+
+    child
+        .stdin
+        .take()
+        .expect("child was spawned with a piped stdin")
+        .write_all(&input)
+        .chain_err(|| format!("failed to write documentation to post-process command '{}'", command))?;
+
+    let output = child
+        .wait_with_output()
+        .chain_err(|| format!("failed to run post-process command '{}'", command))?;
+
+    if !output.status.success() {
+        return Err(format!("post-process command '{}' did not run successfully", command).into());
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .chain_err(|| format!("post-process command '{}' did not print valid documentation JSON", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn documentation() -> Documentation {
+        Documentation {
+            data: Data { id: "crate".to_string(), ty: "crate".to_string(), attributes: HashMap::new(), relationships: None, links: None },
+            included: Vec::new(),
+            meta: HashMap::new(),
+            links: None,
+        }
+    }
+
+    #[test]
+    fn a_command_that_echoes_its_input_round_trips_the_documentation() {
+        let result = run("cat", &documentation()).unwrap();
+        assert_eq!(result.data.id, "crate");
+    }
+
+    #[test]
+    fn a_command_can_rewrite_the_documentation() {
+        let result = run(r#"sed 's/"crate"/"widget"/'"#, &documentation()).unwrap();
+        assert_eq!(result.data.id, "widget");
+    }
+
+    #[test]
+    fn a_failing_command_is_reported_as_an_error() {
+        let error = run("exit 1", &documentation()).unwrap_err();
+        assert!(error.to_string().contains("post-process command"));
+    }
+
+    #[test]
+    fn a_command_producing_invalid_json_is_reported_as_an_error() {
+        let error = run("cat >/dev/null; echo not-json", &documentation()).unwrap_err();
+        assert!(error.to_string().contains("did not print valid documentation JSON"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Reconciling generated [`Documentation`] against the crate's own source,
+//! so a caller can tell when save-analysis silently dropped something (a
+//! private module under an odd `pub_only` setting, a macro-generated item)
+//! instead of trusting the generated documentation's completeness blindly.
+//!
+//! This only parses `entry_path` itself with `syn`; a `mod foo;`
+//! file-backed submodule isn't followed into its own file, so a discrepancy
+//! several modules deep won't be reported. This is a coarse completeness
+//! check on top of analysis, not a substitute for it.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde_derive::Serialize;
+use syn::visit::{self, Visit};
+use syn::Visibility;
+
+use crate::error::*;
+use crate::json::Documentation;
+
+/// A named, publicly visible item found in the crate's own source with no
+/// matching entry (by name) in [`Documentation`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingItem {
+    pub name: String,
+    pub kind: &'static str,
+}
+
+/// Walks a `syn::File`, collecting the name and kind of every `mod` (visited
+/// regardless of visibility, since a private module can still contain
+/// publicly re-exported items analysis should have picked up) and every
+/// other top-level item that's `pub`.
+#[derive(Default)]
+struct ItemCollector {
+    items: Vec<(String, &'static str)>,
+}
+
+impl ItemCollector {
+    fn record_if_public(&mut self, vis: &Visibility, name: &syn::Ident, kind: &'static str) {
+        if matches!(vis, Visibility::Public(_)) {
+            self.items.push((name.to_string(), kind));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ItemCollector {
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        self.items.push((node.ident.to_string(), "module"));
+        visit::visit_item_mod(self, node);
+    }
+
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        self.record_if_public(&node.vis, &node.ident, "struct");
+        visit::visit_item_struct(self, node);
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        self.record_if_public(&node.vis, &node.ident, "enum");
+        visit::visit_item_enum(self, node);
+    }
+
+    fn visit_item_union(&mut self, node: &'ast syn::ItemUnion) {
+        self.record_if_public(&node.vis, &node.ident, "union");
+        visit::visit_item_union(self, node);
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        self.record_if_public(&node.vis, &node.ident, "trait");
+        visit::visit_item_trait(self, node);
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        self.record_if_public(&node.vis, &node.sig.ident, "function");
+        visit::visit_item_fn(self, node);
+    }
+
+    fn visit_item_const(&mut self, node: &'ast syn::ItemConst) {
+        self.record_if_public(&node.vis, &node.ident, "constant");
+        visit::visit_item_const(self, node);
+    }
+
+    fn visit_item_static(&mut self, node: &'ast syn::ItemStatic) {
+        self.record_if_public(&node.vis, &node.ident, "static");
+        visit::visit_item_static(self, node);
+    }
+
+    fn visit_item_type(&mut self, node: &'ast syn::ItemType) {
+        self.record_if_public(&node.vis, &node.ident, "type");
+        visit::visit_item_type(self, node);
+    }
+}
+
+/// Parse `entry_path` (a crate's `[lib] path` entry file) with `syn` and
+/// return the name and kind of every `mod` and public item found in it.
+fn collect_source_items(entry_path: &Path) -> Result<Vec<(String, &'static str)>> {
+    let source = std::fs::read_to_string(entry_path).chain_err(|| format!("failed to read '{}'", entry_path.display()))?;
+    let file = syn::parse_file(&source).chain_err(|| format!("failed to parse '{}' with syn", entry_path.display()))?;
+
+    let mut collector = ItemCollector::default();
+    collector.visit_file(&file);
+    Ok(collector.items)
+}
+
+/// Every name `documentation` has an item for, whether that's its own
+/// `data` or something in `included`.
+fn known_names(documentation: &Documentation) -> HashSet<&str> {
+    std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .filter_map(|data| data.attributes.get("name").and_then(serde_json::Value::as_str))
+        .collect()
+}
+
+/// Compare `documentation`'s items against what `syn` finds by parsing
+/// `entry_path`, returning every source item with no by-name match in
+/// `documentation`.
+pub fn find_missing_items(documentation: &Documentation, entry_path: &Path) -> Result<Vec<MissingItem>> {
+    let known = known_names(documentation);
+    let source_items = collect_source_items(entry_path)?;
+
+    Ok(source_items
+        .into_iter()
+        .filter(|(name, _)| !known.contains(name.as_str()))
+        .map(|(name, kind)| MissingItem { name, kind })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn documentation_with_names(names: &[&str]) -> Documentation {
+        let included = names
+            .iter()
+            .map(|name| {
+                let mut attributes = HashMap::new();
+                attributes.insert("name".to_string(), serde_json::Value::String(name.to_string()));
+                Data {
+                    id: name.to_string(),
+                    ty: "struct".to_string(),
+                    attributes,
+                    relationships: None,
+                    links: None,
+                }
+            })
+            .collect();
+
+        Documentation {
+            data: Data::default(),
+            included,
+            meta: HashMap::new(),
+            links: None,
+        }
+    }
+
+    #[test]
+    fn a_module_missing_from_analysis_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        std::fs::write(&entry_path, "mod hidden;\npub struct Known;\n").unwrap();
+
+        let documentation = documentation_with_names(&["Known"]);
+        let missing = find_missing_items(&documentation, &entry_path).unwrap();
+
+        assert_eq!(missing, vec![MissingItem { name: "hidden".to_string(), kind: "module" }]);
+    }
+
+    #[test]
+    fn a_private_item_is_not_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        std::fs::write(&entry_path, "struct Private;\n").unwrap();
+
+        let documentation = documentation_with_names(&[]);
+        let missing = find_missing_items(&documentation, &entry_path).unwrap();
+
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn everything_already_documented_reports_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry_path = dir.path().join("lib.rs");
+        std::fs::write(&entry_path, "pub fn documented() {}\n").unwrap();
+
+        let documentation = documentation_with_names(&["documented"]);
+        let missing = find_missing_items(&documentation, &entry_path).unwrap();
+
+        assert!(missing.is_empty());
+    }
+}
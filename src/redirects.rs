@@ -0,0 +1,70 @@
+//! Loading a `redirects.toml` mapping old item paths to their new location,
+//! so a docs host can preserve deep links after an item is renamed or
+//! moved.
+//!
+//! Deriving redirects from `#[deprecated(note = "renamed to ...")]` instead
+//! isn't possible yet: `rls_analysis::Def` (0.18.3) doesn't expose
+//! attribute data at all (the same gap noted on [`crate::json::parse_doc_cfg`]
+//! and the `deprecated` attribute in [`crate::json`]), so there's no `note`
+//! text to parse. Only the explicit file is supported for now.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_derive::Deserialize;
+
+use crate::error::*;
+
+#[derive(Debug, Deserialize)]
+struct RedirectsFile {
+    #[serde(default)]
+    redirects: HashMap<String, String>,
+}
+
+/// Load `redirects.toml` from `crate_root`, if present. A missing file
+/// isn't an error: most crates don't have one.
+pub fn load_redirects(crate_root: &Path) -> Result<HashMap<String, String>> {
+    let path = crate_root.join("redirects.toml");
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(HashMap::new()),
+    };
+
+    let file: RedirectsFile =
+        toml::from_str(&contents).chain_err(|| format!("failed to parse '{}'", path.display()))?;
+
+    Ok(file.redirects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_redirects_from_the_crate_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("redirects.toml"),
+            "[redirects]\n\"old::Path\" = \"new::Path\"\n",
+        )
+        .unwrap();
+
+        let redirects = load_redirects(dir.path()).unwrap();
+        assert_eq!(redirects.get("old::Path").unwrap(), "new::Path");
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_map() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_redirects(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("redirects.toml"), "not valid toml =").unwrap();
+        assert!(load_redirects(dir.path()).is_err());
+    }
+}
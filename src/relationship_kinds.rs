@@ -0,0 +1,81 @@
+//! The fixed table of relationship kinds this crate's own generation ever
+//! produces (see [`crate::json`]'s `walk`), each paired with the singular
+//! type its members are expected to have.
+//!
+//! Centralizing them here, in one order, means [`crate::json::Relationships`]
+//! can serialize its keys in that same order instead of whatever order a
+//! `HashMap` happens to iterate in, and [`crate::validate`] has a single
+//! table to check a relationship's members against, rather than a
+//! hand-maintained copy of the same list.
+
+/// One relationship kind: the plural key it appears under in a `Data`'s
+/// `relationships` map, and the singular type name its members are expected
+/// to have. `singular` is `None` for a relationship that isn't a plural
+/// collection of one type, e.g. `"parent"` or `"usedBy"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelationshipKind {
+    pub plural: &'static str,
+    pub singular: Option<&'static str>,
+}
+
+/// Every relationship kind this crate generates, in the fixed order
+/// [`crate::json::Relationships`] serializes them in and a frontend can rely
+/// on for section ordering.
+pub const RELATIONSHIP_KINDS: &[RelationshipKind] = &[
+    RelationshipKind { plural: "modules", singular: Some("module") },
+    RelationshipKind { plural: "structs", singular: Some("struct") },
+    RelationshipKind { plural: "unions", singular: Some("union") },
+    RelationshipKind { plural: "enums", singular: Some("enum") },
+    RelationshipKind { plural: "traits", singular: Some("trait") },
+    RelationshipKind { plural: "functions", singular: Some("function") },
+    RelationshipKind { plural: "methods", singular: Some("method") },
+    RelationshipKind { plural: "parent", singular: None },
+    RelationshipKind { plural: "usedBy", singular: None },
+    RelationshipKind { plural: "implementations", singular: None },
+];
+
+/// The plural relationship key for `singular` (e.g. `"struct"` ->
+/// `"structs"`), falling back to appending an `s` for a type not in
+/// [`RELATIONSHIP_KINDS`].
+pub fn plural(singular: &str) -> String {
+    RELATIONSHIP_KINDS
+        .iter()
+        .find(|kind| kind.singular == Some(singular))
+        .map(|kind| kind.plural.to_string())
+        .unwrap_or_else(|| format!("{}s", singular))
+}
+
+/// Where `key` sorts among [`RELATIONSHIP_KINDS`], for ordering a
+/// relationship map's keys deterministically: known kinds in table order,
+/// then any other key alphabetically.
+pub fn rank(key: &str) -> (usize, &str) {
+    let index = RELATIONSHIP_KINDS.iter().position(|kind| kind.plural == key).unwrap_or(RELATIONSHIP_KINDS.len());
+    (index, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plural_looks_up_known_singulars_in_the_table() {
+        assert_eq!(plural("struct"), "structs");
+        assert_eq!(plural("module"), "modules");
+    }
+
+    #[test]
+    fn plural_falls_back_to_appending_an_s_for_unknown_types() {
+        assert_eq!(plural("constant"), "constants");
+    }
+
+    #[test]
+    fn rank_orders_known_kinds_before_unknown_ones() {
+        assert!(rank("modules") < rank("functions"));
+        assert!(rank("functions") < rank("some-unknown-kind"));
+    }
+
+    #[test]
+    fn rank_orders_unknown_kinds_alphabetically() {
+        assert!(rank("aaa") < rank("zzz"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Building a local usage report of a CLI invocation: which subcommand ran,
+//! with which options, how long it took, and how many items it touched.
+//!
+//! Meant as input for a monorepo's own build-system observability (e.g.
+//! aggregating how long `rustdoc build` takes across every crate in a CI
+//! run), not for this project's own use: nothing here is ever sent
+//! anywhere, only written to whatever path the caller passes `--report`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_derive::Serialize;
+
+use crate::error::*;
+use crate::write::write_if_changed;
+
+/// One CLI invocation's usage report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageReport {
+    pub subcommand: String,
+    pub options: HashMap<String, String>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u128,
+    #[serde(rename = "itemCount")]
+    pub item_count: Option<usize>,
+}
+
+impl UsageReport {
+    pub fn new(subcommand: impl Into<String>, options: HashMap<String, String>, duration: Duration, item_count: Option<usize>) -> Self {
+        UsageReport {
+            subcommand: subcommand.into(),
+            options,
+            duration_ms: duration.as_millis(),
+            item_count,
+        }
+    }
+}
+
+/// Write `report` to `path` as pretty-printed JSON.
+pub fn write_report(path: &Path, report: &UsageReport) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_if_changed(path, serde_json::to_string_pretty(report)?.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_the_report_as_pretty_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+
+        let mut options = HashMap::new();
+        options.insert("emit".to_string(), "json".to_string());
+        let report = UsageReport::new("build", options, Duration::from_millis(42), Some(3));
+
+        write_report(&path, &report).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("\"subcommand\": \"build\""));
+        assert!(contents.contains("\"durationMs\": 42"));
+        assert!(contents.contains("\"itemCount\": 3"));
+    }
+}
@@ -0,0 +1,114 @@
+//! A self-contained smoke test of the whole pipeline: generate a tiny
+//! sample crate, build its documentation, run its doc tests, and check the
+//! output actually describes what went in.
+//!
+//! Backs the hidden `rustdoc selftest` subcommand, so a packager or user
+//! can check their installation works end to end without a real crate on
+//! hand, and is exercised directly by `tests/selftest.rs` as this crate's
+//! own integration test.
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::*;
+
+const SAMPLE_MANIFEST: &str = r#"[package]
+name = "rustdoc-selftest"
+version = "0.1.0"
+edition = "2018"
+publish = false
+
+[lib]
+path = "src/lib.rs"
+"#;
+
+const SAMPLE_LIB: &str = r#"//! A tiny crate documented purely so `rustdoc selftest` has something
+//! realistic to run against.
+
+/// A widget with a name and a size.
+///
+/// ```rust
+/// let widget = rustdoc_selftest::Widget::new("gadget", 3);
+/// assert_eq!(widget.name(), "gadget");
+/// ```
+pub struct Widget {
+    name: String,
+    size: u32,
+}
+
+impl Widget {
+    /// Create a new widget.
+    pub fn new(name: &str, size: u32) -> Widget {
+        Widget {
+            name: name.to_string(),
+            size,
+        }
+    }
+
+    /// The widget's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The widget's size.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+"#;
+
+/// Write the sample crate (mirroring the repo's own `example/`) into `dir`.
+fn write_sample_crate(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join("Cargo.toml"), SAMPLE_MANIFEST)?;
+    fs::write(dir.join("src").join("lib.rs"), SAMPLE_LIB)?;
+    Ok(())
+}
+
+/// Check that `documentation` actually describes the sample crate's
+/// `Widget` struct and its docs.
+fn check_documentation(documentation: &crate::Documentation) -> Result<()> {
+    let widget = documentation
+        .included
+        .iter()
+        .find(|data| data.ty == "struct" && data.attributes.get("name").and_then(|v| v.as_str()) == Some("Widget"))
+        .ok_or("selftest: generated documentation has no 'Widget' struct")?;
+
+    let docs = widget.attributes.get("docs").and_then(|v| v.as_str()).unwrap_or("");
+    if docs.is_empty() {
+        return Err("selftest: 'Widget' has no docs".into());
+    }
+
+    Ok(())
+}
+
+/// Generate a sample crate, build its documentation, run its doc tests, and
+/// verify the output describes what was generated. Returns an error
+/// describing the first thing that didn't work.
+pub fn run() -> Result<()> {
+    let dir = tempfile::tempdir().chain_err(|| "selftest: couldn't create a temporary directory")?;
+    write_sample_crate(dir.path())?;
+
+    let config = crate::Config::new(dir.path().join("Cargo.toml"))
+        .chain_err(|| "selftest: couldn't configure the sample crate")?;
+    let documentation = crate::build(&config).chain_err(|| "selftest: build failed")?;
+
+    check_documentation(&documentation)?;
+
+    let tests_dir = crate::test::default_tests_dir(dir.path(), "rustdoc-selftest");
+    crate::test(
+        &documentation,
+        &tests_dir,
+        dir.path(),
+        false,
+        0,
+        false,
+        false,
+        false,
+        crate::test::TestBackend::Rustc,
+        crate::color::ColorChoice::Auto,
+    )
+        .chain_err(|| "selftest: doc tests failed")?;
+
+    Ok(())
+}
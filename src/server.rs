@@ -0,0 +1,178 @@
+//! An in-process HTTP JSON:API over already-generated [`Documentation`],
+//! for an editor or bot that wants to look up a handful of items without
+//! loading the whole `data.json` into its own process.
+//!
+//! This crate otherwise deliberately has no server or frontend of its own
+//! (see [`crate::browser`]'s doc comment); this module is the one
+//! exception, and it's opt-in behind the `api-server` Cargo feature so
+//! nothing here costs a normal build a dependency it doesn't want.
+//! `Documentation` is loaded once, up front, and held in memory for the
+//! life of the process — this is meant for a local, short-lived query
+//! session (an editor's "jump to docs", a CI bot's one-off lookup), not a
+//! long-running public service; there's no reload-on-change, TLS, or
+//! request-rate limiting.
+//!
+//! Three routes, matching the request: `GET /crates/:name` (the crate's own
+//! [`Data`], if `:name` matches), `GET /items/:id` (a single item by its
+//! resource id), and `GET /search?q=...` (every item whose `name` contains
+//! the query, case-insensitively). Every response is a JSON:API document:
+//! `{"data": ...}` on success, `{"errors": [{"title": ...}]}` otherwise.
+
+use std::io::Cursor;
+use std::net::SocketAddr;
+
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::error::*;
+use crate::json::{Data, Documentation};
+
+type JsonResponse = Response<Cursor<Vec<u8>>>;
+
+/// Serve `documentation` over HTTP at `addr` until the process is killed.
+pub fn serve(documentation: Documentation, addr: SocketAddr) -> Result<()> {
+    let server = Server::http(addr).map_err(|e| format!("failed to bind to {}: {}", addr, e))?;
+
+    for request in server.incoming_requests() {
+        let response = route(&documentation, request.url(), request.method());
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+fn route(documentation: &Documentation, url: &str, method: &Method) -> JsonResponse {
+    if *method != Method::Get {
+        return error_response(405, "method not allowed");
+    }
+
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+    if let Some(name) = path.strip_prefix("/crates/") {
+        return crate_response(documentation, name);
+    }
+
+    if let Some(id) = path.strip_prefix("/items/") {
+        return item_response(documentation, id);
+    }
+
+    if path == "/search" {
+        let q = query_param(query, "q").unwrap_or_default();
+        return search_response(documentation, &q);
+    }
+
+    error_response(404, "not found")
+}
+
+fn crate_response(documentation: &Documentation, name: &str) -> JsonResponse {
+    match documentation.data.attributes.get("name").and_then(|v| v.as_str()) {
+        Some(crate_name) if crate_name == name => data_response(&documentation.data),
+        _ => error_response(404, &format!("no crate named '{}'", name)),
+    }
+}
+
+fn item_response(documentation: &Documentation, id: &str) -> JsonResponse {
+    if documentation.data.id == id {
+        return data_response(&documentation.data);
+    }
+
+    match documentation.included.iter().find(|data| data.id == id) {
+        Some(data) => data_response(data),
+        None => error_response(404, &format!("no item with id '{}'", id)),
+    }
+}
+
+fn search_response(documentation: &Documentation, query: &str) -> JsonResponse {
+    let query = query.to_lowercase();
+    let matches: Vec<&Data> = documentation
+        .included
+        .iter()
+        .filter(|data| {
+            data.attributes
+                .get("name")
+                .and_then(|v| v.as_str())
+                .is_some_and(|name| name.to_lowercase().contains(&query))
+        })
+        .collect();
+
+    json_response(200, &serde_json::json!({ "data": matches }))
+}
+
+/// The value of `key` in a `key=value&...` query string, percent-decoded.
+fn query_param(query: &str, key: &str) -> Option<String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.into_owned())
+}
+
+fn data_response(data: &Data) -> JsonResponse {
+    json_response(200, &serde_json::json!({ "data": data }))
+}
+
+fn error_response(status: u16, title: &str) -> JsonResponse {
+    json_response(status, &serde_json::json!({ "errors": [{ "title": title }] }))
+}
+
+fn json_response(status: u16, body: &serde_json::Value) -> JsonResponse {
+    let bytes = serde_json::to_vec(body).unwrap_or_else(|_| b"{}".to_vec());
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/vnd.api+json"[..]).expect("static header is valid");
+
+    Response::from_data(bytes).with_status_code(status).with_header(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn documentation() -> Documentation {
+        let mut crate_attrs = HashMap::new();
+        crate_attrs.insert("name".to_string(), serde_json::Value::String("my_crate".to_string()));
+        let data = Data { id: "crate".to_string(), ty: "crate".to_string(), attributes: crate_attrs, relationships: None, links: None };
+
+        let mut widget_attrs = HashMap::new();
+        widget_attrs.insert("name".to_string(), serde_json::Value::String("Widget".to_string()));
+        let widget = Data { id: "widget".to_string(), ty: "struct".to_string(), attributes: widget_attrs, relationships: None, links: None };
+
+        Documentation { data, included: vec![widget], meta: HashMap::new(), links: None }
+    }
+
+    fn status(response: &JsonResponse) -> u16 {
+        response.status_code().0
+    }
+
+    #[test]
+    fn a_matching_crate_name_returns_its_data() {
+        let response = route(&documentation(), "/crates/my_crate", &Method::Get);
+        assert_eq!(status(&response), 200);
+    }
+
+    #[test]
+    fn an_unknown_crate_name_is_a_404() {
+        let response = route(&documentation(), "/crates/other_crate", &Method::Get);
+        assert_eq!(status(&response), 404);
+    }
+
+    #[test]
+    fn an_item_is_found_by_id() {
+        let response = route(&documentation(), "/items/widget", &Method::Get);
+        assert_eq!(status(&response), 200);
+    }
+
+    #[test]
+    fn a_search_for_a_substring_is_answered_with_a_200() {
+        let response = search_response(&documentation(), "widg");
+        assert_eq!(status(&response), 200);
+    }
+
+    #[test]
+    fn a_non_get_request_is_rejected() {
+        let response = route(&documentation(), "/crates/my_crate", &Method::Post);
+        assert_eq!(status(&response), 405);
+    }
+
+    #[test]
+    fn an_unrecognized_path_is_a_404() {
+        let response = route(&documentation(), "/nope", &Method::Get);
+        assert_eq!(status(&response), 404);
+    }
+}
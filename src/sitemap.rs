@@ -0,0 +1,123 @@
+//! Generating `sitemap.xml` and `robots.txt` for a crate's documentation,
+//! from the same `--base-url` (see [`crate::links`]) a frontend uses to
+//! build each item's own page.
+//!
+//! OpenGraph meta tags per page aren't produced here: this crate renders no
+//! HTML page for an item (its only HTML output is the source listings of
+//! [`crate::source_pages`]), so there's no item page to put a `<meta>` tag
+//! in. That's for the frontend rendering these pages to add,
+//! once it has enough of an item's data to fill one in — its `docs`
+//! attribute for a description and its `links.self` for the canonical URL
+//! are both already present on every item [`crate::links::add_links`]
+//! produces.
+
+use crate::json::Documentation;
+
+/// Build `sitemap.xml`'s contents: one `<url>` entry per documented item
+/// that has a `links.self`, i.e. every item once [`crate::links::add_links`]
+/// has run.
+pub fn build_sitemap(documentation: &Documentation) -> String {
+    let items = std::iter::once(&documentation.data).chain(documentation.included.iter());
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for item in items {
+        if let Some(url) = item.links.as_ref().and_then(|links| links.get("self")) {
+            xml.push_str(&format!("  <url><loc>{}</loc></url>\n", escape_xml(url)));
+        }
+    }
+    xml.push_str("</urlset>\n");
+
+    xml
+}
+
+/// Build `robots.txt`'s contents: allow everything, and point crawlers at
+/// the `sitemap.xml` written alongside it.
+pub fn build_robots_txt(base_url: &str) -> String {
+    format!("User-agent: *\nAllow: /\nSitemap: {}sitemap.xml\n", ensure_trailing_slash(base_url))
+}
+
+fn ensure_trailing_slash(base_url: &str) -> String {
+    if base_url.ends_with('/') {
+        base_url.to_string()
+    } else {
+        format!("{}/", base_url)
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn documentation_with_links() -> Documentation {
+        let mut data_links = HashMap::new();
+        data_links.insert("self".to_string(), "https://docs.example.com/crates/crate".to_string());
+
+        let mut item_links = HashMap::new();
+        item_links.insert("self".to_string(), "https://docs.example.com/structs/abc?a=1&b=2".to_string());
+
+        Documentation {
+            data: Data {
+                id: "crate".to_string(),
+                ty: "crate".to_string(),
+                links: Some(data_links),
+                ..Default::default()
+            },
+            included: vec![Data {
+                id: "abc".to_string(),
+                ty: "struct".to_string(),
+                links: Some(item_links),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lists_every_linked_item() {
+        let sitemap = build_sitemap(&documentation_with_links());
+        assert!(sitemap.contains("<loc>https://docs.example.com/crates/crate</loc>"));
+        assert!(sitemap.contains("structs/abc"));
+    }
+
+    #[test]
+    fn escapes_special_characters_in_urls() {
+        let sitemap = build_sitemap(&documentation_with_links());
+        assert!(sitemap.contains("a=1&amp;b=2"));
+        assert!(!sitemap.contains("a=1&b=2"));
+    }
+
+    #[test]
+    fn items_without_a_self_link_are_left_out() {
+        let documentation = Documentation::default();
+        let sitemap = build_sitemap(&documentation);
+        assert!(!sitemap.contains("<url>"));
+    }
+
+    #[test]
+    fn robots_txt_points_at_the_sitemap() {
+        assert_eq!(
+            build_robots_txt("https://docs.example.com"),
+            "User-agent: *\nAllow: /\nSitemap: https://docs.example.com/sitemap.xml\n"
+        );
+    }
+
+    #[test]
+    fn robots_txt_does_not_double_the_trailing_slash() {
+        assert_eq!(
+            build_robots_txt("https://docs.example.com/"),
+            "User-agent: *\nAllow: /\nSitemap: https://docs.example.com/sitemap.xml\n"
+        );
+    }
+}
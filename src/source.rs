@@ -0,0 +1,129 @@
+//! Reading source snippets for documented items, using the spans recorded
+//! in each item's `span` attribute.
+//!
+//! This is opt-in (see `Config::include_source`) since it means reading
+//! every source file an item's span points at, which isn't free for large
+//! crates.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::error::*;
+use crate::json::{Data, Documentation};
+
+/// Read `item`'s `span` attribute and return the lines it covers from disk.
+fn snippet_for(span: &Value, workspace_root: &Path) -> Result<Option<String>> {
+    let file = match span.get("file").and_then(Value::as_str) {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+    let line_start = span.get("lineStart").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let line_end = span.get("lineEnd").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+    let path = workspace_root.join(file);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        // Analysis data can reference files outside the crate (e.g. macros
+        // expanding into standard library source); skip those quietly.
+        Err(_) => return Ok(None),
+    };
+
+    let snippet: String = contents
+        .lines()
+        .skip(line_start)
+        .take(line_end.saturating_sub(line_start) + 1)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(Some(snippet))
+}
+
+/// Embed a `source` attribute on every item in `documentation` holding the
+/// snippet of source its span covers, resolved relative to
+/// `workspace_root`.
+pub fn embed_source_snippets(documentation: &mut Documentation, workspace_root: &Path) -> Result<()> {
+    let items: Vec<&mut Data> = std::iter::once(&mut documentation.data)
+        .chain(documentation.included.iter_mut())
+        .collect();
+
+    for data in items {
+        let span = match data.attributes.get("span").cloned() {
+            Some(span) => span,
+            None => continue,
+        };
+
+        if let Some(snippet) = snippet_for(&span, workspace_root)? {
+            data.attributes
+                .insert("source".to_string(), Value::String(snippet));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn embeds_the_lines_a_span_covers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "one\ntwo\nthree\nfour\n").unwrap();
+
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            "span".to_string(),
+            serde_json::json!({ "file": "lib.rs", "lineStart": 1, "lineEnd": 2 }),
+        );
+
+        let mut documentation = Documentation {
+            data: Data {
+                id: "crate".to_string(),
+                ty: "crate".to_string(),
+                attributes,
+                relationships: None,
+                ..Default::default()
+            },
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        embed_source_snippets(&mut documentation, dir.path()).unwrap();
+
+        assert_eq!(
+            documentation.data.attributes.get("source").unwrap(),
+            &Value::String("two\nthree".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_files_are_skipped_quietly() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            "span".to_string(),
+            serde_json::json!({ "file": "does-not-exist.rs", "lineStart": 0, "lineEnd": 0 }),
+        );
+
+        let mut documentation = Documentation {
+            data: Data {
+                id: "crate".to_string(),
+                ty: "crate".to_string(),
+                attributes,
+                relationships: None,
+                ..Default::default()
+            },
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        embed_source_snippets(&mut documentation, dir.path()).unwrap();
+        assert!(!documentation.data.attributes.contains_key("source"));
+    }
+}
@@ -0,0 +1,227 @@
+//! Rendering each source file an item's span points at into a static HTML
+//! page with a per-line anchor, so a `[src]` link works even when a
+//! consumer serves `output_path` with nothing else running to resolve the
+//! crate's own source tree.
+//!
+//! Gated behind the same [`crate::Config::include_source`] flag as
+//! [`crate::source::embed_source_snippets`]: both need every span's file
+//! read off disk, so a crate that doesn't want that cost pays it once, not
+//! twice.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::*;
+use crate::json::{Data, Documentation};
+use crate::write::write_if_changed;
+
+/// Escape the characters HTML gives special meaning, so a source line can be
+/// dropped into a page verbatim.
+fn escape_html(line: &str) -> String {
+    line.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Render `contents` (one file's full source) as a self-contained HTML page,
+/// with each line wrapped in an anchor (`id="L<n>"`) a `sourceHref` can
+/// target. `footer`, when given, is appended after the `<pre>` block as-is;
+/// callers own its content (see [`crate::license::footer`]), this module
+/// only knows how to place it on the page.
+fn render_source_page(contents: &str, footer: Option<&str>) -> String {
+    let mut page = String::from("<!DOCTYPE html>\n<html><body><pre>\n");
+    for (index, line) in contents.lines().enumerate() {
+        let number = index + 1;
+        page.push_str(&format!("<span id=\"L{0}\">{0}</span> {1}\n", number, escape_html(line)));
+    }
+    page.push_str("</pre>\n");
+    if let Some(footer) = footer {
+        page.push_str(&format!("<footer>{}</footer>\n", escape_html(footer)));
+    }
+    page.push_str("</body></html>\n");
+    page
+}
+
+/// A source file's path (as recorded in a `span` attribute) turned into the
+/// path its rendered page is written to under `output_dir/src`, e.g.
+/// `src/lib.rs` becomes `src/src/lib.rs.html`.
+fn page_path(file: &str) -> PathBuf {
+    let mut path = Path::new("src").join(file);
+    let file_name = format!("{}.html", path.file_name().and_then(|name| name.to_str()).unwrap_or("source"));
+    path.set_file_name(file_name);
+    path
+}
+
+/// Render an HTML page (with line anchors) for every source file referenced
+/// by a `span` attribute in `documentation`, writing each one under
+/// `output_dir`, and set a `sourceHref` attribute (a path relative to
+/// `output_dir`, e.g. `src/lib.rs.html#L10-12`) on every item whose span
+/// resolved to one, so a frontend can implement `[src]` links offline.
+///
+/// Returns every page path written, so a caller can report it as an
+/// artifact (see [`crate::observer::BuildObserver::on_artifact_written`]).
+///
+/// Like [`crate::source::embed_source_snippets`], a span pointing outside
+/// the crate (into an expanded macro, the standard library) is skipped
+/// quietly rather than failing the whole build.
+///
+/// `footer`, when given, is appended to every page rendered (see
+/// [`crate::Config::stamp_license`]); this module doesn't interpret it.
+pub fn build_source_pages(
+    documentation: &mut Documentation,
+    workspace_root: &Path,
+    output_dir: &Path,
+    footer: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    let mut pages: HashMap<String, PathBuf> = HashMap::new();
+    let mut written = Vec::new();
+
+    let items: Vec<&mut Data> = std::iter::once(&mut documentation.data)
+        .chain(documentation.included.iter_mut())
+        .collect();
+
+    for data in items {
+        let span = match data.attributes.get("span").cloned() {
+            Some(span) => span,
+            None => continue,
+        };
+
+        let file = match span.get("file").and_then(Value::as_str) {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if !pages.contains_key(file) {
+            let contents = match fs::read_to_string(workspace_root.join(file)) {
+                Ok(contents) => contents,
+                // Analysis data can reference files outside the crate, same
+                // as `embed_source_snippets`; skip those quietly.
+                Err(_) => continue,
+            };
+
+            let relative = page_path(file);
+            let output_file = output_dir.join(&relative);
+            fs::create_dir_all(output_file.parent().unwrap_or(output_dir))?;
+            write_if_changed(&output_file, render_source_page(&contents, footer).as_bytes())?;
+
+            written.push(output_file);
+            pages.insert(file.to_string(), relative);
+        }
+
+        let relative = match pages.get(file) {
+            Some(relative) => relative,
+            None => continue,
+        };
+
+        let line_start = span.get("lineStart").and_then(Value::as_u64).unwrap_or(0) + 1;
+        let line_end = span.get("lineEnd").and_then(Value::as_u64).unwrap_or(0) + 1;
+
+        data.attributes.insert(
+            "sourceHref".to_string(),
+            Value::String(format!("{}#L{}-{}", relative.display(), line_start, line_end)),
+        );
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap as Map;
+
+    fn data_with_span(id: &str, file: &str, line_start: u64, line_end: u64) -> Data {
+        let mut attributes = Map::new();
+        attributes.insert(
+            "span".to_string(),
+            serde_json::json!({ "file": file, "lineStart": line_start, "lineEnd": line_end }),
+        );
+        Data {
+            id: id.to_string(),
+            ty: "function".to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn writes_a_page_and_sets_a_source_href() {
+        let workspace = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        fs::write(workspace.path().join("lib.rs"), "one\ntwo\nthree\n").unwrap();
+
+        let mut documentation = Documentation {
+            data: data_with_span("crate", "lib.rs", 0, 0),
+            included: vec![data_with_span("a", "lib.rs", 1, 2)],
+            meta: Map::new(),
+            ..Default::default()
+        };
+
+        let written = build_source_pages(&mut documentation, workspace.path(), output.path(), None).unwrap();
+        assert_eq!(written, vec![output.path().join("src/lib.rs.html")]);
+
+        let page = fs::read_to_string(output.path().join("src/lib.rs.html")).unwrap();
+        assert!(page.contains("id=\"L2\""));
+        assert!(page.contains("two"));
+
+        assert_eq!(
+            documentation.included[0].attributes.get("sourceHref").unwrap(),
+            "src/lib.rs.html#L2-3"
+        );
+    }
+
+    #[test]
+    fn a_file_shared_by_two_items_is_only_rendered_once() {
+        let workspace = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        fs::write(workspace.path().join("lib.rs"), "one\ntwo\n").unwrap();
+
+        let mut documentation = Documentation {
+            data: data_with_span("crate", "lib.rs", 0, 0),
+            included: vec![data_with_span("a", "lib.rs", 0, 0), data_with_span("b", "lib.rs", 1, 1)],
+            meta: Map::new(),
+            ..Default::default()
+        };
+
+        let written = build_source_pages(&mut documentation, workspace.path(), output.path(), None).unwrap();
+        assert_eq!(written.len(), 1);
+    }
+
+    #[test]
+    fn a_footer_is_appended_to_every_rendered_page() {
+        let workspace = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        fs::write(workspace.path().join("lib.rs"), "one\n").unwrap();
+
+        let mut documentation = Documentation {
+            data: data_with_span("crate", "lib.rs", 0, 0),
+            included: Vec::new(),
+            meta: Map::new(),
+            ..Default::default()
+        };
+
+        build_source_pages(&mut documentation, workspace.path(), output.path(), Some("Licensed under MIT.")).unwrap();
+
+        let page = fs::read_to_string(output.path().join("src/lib.rs.html")).unwrap();
+        assert!(page.contains("<footer>Licensed under MIT.</footer>"));
+    }
+
+    #[test]
+    fn missing_files_are_skipped_quietly() {
+        let workspace = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+
+        let mut documentation = Documentation {
+            data: data_with_span("crate", "does-not-exist.rs", 0, 0),
+            included: Vec::new(),
+            meta: Map::new(),
+            ..Default::default()
+        };
+
+        let written = build_source_pages(&mut documentation, workspace.path(), output.path(), None).unwrap();
+        assert!(written.is_empty());
+        assert!(!documentation.data.attributes.contains_key("sourceHref"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Flagging doc examples whose text still refers to a crate item by a path
+//! that doesn't resolve to anything in the crate's own documentation
+//! anymore — the signature of an example left behind after the item it was
+//! written against was renamed or moved.
+//!
+//! Every doctest [`crate::test::find_tests`] extracts comes from an item
+//! that currently exists in [`Documentation`] (it's read straight off the
+//! item's own `docs` attribute), so there's no such thing here as a test
+//! whose *originating* item is missing. What this catches instead is an
+//! example whose *body* still names another item by its old path: renaming
+//! `Config::old_name` to `Config::new_name` doesn't touch an example that
+//! calls `Config::old_name(...)` anywhere else, so it silently keeps
+//! compiling right up until `old_name` is actually removed. This looks for
+//! that drift ahead of time, the same way `#[deprecated]` warns about a
+//! renamed item before it's gone.
+//!
+//! Like [`crate::extern_crates`], this is a plain substring heuristic
+//! against `qualname` text, not real name resolution (`rls_analysis::Def`
+//! 0.18.3 has no such API to resolve a path against); a crate path mentioned
+//! only in prose, rather than as an actual expression, can produce a false
+//! positive.
+
+use std::collections::HashSet;
+
+use serde_derive::Serialize;
+
+use crate::examples::find_examples;
+use crate::json::{Data, Documentation};
+
+/// A path inside a doc example that looks like a reference to one of the
+/// crate's own items, but doesn't match any item's `qualname` anymore.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleReference {
+    /// The item whose doc example mentions `path`.
+    pub item: String,
+    /// The crate-relative path the example references, e.g.
+    /// `"my_crate::Config::old_name"`.
+    pub path: String,
+}
+
+/// Every `crate_name::...` token found in `text`, stopping each one at the
+/// first character that couldn't be part of a path (so `Config::new()`'s
+/// trailing `()` isn't swept in).
+fn crate_paths(text: &str, crate_name: &str) -> Vec<String> {
+    let prefix = format!("{}::", crate_name);
+    let mut found = Vec::new();
+    let mut offset = 0;
+
+    while let Some(relative_start) = text[offset..].find(&prefix) {
+        let start = offset + relative_start;
+        let end = text[start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .map(|relative_end| start + relative_end)
+            .unwrap_or(text.len());
+
+        found.push(text[start..end].to_string());
+        offset = end.max(start + prefix.len());
+    }
+
+    found
+}
+
+/// Every `qualname` [`Documentation`] currently has an item for.
+fn known_qualnames(documentation: &Documentation) -> HashSet<&str> {
+    std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .filter_map(|data| data.attributes.get("qualname").and_then(serde_json::Value::as_str))
+        .collect()
+}
+
+/// Find every doc example across `documentation` whose body names a
+/// `crate_name::...` path that isn't among the crate's own `qualname`s.
+pub fn find_stale_references(documentation: &Documentation, crate_name: &str) -> Vec<StaleReference> {
+    let known = known_qualnames(documentation);
+    let mut stale = Vec::new();
+
+    let all_data: Vec<&Data> = std::iter::once(&documentation.data).chain(documentation.included.iter()).collect();
+    for data in all_data {
+        let docs = match data.attributes.get("docs").and_then(|v| v.as_str()) {
+            Some(docs) => docs,
+            None => continue,
+        };
+        let item = data.attributes.get("qualname").and_then(|v| v.as_str()).unwrap_or(&data.id);
+
+        for example in find_examples(docs) {
+            for path in crate_paths(&example.compiled, crate_name) {
+                if !known.contains(path.as_str()) {
+                    stale.push(StaleReference { item: item.to_string(), path });
+                }
+            }
+        }
+    }
+
+    stale.sort_by(|a, b| (&a.item, &a.path).cmp(&(&b.item, &b.path)));
+    stale.dedup();
+    stale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Relationships;
+    use std::collections::HashMap;
+
+    fn data(id: &str, qualname: &str, docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("qualname".to_string(), serde_json::Value::String(qualname.to_string()));
+        attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        Data { id: id.to_string(), ty: "function".to_string(), attributes, relationships: Some(Relationships::default()), ..Default::default() }
+    }
+
+    #[test]
+    fn a_path_matching_a_known_qualname_is_not_flagged() {
+        let docs = "```rust\nmy_crate::Config::new();\n```\n";
+        let documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("config_new", "my_crate::Config::new", docs)],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        assert!(find_stale_references(&documentation, "my_crate").is_empty());
+    }
+
+    #[test]
+    fn a_path_to_a_renamed_item_is_flagged() {
+        let docs = "```rust\nmy_crate::Config::old_name();\n```\n";
+        let documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("caller", "my_crate::caller", docs), data("config_new", "my_crate::Config::new_name", "")],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        let stale = find_stale_references(&documentation, "my_crate");
+        assert_eq!(stale, vec![StaleReference { item: "my_crate::caller".to_string(), path: "my_crate::Config::old_name".to_string() }]);
+    }
+
+    #[test]
+    fn a_path_to_a_different_crate_is_ignored() {
+        let docs = "```rust\nother_crate::Thing::new();\n```\n";
+        let documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("item", "my_crate::item", docs)],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        assert!(find_stale_references(&documentation, "my_crate").is_empty());
+    }
+
+    #[test]
+    fn the_same_stale_path_from_one_item_is_only_reported_once() {
+        let docs = "```rust\nmy_crate::old();\nmy_crate::old();\n```\n";
+        let documentation = Documentation {
+            data: data("crate", "my_crate", ""),
+            included: vec![data("item", "my_crate::item", docs)],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        assert_eq!(find_stale_references(&documentation, "my_crate").len(), 1);
+    }
+}
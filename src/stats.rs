@@ -0,0 +1,125 @@
+//! Computing a build's `meta.stats` summary: how many items were
+//! documented, broken down by kind, and how many doc examples were found
+//! across all of them.
+//!
+//! Always computed and inserted into `meta` (unlike `metrics.json`, this is
+//! cheap enough not to need its own opt-in flag). `rustdoc build --stats`
+//! prints a one-line human summary derived from the same numbers, so the
+//! printed line and `meta.stats` never disagree.
+
+use std::collections::BTreeMap;
+
+use serde_derive::Serialize;
+
+use crate::examples::find_examples;
+use crate::json::Documentation;
+
+/// A build's item counts by kind and total doc example count. See the
+/// module docs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    #[serde(rename = "itemsByType")]
+    pub items_by_type: BTreeMap<String, usize>,
+    #[serde(rename = "exampleCount")]
+    pub example_count: usize,
+}
+
+/// Compute [`Stats`] over `documentation`'s own `data` plus everything in
+/// `included`.
+pub fn compute_stats(documentation: &Documentation) -> Stats {
+    let mut items_by_type = BTreeMap::new();
+    let mut example_count = 0;
+
+    for data in std::iter::once(&documentation.data).chain(documentation.included.iter()) {
+        *items_by_type.entry(data.ty.clone()).or_insert(0) += 1;
+
+        if let Some(docs) = data.attributes.get("docs").and_then(|v| v.as_str()) {
+            example_count += find_examples(docs).len();
+        }
+    }
+
+    Stats { items_by_type, example_count }
+}
+
+impl Stats {
+    /// A one-line human summary resembling cargo's own "Finished" line,
+    /// e.g. `documented 42 items (10 functions, 12 modules, 20 structs), 8
+    /// examples`.
+    pub fn summary(&self) -> String {
+        let total: usize = self.items_by_type.values().sum();
+        let breakdown: Vec<String> =
+            self.items_by_type.iter().map(|(ty, count)| format!("{} {}", count, pluralize(ty, *count))).collect();
+
+        format!(
+            "documented {} item{} ({}), {} example{}",
+            total,
+            if total == 1 { "" } else { "s" },
+            breakdown.join(", "),
+            self.example_count,
+            if self.example_count == 1 { "" } else { "s" },
+        )
+    }
+}
+
+fn pluralize(ty: &str, count: usize) -> String {
+    if count == 1 {
+        ty.to_string()
+    } else {
+        format!("{}s", ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn data(ty: &str, docs: Option<&str>) -> Data {
+        let mut attributes = HashMap::new();
+        if let Some(docs) = docs {
+            attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        }
+        Data { id: ty.to_string(), ty: ty.to_string(), attributes, relationships: None, links: None }
+    }
+
+    #[test]
+    fn counts_items_by_type_and_examples_across_docs() {
+        let documentation = Documentation {
+            data: data("crate", None),
+            included: vec![
+                data("struct", Some("```rust\nlet x = 1;\n```\n")),
+                data("struct", None),
+                data("function", Some("```rust\nlet a = 1;\n```\n\n```rust\nlet b = 2;\n```\n")),
+            ],
+            meta: HashMap::new(),
+            links: None,
+        };
+
+        let stats = compute_stats(&documentation);
+
+        assert_eq!(stats.items_by_type.get("crate"), Some(&1));
+        assert_eq!(stats.items_by_type.get("struct"), Some(&2));
+        assert_eq!(stats.items_by_type.get("function"), Some(&1));
+        assert_eq!(stats.example_count, 3);
+    }
+
+    #[test]
+    fn summary_pluralizes_singular_counts_correctly() {
+        let documentation = Documentation { data: data("crate", None), included: Vec::new(), meta: HashMap::new(), links: None };
+        let stats = compute_stats(&documentation);
+        assert_eq!(stats.summary(), "documented 1 item (1 crate), 0 examples");
+    }
+
+    #[test]
+    fn summary_pluralizes_item_kinds_and_counts() {
+        let documentation = Documentation {
+            data: data("crate", None),
+            included: vec![data("struct", None), data("struct", None)],
+            meta: HashMap::new(),
+            links: None,
+        };
+        let stats = compute_stats(&documentation);
+        assert_eq!(stats.summary(), "documented 3 items (1 crate, 2 structs), 0 examples");
+    }
+}
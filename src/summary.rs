@@ -0,0 +1,218 @@
+//! Deriving each item's `plainSummary` attribute: a short, markdown-free
+//! description of an item, for a frontend to use as a page's `<meta
+//! name="description">` and social card (Open Graph, Twitter Card) text
+//! when it renders that item's own page.
+//!
+//! This crate doesn't render item pages itself (its only HTML output is the
+//! source listings of [`crate::source_pages`]); see the crate's own
+//! top-level doc comment. `plainSummary` is exposed as a `data.json` attribute so
+//! whichever frontend does render HTML has a ready per-item description,
+//! instead of every frontend re-deriving the same thing from `docs` (first
+//! paragraph, markdown stripped, truncated to a sensible length).
+
+use serde_derive::Serialize;
+use serde_json::Value;
+
+use crate::json::Documentation;
+
+/// The default truncation length, matching the ~155-160 character window
+/// most search engines and social platforms actually display of a page's
+/// description before cutting it off.
+pub const DEFAULT_SUMMARY_LENGTH: usize = 160;
+
+/// The suffix appended to a summary truncated short of `docs`'s first
+/// paragraph.
+const ELLIPSIS: &str = "…";
+
+/// Strip the markdown syntax [`plain_summary`] doesn't want carried into a
+/// plain-text description: fenced/inline code backticks, heading `#`s, a
+/// link's `[text](url)` down to just `text`, and `*`/`_` emphasis markers.
+fn strip_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' | '*' | '_' | '#' => {}
+            '[' => {
+                let mut label = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    label.push(c);
+                }
+                out.push_str(&label);
+                if chars.peek() == Some(&'(') {
+                    for c in chars.by_ref() {
+                        if c == ')' {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Truncate `text` to at most `max_len` characters, breaking on the last
+/// word boundary before the limit and appending [`ELLIPSIS`], rather than
+/// cutting a word in half. The second element of the pair is whether
+/// truncation actually happened.
+fn truncate(text: &str, max_len: usize) -> (String, bool) {
+    if text.chars().count() <= max_len {
+        return (text.to_string(), false);
+    }
+
+    let truncated: String = text.chars().take(max_len).collect();
+    let truncated = truncated.rsplit_once(' ').map_or(truncated.as_str(), |(head, _)| head);
+    (format!("{}{}", truncated.trim_end(), ELLIPSIS), true)
+}
+
+/// An item's `docs` attribute, reduced to its first paragraph three ways:
+/// as markdown, as plain text, and whether the plain-text form had to be
+/// truncated to fit. Set as the `summary` attribute (see
+/// [`apply_summaries`]).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    /// The first paragraph of `docs`, markdown intact.
+    pub markdown: String,
+    /// [`Summary::markdown`] with markdown syntax stripped, whitespace
+    /// collapsed, and truncated to fit the configured length. What
+    /// `plainSummary` (see [`apply_summaries`]) already held.
+    pub plain: String,
+    /// Whether [`Summary::plain`] is shorter than [`Summary::markdown`]'s
+    /// plain-text form because it had to be truncated to fit.
+    pub truncated: bool,
+}
+
+/// Derive a structured summary of `docs`, truncating its plain-text form to
+/// `max_len` characters (see [`truncate`]).
+pub fn summarize(docs: &str, max_len: usize) -> Summary {
+    let markdown = docs.split("\n\n").next().unwrap_or("").trim().to_string();
+    let collapsed = strip_markdown(&markdown).split_whitespace().collect::<Vec<_>>().join(" ");
+    let (plain, truncated) = truncate(&collapsed, max_len);
+    Summary { markdown, plain, truncated }
+}
+
+/// Derive a plain-text summary of `docs`: its first paragraph, markdown
+/// stripped, whitespace collapsed, truncated to `max_len` characters. A thin
+/// wrapper around [`summarize`] for a caller that only wants the plain-text
+/// form.
+pub fn plain_summary(docs: &str, max_len: usize) -> String {
+    summarize(docs, max_len).plain
+}
+
+/// Set a structured `summary` attribute (see [`Summary`]) on every item in
+/// `documentation`, derived from its `docs` attribute.
+///
+/// The legacy `plainSummary` string attribute is also set, holding the same
+/// text as `summary.plain`, so a consumer still reading the old shape keeps
+/// working for one more release; it's slated for removal once frontends
+/// have moved over to `summary`.
+pub fn apply_summaries(documentation: &mut Documentation, max_len: usize) {
+    let items = std::iter::once(&mut documentation.data).chain(documentation.included.iter_mut());
+
+    for data in items {
+        let docs = data.attributes.get("docs").and_then(Value::as_str).unwrap_or("").to_string();
+        let summary = summarize(&docs, max_len);
+
+        data.attributes.insert("plainSummary".to_string(), Value::String(summary.plain.clone()));
+        data.attributes.insert("summary".to_string(), serde_json::to_value(&summary).unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Data;
+    use std::collections::HashMap;
+
+    fn data_with_docs(docs: &str) -> Data {
+        let mut attributes = HashMap::new();
+        attributes.insert("docs".to_string(), Value::String(docs.to_string()));
+        Data { attributes, ..Default::default() }
+    }
+
+    #[test]
+    fn takes_only_the_first_paragraph() {
+        let summary = plain_summary("First paragraph.\n\nSecond paragraph.", 160);
+        assert_eq!(summary, "First paragraph.");
+    }
+
+    #[test]
+    fn strips_markdown_syntax() {
+        let summary = plain_summary("Uses `Vec<T>` and a [link](https://example.com) with **bold** text.", 160);
+        assert_eq!(summary, "Uses Vec<T> and a link with bold text.");
+    }
+
+    #[test]
+    fn truncates_on_a_word_boundary_with_an_ellipsis() {
+        let docs = "one two three four five six seven eight nine ten";
+        let summary = plain_summary(docs, 20);
+        assert_eq!(summary, "one two three four…");
+        assert!(summary.chars().count() <= 21);
+    }
+
+    #[test]
+    fn short_docs_are_left_untouched() {
+        assert_eq!(plain_summary("Short.", 160), "Short.");
+    }
+
+    #[test]
+    fn apply_summaries_sets_the_attribute_on_every_item() {
+        let mut documentation = Documentation {
+            data: data_with_docs("Crate-level docs."),
+            included: vec![data_with_docs("Item docs.")],
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        apply_summaries(&mut documentation, DEFAULT_SUMMARY_LENGTH);
+
+        assert_eq!(
+            documentation.data.attributes.get("plainSummary").and_then(Value::as_str),
+            Some("Crate-level docs.")
+        );
+        assert_eq!(
+            documentation.included[0].attributes.get("plainSummary").and_then(Value::as_str),
+            Some("Item docs.")
+        );
+    }
+
+    #[test]
+    fn apply_summaries_also_sets_the_structured_attribute() {
+        let mut documentation = Documentation {
+            data: data_with_docs("Crate-level docs."),
+            included: Vec::new(),
+            meta: HashMap::new(),
+            ..Default::default()
+        };
+
+        apply_summaries(&mut documentation, DEFAULT_SUMMARY_LENGTH);
+
+        let summary = documentation.data.attributes.get("summary").unwrap();
+        assert_eq!(summary["markdown"], "Crate-level docs.");
+        assert_eq!(summary["plain"], "Crate-level docs.");
+        assert_eq!(summary["truncated"], false);
+    }
+
+    #[test]
+    fn summarize_keeps_markdown_intact_but_strips_it_from_plain() {
+        let summary = summarize("Uses `Vec<T>` for storage.\n\nMore.", 160);
+        assert_eq!(summary.markdown, "Uses `Vec<T>` for storage.");
+        assert_eq!(summary.plain, "Uses Vec<T> for storage.");
+        assert!(!summary.truncated);
+    }
+
+    #[test]
+    fn summarize_flags_truncation() {
+        let docs = "one two three four five six seven eight nine ten";
+        let summary = summarize(docs, 20);
+        assert!(summary.truncated);
+        assert!(summary.plain.ends_with('…'));
+    }
+}
@@ -0,0 +1,833 @@
+//! Extracting and running the doc examples embedded in a crate's
+//! documentation.
+
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::color::ColorChoice;
+use crate::command::ProcessRunner;
+use crate::doctest::DoctestConfig;
+use crate::error::*;
+use crate::examples::find_examples;
+use crate::json::{Data, Documentation};
+
+/// A single doc example, extracted from an item's `docs` attribute and
+/// ready to be written out and compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Test {
+    /// A name derived from the item the example came from, e.g.
+    /// `"my_crate_Thing_foo_0"`. Built from the item's (already normalized,
+    /// see [`crate::json::normalize_qualname`]) `qualname` attribute rather
+    /// than its opaque JSON-API id, so a build script failure or a
+    /// `rustc` version bump doesn't churn every test's name. Already a
+    /// valid, filesystem-safe, and unique Rust identifier (see
+    /// [`unique_name`]); nothing further needs to sanitize it.
+    pub name: String,
+    /// The compilable source of the example, wrapped in a `fn main`.
+    pub text: String,
+}
+
+/// Whether `body` uses the `?` operator anywhere outside a string, char, or
+/// comment, the same signal upstream `rustdoc` uses to decide an example
+/// wants a `Result`-returning `main` rather than a plain one.
+///
+/// This is a lexical scan rather than a full tokenizer, so it can be fooled
+/// by things like raw strings or byte string literals; that's an acceptable
+/// trade-off for a heuristic that only affects how an example is wrapped,
+/// not what it asserts.
+fn uses_try_operator(body: &str) -> bool {
+    let mut chars = body.chars().peekable();
+    let mut in_line_comment = false;
+    let mut in_block_comment = false;
+    let mut in_string = false;
+    let mut in_char = false;
+
+    while let Some(c) = chars.next() {
+        if in_line_comment {
+            if c == '\n' {
+                in_line_comment = false;
+            }
+            continue;
+        }
+
+        if in_block_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block_comment = false;
+            }
+            continue;
+        }
+
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        if in_char {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' => in_char = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '/' if chars.peek() == Some(&'/') => in_line_comment = true,
+            '/' if chars.peek() == Some(&'*') => in_block_comment = true,
+            '"' => in_string = true,
+            '\'' => in_char = true,
+            '?' => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Wrap a single example's body in a runnable `fn main`, prefixed with any
+/// crate-level inner attributes (via [`DoctestConfig::attrs`]) and, for a
+/// `#![no_std]` crate, its [`DoctestConfig::harness`].
+///
+/// Examples using the `?` operator (e.g. `let x = foo()?;`) need `main` to
+/// return a `Result`, or they won't compile; those are wrapped in a
+/// `Result`-returning `main` that returns `Ok(())` on success, mirroring
+/// upstream `rustdoc`'s doc test handling. [`DoctestConfig::no_std`] examples
+/// are never wrapped this way even if they use `?`, since the `Result`'s
+/// `Box<dyn std::error::Error>` isn't available without `std`; a `no_std`
+/// example that wants a fallible `main` has to write its own out in full.
+fn preprocess(body: &str, doctest_config: &DoctestConfig) -> String {
+    let attrs: String = doctest_config.attrs.iter().map(|attr| format!("#![{}]\n", attr)).collect();
+    let harness = if doctest_config.no_std {
+        doctest_config.harness.as_deref().map(|harness| format!("{}\n", harness)).unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    if !doctest_config.no_std && uses_try_operator(body) {
+        format!(
+            "{}{}fn main() -> Result<(), Box<dyn std::error::Error>> {{\n{}\nOk(())\n}}\n",
+            harness, attrs, body
+        )
+    } else {
+        format!("{}{}fn main() {{\n{}\n}}\n", harness, attrs, body)
+    }
+}
+
+/// Whether `data`'s `deprecated` attribute is set.
+fn is_deprecated(data: &Data) -> bool {
+    data.attributes
+        .get("deprecated")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Extract every doc example out of `documentation`.
+///
+/// `doctest_config`'s [`DoctestConfig::attrs`] are prepended to every
+/// compiled example (see [`preprocess`]).
+///
+/// When `skip_deprecated` is set, examples belonging to deprecated items are
+/// left out, and a one-line summary of how many were skipped is printed to
+/// stderr.
+pub fn find_tests(documentation: &Documentation, doctest_config: &DoctestConfig, skip_deprecated: bool) -> Vec<Test> {
+    let mut tests = Vec::new();
+    let mut skipped = 0;
+    let mut seen_names = HashSet::new();
+
+    let all_data: Vec<&Data> = std::iter::once(&documentation.data)
+        .chain(documentation.included.iter())
+        .collect();
+
+    for data in all_data {
+        let docs = match data.attributes.get("docs").and_then(|v| v.as_str()) {
+            Some(docs) => docs,
+            None => continue,
+        };
+
+        let examples = find_examples(docs);
+
+        if skip_deprecated && is_deprecated(data) {
+            skipped += examples.len();
+            continue;
+        }
+
+        let qualname = data
+            .attributes
+            .get("qualname")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&data.id);
+
+        let base_name = unique_name(qualname, &mut seen_names);
+
+        for (index, example) in examples.into_iter().enumerate() {
+            tests.push(Test {
+                name: format!("{}_{}", base_name, index),
+                text: preprocess(&example.compiled, doctest_config),
+            });
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!("skipped {} doc test(s) from deprecated items", skipped);
+    }
+
+    tests
+}
+
+/// Turn a qualname into a valid Rust identifier / filesystem-safe name.
+///
+/// Every run of characters that isn't `[A-Za-z0-9_]` becomes a single `_`
+/// (or nothing, right after an existing `_`) — this covers not just
+/// the `::` module separator but generic parameters (`Vec<T>`) and the
+/// `[hash]` suffix analysis sometimes appends to a qualname to disambiguate
+/// two items that would otherwise share one. A result that would start with
+/// a digit (e.g. a tuple struct field's qualname) is prefixed with `_`,
+/// since Rust identifiers can't start with one.
+fn sanitize_name(name: &str) -> String {
+    let mut sanitized = String::with_capacity(name.len());
+    let mut last_was_underscore = false;
+
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            sanitized.push(c);
+            last_was_underscore = c == '_';
+        } else if !last_was_underscore {
+            sanitized.push('_');
+            last_was_underscore = true;
+        }
+    }
+
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+/// [`sanitize_name`] `name`, appending a short hash of the original `name`
+/// when the sanitized result collides with one already in `seen` (e.g.
+/// `a::b_c` and `a_b::c` both sanitize to `a_b_c`), so two distinct qualnames
+/// never produce the same test name or output file.
+fn unique_name(name: &str, seen: &mut HashSet<String>) -> String {
+    let sanitized = sanitize_name(name);
+    if seen.insert(sanitized.clone()) {
+        return sanitized;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    let disambiguated = format!("{}_{:x}", sanitized, hasher.finish());
+    seen.insert(disambiguated.clone());
+    disambiguated
+}
+
+/// Where doc test sources and binaries are written by default:
+/// `<manifest_dir>/target/rustdoc-test/<crate_name>`.
+///
+/// Deliberately outside [`crate::Config::output_path`] (the published docs
+/// directory), so a `data.json` published to a docs site never drags
+/// generated test sources or binaries along with it. Callers that want a
+/// different location (e.g. a shared CI cache directory) can pass their own
+/// path to [`crate::test`] instead of this default.
+pub fn default_tests_dir(manifest_dir: &Path, crate_name: &str) -> PathBuf {
+    manifest_dir.join("target").join("rustdoc-test").join(crate_name)
+}
+
+/// Write every test's source into `output_dir`, returning the paths written.
+pub fn save_tests(tests: &[Test], output_dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(output_dir)?;
+
+    let mut paths = Vec::new();
+    for test in tests {
+        let path = output_dir.join(format!("{}.rs", test.name));
+        fs::write(&path, &test.text)?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Compile every saved test with `rustc`, returning the resulting binaries.
+///
+/// Paths are threaded through as `Path`/`PathBuf` end to end (`Command::arg`
+/// and `Path::join` both take anything `AsRef<OsStr>`), and error messages
+/// use `Path::display`'s lossy formatting rather than `to_str().unwrap()`, so
+/// a crate under a non-UTF8 or merely non-ASCII path is compiled and run the
+/// same as any other; only the error text for a genuinely non-UTF8 path may
+/// render with replacement characters.
+///
+/// Each binary's name is given `std::env::consts::EXE_SUFFIX` explicitly
+/// (`.exe` on Windows, nothing elsewhere), so the returned path is the exact
+/// file `rustc` wrote and can be handed straight to [`run_once`] without
+/// either side guessing at the other's naming convention.
+///
+/// `extern_crates` (name, rlib path) pairs, built by
+/// [`crate::cargo::build_extern_crate`] from
+/// [`crate::doctest::DoctestConfig::inject`], are each passed to `rustc` as
+/// `--extern name=path`, so an example can use them without its own
+/// boilerplate.
+///
+/// `color` forwards `--color` to `rustc`, so a failing example's output is
+/// colored (or not) the same as the rest of this crate's own output; see
+/// [`crate::color`].
+pub fn compile_tests(
+    paths: &[PathBuf],
+    out_dir: &Path,
+    extern_crates: &[(String, PathBuf)],
+    color: ColorChoice,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir)?;
+
+    let mut binaries = Vec::new();
+    for path in paths {
+        let stem = path
+            .file_stem()
+            .ok_or_else(|| format!("test path '{}' has no file name", path.display()))?
+            .to_string_lossy();
+        // Bake in the platform's executable suffix (`.exe` on Windows,
+        // nothing elsewhere) ourselves rather than relying on `rustc` to
+        // add one to a bare `-o` name, so the path we later hand to
+        // `Command::new` in `run_once` is exactly the file `rustc` wrote.
+        let binary = out_dir.join(format!("{}{}", stem, std::env::consts::EXE_SUFFIX));
+
+        let mut command = Command::new("rustc");
+        command.arg(path).arg("-o").arg(&binary).arg("--color").arg(color.as_cargo_arg());
+        for (name, rlib) in extern_crates {
+            let mut extern_arg = std::ffi::OsString::from(format!("{}=", name));
+            extern_arg.push(rlib);
+            command.arg("--extern").arg(extern_arg);
+        }
+
+        let status = command
+            .status()
+            .chain_err(|| ErrorKind::Cargo(format!("rustc {}", path.display())))?;
+
+        if !status.success() {
+            return Err(ErrorKind::Cargo(format!("rustc {}", path.display())).into());
+        }
+
+        binaries.push(binary);
+    }
+
+    Ok(binaries)
+}
+
+/// Which backend compiles and runs a crate's [`Test`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TestBackend {
+    /// Compile each example directly with `rustc` (see [`compile_tests`] and
+    /// [`run_tests`]). Starts up fast, but doesn't see the documented
+    /// crate's own `Cargo.toml`: dependency resolution goes only as far as
+    /// [`crate::doctest::DoctestConfig::inject`]'s `--extern` wiring, the
+    /// crate's edition isn't picked up, and nothing is cached between runs.
+    #[default]
+    Rustc,
+    /// Generate a throwaway Cargo package depending on the documented crate
+    /// by path, and run `cargo test` in it (see
+    /// [`compile_and_run_with_cargo`]). Slower to start (cargo resolves and
+    /// builds the crate's whole dependency graph), but gets correct
+    /// dependency resolution, edition handling, and incremental compilation
+    /// across runs for free.
+    Cargo,
+}
+
+impl TestBackend {
+    /// Parse a `--doctest-backend` value, e.g. `"cargo"`.
+    pub fn parse(value: &str) -> Result<TestBackend> {
+        match value {
+            "rustc" => Ok(TestBackend::Rustc),
+            "cargo" => Ok(TestBackend::Cargo),
+            other => Err(format!("unsupported --doctest-backend '{}'; expected 'rustc' or 'cargo'", other).into()),
+        }
+    }
+}
+
+/// Turn `test`'s `fn main`-wrapped source (see [`preprocess`]) into a
+/// `#[test]` function named after it, for [`compile_and_run_with_cargo`]'s
+/// generated integration test file.
+///
+/// This is a plain string replacement rather than reparsing `test.text`; a
+/// `#[test]` function is allowed to return the same
+/// `Result<(), Box<dyn std::error::Error>>` a `?`-using example's `main`
+/// already does, so renaming `fn main` in place is enough either way.
+fn as_integration_test(test: &Test) -> String {
+    test.text.replacen("fn main(", &format!("#[test]\nfn {}(", test.name), 1)
+}
+
+/// Parse `cargo test`'s own stdout into one [`TestOutcome`] per `test <name>
+/// ... ok|FAILED` line it prints.
+///
+/// Unlike [`run_tests_with_outcomes`], this doesn't get a wall-clock duration
+/// or captured stdout/stderr per example: cargo's default test harness
+/// doesn't report either on its own summary line, and mining them out of its
+/// `---- name stdout ----` failure sections isn't attempted here.
+fn parse_cargo_test_output(stdout: &str) -> Vec<TestOutcome> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, status) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+            Some(TestOutcome {
+                binary: name.trim().to_string(),
+                duration: Duration::default(),
+                success: status.trim() == "ok",
+                stdout: String::new(),
+                stderr: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Generate a throwaway Cargo package under `package_dir` depending on the
+/// crate at `crate_manifest_path` (named `crate_name`) by path, write every
+/// one of `tests` into it as its own `#[test]` function (see
+/// [`as_integration_test`]), and run `cargo test` in it.
+///
+/// This is the [`TestBackend::Cargo`] backend. `offline` forwards
+/// `--offline` to the underlying `cargo test`, `color` forwards `--color`
+/// (see [`crate::color`]), and `runner` is how it gets spawned (see
+/// [`crate::command::ProcessRunner`]), the same as
+/// [`crate::cargo::build_extern_crate`].
+#[allow(clippy::too_many_arguments)]
+pub fn compile_and_run_with_cargo(
+    tests: &[Test],
+    package_dir: &Path,
+    crate_manifest_path: &Path,
+    crate_name: &str,
+    offline: bool,
+    color: ColorChoice,
+    runner: &dyn ProcessRunner,
+) -> Result<Vec<TestOutcome>> {
+    let crate_dir = crate_manifest_path
+        .parent()
+        .ok_or_else(|| format!("'{}' has no parent directory", crate_manifest_path.display()))?;
+
+    fs::create_dir_all(package_dir.join("src"))?;
+    fs::create_dir_all(package_dir.join("tests"))?;
+
+    fs::write(
+        package_dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"rustdoc-doctests\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n[dependencies]\n{} = {{ path = {:?} }}\n",
+            crate_name, crate_dir
+        ),
+    )?;
+    fs::write(package_dir.join("src").join("lib.rs"), "")?;
+
+    let body: String = tests.iter().map(|test| format!("{}\n\n", as_integration_test(test))).collect();
+    fs::write(package_dir.join("tests").join("doctests.rs"), body)?;
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("test")
+        .arg("--manifest-path")
+        .arg(package_dir.join("Cargo.toml"))
+        .arg("--color")
+        .arg(color.as_cargo_arg());
+    if offline {
+        command.arg("--offline");
+    }
+
+    let description = "cargo test (doctest cargo backend)".to_string();
+    let output = runner.run(&mut command).chain_err(|| ErrorKind::Cargo(description.clone()))?;
+    let outcomes = parse_cargo_test_output(&String::from_utf8_lossy(&output.stdout));
+
+    if !output.status.success() && outcomes.iter().all(|outcome| outcome.success) {
+        return Err(ErrorKind::Cargo(description).into());
+    }
+
+    Ok(outcomes)
+}
+
+/// Run every compiled doc test binary, returning an error naming the first
+/// one that failed.
+pub fn run_tests(binaries: &[PathBuf], runner: &dyn ProcessRunner) -> Result<()> {
+    for outcome in run_tests_with_outcomes(binaries, 0, runner) {
+        if !outcome.success {
+            return Err(ErrorKind::DocTest(outcome.binary).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// A single doc test binary's result: whether it passed, how long it took,
+/// and what it printed. Every binary already runs in its own process (each
+/// doc example is compiled to its own binary by [`compile_tests`]); this is
+/// what lets a caller (like `rustdoc test`'s per-example timing output) see
+/// each one's outcome individually instead of just the first failure, the
+/// way [`run_tests`] reports it.
+#[derive(Debug, Clone)]
+pub struct TestOutcome {
+    /// The binary's path (or, from [`compile_and_run_with_cargo`], the test
+    /// function's name), as used in [`ErrorKind::DocTest`] on a failure.
+    pub binary: String,
+    pub duration: Duration,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `binary` once, capturing its stdout/stderr and wall-clock duration
+/// instead of inheriting the parent process's, so a caller can attach a
+/// failing example's own output to the error it produces.
+///
+/// `runner` is how `binary` itself gets spawned (see
+/// [`crate::command::ProcessRunner`]), so this can be exercised in a test
+/// with a fake process outcome instead of actually running a binary.
+fn run_once(binary: &Path, runner: &dyn ProcessRunner) -> TestOutcome {
+    let started = Instant::now();
+    let result = runner.run(&mut Command::new(binary));
+    let duration = started.elapsed();
+
+    let (success, stdout, stderr) = match result {
+        Ok(output) => (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ),
+        Err(e) => (false, String::new(), format!("failed to run doc test '{}': {}", binary.display(), e)),
+    };
+
+    TestOutcome {
+        binary: binary.display().to_string(),
+        duration,
+        success,
+        stdout,
+        stderr,
+    }
+}
+
+/// Run every compiled doc test binary, each in its own process with
+/// captured stdout/stderr, retrying a failing one up to `retries` more
+/// times before settling on its final outcome (an example that only fails
+/// under contention with the others, e.g. one binding a fixed port,
+/// shouldn't fail a whole run over one bad attempt). Unlike [`run_tests`],
+/// this doesn't stop at the first failure; it returns every binary's
+/// outcome so a caller can report on all of them.
+pub fn run_tests_with_outcomes(binaries: &[PathBuf], retries: u32, runner: &dyn ProcessRunner) -> Vec<TestOutcome> {
+    binaries
+        .iter()
+        .map(|binary| {
+            let mut outcome = run_once(binary, runner);
+            for _ in 0..retries {
+                if outcome.success {
+                    break;
+                }
+                outcome = run_once(binary, runner);
+            }
+            outcome
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::SystemProcessRunner;
+
+    #[test]
+    fn sanitizes_test_names_for_the_filesystem() {
+        assert_eq!(sanitize_name("my_crate::foo"), "my_crate_foo");
+    }
+
+    #[test]
+    fn sanitizes_generics_and_hash_suffixes_to_underscores() {
+        assert_eq!(sanitize_name("my_crate::Thing<T>"), "my_crate_Thing_T_");
+        assert_eq!(sanitize_name("my_crate::foo[abcd1234]"), "my_crate_foo_abcd1234_");
+    }
+
+    #[test]
+    fn prefixes_a_leading_digit_so_the_result_is_a_valid_identifier() {
+        assert_eq!(sanitize_name("0::foo"), "_0_foo");
+    }
+
+    #[test]
+    fn unique_name_leaves_a_first_occurrence_untouched() {
+        let mut seen = HashSet::new();
+        assert_eq!(unique_name("my_crate::foo", &mut seen), "my_crate_foo");
+    }
+
+    #[test]
+    fn unique_name_disambiguates_a_sanitization_collision_with_a_hash_suffix() {
+        let mut seen = HashSet::new();
+        let first = unique_name("a::b_c", &mut seen);
+        let second = unique_name("a_b::c", &mut seen);
+
+        assert_eq!(first, "a_b_c");
+        assert_ne!(second, "a_b_c");
+        assert!(second.starts_with("a_b_c_"));
+    }
+
+    #[test]
+    fn unique_name_is_deterministic_for_the_same_input() {
+        let mut seen = HashSet::new();
+        unique_name("a::b_c", &mut seen);
+        let first_run = unique_name("a_b::c", &mut seen);
+
+        let mut seen = HashSet::new();
+        unique_name("a::b_c", &mut seen);
+        let second_run = unique_name("a_b::c", &mut seen);
+
+        assert_eq!(first_run, second_run);
+    }
+
+    #[test]
+    fn find_tests_disambiguates_examples_from_colliding_qualnames() {
+        let documentation = Documentation {
+            data: data_with_docs("crate", "", false),
+            included: vec![data_with_docs("a::b_c", "```\nassert!(true);\n```", false), data_with_docs("a_b::c", "```\nassert!(true);\n```", false)],
+            meta: std::collections::HashMap::new(),
+            links: None,
+        };
+
+        let doctest_config = DoctestConfig::default();
+        let tests = find_tests(&documentation, &doctest_config, false);
+
+        let names: HashSet<&str> = tests.iter().map(|test| test.name.as_str()).collect();
+        assert_eq!(names.len(), tests.len());
+    }
+
+    #[test]
+    fn wraps_try_operator_examples_in_a_result_returning_main() {
+        let body = "let x = \"1\".parse::<i32>()?;\nassert_eq!(x, 1);";
+        let wrapped = preprocess(body, &DoctestConfig::default());
+        assert!(wrapped.starts_with("fn main() -> Result<(), Box<dyn std::error::Error>> {"));
+        assert!(wrapped.contains("Ok(())"));
+    }
+
+    #[test]
+    fn leaves_plain_examples_with_a_unit_main() {
+        let wrapped = preprocess("assert_eq!(1 + 1, 2);", &DoctestConfig::default());
+        assert!(wrapped.starts_with("fn main() {"));
+        assert!(!wrapped.contains("Result"));
+    }
+
+    #[test]
+    fn prepends_crate_level_doctest_attrs() {
+        let doctest_config = DoctestConfig { attrs: vec!["deny(warnings)".to_string()], ..Default::default() };
+        let wrapped = preprocess("assert!(true);", &doctest_config);
+        assert!(wrapped.starts_with("#![deny(warnings)]\nfn main() {"));
+    }
+
+    #[test]
+    fn no_std_examples_never_get_the_std_error_result_wrapper() {
+        let doctest_config = DoctestConfig { no_std: true, ..Default::default() };
+        let wrapped = preprocess("let x = \"1\".parse::<i32>()?;", &doctest_config);
+        assert!(wrapped.starts_with("fn main() {"));
+        assert!(!wrapped.contains("std::error::Error"));
+    }
+
+    #[test]
+    fn no_std_examples_are_prefixed_with_the_configured_harness() {
+        let doctest_config = DoctestConfig {
+            no_std: true,
+            harness: Some("#[panic_handler]\nfn panic(_: &core::panic::PanicInfo) -> ! { loop {} }".to_string()),
+            ..Default::default()
+        };
+        let wrapped = preprocess("assert!(true);", &doctest_config);
+        assert!(wrapped.starts_with("#[panic_handler]\n"));
+    }
+
+    #[test]
+    fn harness_is_ignored_without_no_std() {
+        let doctest_config = DoctestConfig { harness: Some("#[panic_handler]".to_string()), ..Default::default() };
+        let wrapped = preprocess("assert!(true);", &doctest_config);
+        assert!(wrapped.starts_with("fn main() {"));
+    }
+
+    #[test]
+    fn a_question_mark_inside_a_string_or_comment_does_not_trigger_the_wrapper() {
+        assert!(!uses_try_operator(r#"let s = "is this ok?";"#));
+        assert!(!uses_try_operator("// what about this?\nlet x = 1;"));
+        assert!(!uses_try_operator("let c = '?';"));
+    }
+
+    #[test]
+    fn save_and_compile_tests_handle_a_non_ascii_output_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("tëst-crate-☃");
+
+        let tests = vec![Test {
+            name: "example_0".to_string(),
+            text: "fn main() { assert_eq!(1 + 1, 2); }".to_string(),
+        }];
+
+        let paths = save_tests(&tests, &output_dir).unwrap();
+        let binaries = compile_tests(&paths, &output_dir.join("bin"), &[], ColorChoice::Auto).unwrap();
+        run_tests(&binaries, &SystemProcessRunner).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn compiled_test_binaries_are_given_an_exe_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let tests = vec![Test {
+            name: "example_0".to_string(),
+            text: "fn main() { assert_eq!(1 + 1, 2); }".to_string(),
+        }];
+
+        let paths = save_tests(&tests, &dir.path().join("tests")).unwrap();
+        let binaries = compile_tests(&paths, &dir.path().join("bin"), &[], ColorChoice::Auto).unwrap();
+
+        assert_eq!(binaries[0].extension().and_then(|ext| ext.to_str()), Some("exe"));
+    }
+
+    #[test]
+    fn captures_stdout_and_records_a_duration() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let tests = vec![Test {
+            name: "example_0".to_string(),
+            text: "fn main() { println!(\"hi\"); }".to_string(),
+        }];
+
+        let paths = save_tests(&tests, &dir.path().join("tests")).unwrap();
+        let binaries = compile_tests(&paths, &dir.path().join("bin"), &[], ColorChoice::Auto).unwrap();
+
+        let outcomes = run_tests_with_outcomes(&binaries, 0, &SystemProcessRunner);
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].success);
+        assert_eq!(outcomes[0].stdout.trim(), "hi");
+    }
+
+    #[test]
+    fn retries_a_failing_test_before_giving_up() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let tests = vec![Test {
+            name: "example_0".to_string(),
+            text: "fn main() { std::process::exit(1); }".to_string(),
+        }];
+
+        let paths = save_tests(&tests, &dir.path().join("tests")).unwrap();
+        let binaries = compile_tests(&paths, &dir.path().join("bin"), &[], ColorChoice::Auto).unwrap();
+
+        let outcomes = run_tests_with_outcomes(&binaries, 2, &SystemProcessRunner);
+        assert_eq!(outcomes.len(), 1);
+        assert!(!outcomes[0].success);
+    }
+
+    #[test]
+    fn a_fake_runner_reports_a_binary_as_failing_without_actually_running_it() {
+        use crate::command::testing::FakeProcessRunner;
+
+        let runner = FakeProcessRunner::new(vec![(1, b"boom".to_vec())]);
+        let outcomes = run_tests_with_outcomes(&[PathBuf::from("not-a-real-binary")], 0, &runner);
+
+        assert_eq!(outcomes.len(), 1);
+        assert!(!outcomes[0].success);
+        assert_eq!(outcomes[0].stdout, "boom");
+    }
+
+    #[test]
+    fn parses_rustc_and_cargo_backend_names() {
+        assert_eq!(TestBackend::parse("rustc").unwrap(), TestBackend::Rustc);
+        assert_eq!(TestBackend::parse("cargo").unwrap(), TestBackend::Cargo);
+        assert!(TestBackend::parse("gcc").is_err());
+    }
+
+    #[test]
+    fn turns_a_wrapped_example_into_a_named_test_function() {
+        let test = Test { name: "my_crate_foo_0".to_string(), text: "fn main() {\nassert!(true);\n}\n".to_string() };
+        let converted = as_integration_test(&test);
+        assert!(converted.starts_with("#[test]\nfn my_crate_foo_0() {\n"));
+    }
+
+    #[test]
+    fn parses_passing_and_failing_lines_from_cargo_tests_own_output() {
+        let stdout = "running 2 tests\ntest my_crate_foo_0 ... ok\ntest my_crate_bar_0 ... FAILED\n\nfailures:\n";
+        let outcomes = parse_cargo_test_output(stdout);
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes[0].success);
+        assert_eq!(outcomes[0].binary, "my_crate_foo_0");
+        assert!(!outcomes[1].success);
+        assert_eq!(outcomes[1].binary, "my_crate_bar_0");
+    }
+
+    #[test]
+    fn compile_and_run_with_cargo_writes_a_package_and_parses_a_fake_cargo_test_run() {
+        use crate::command::testing::FakeProcessRunner;
+
+        let dir = tempfile::tempdir().unwrap();
+        let crate_manifest_path = dir.path().join("my-crate").join("Cargo.toml");
+        std::fs::create_dir_all(crate_manifest_path.parent().unwrap()).unwrap();
+
+        let tests = vec![Test {
+            name: "my_crate_foo_0".to_string(),
+            text: "fn main() {\nassert!(true);\n}\n".to_string(),
+        }];
+
+        let stdout = b"running 1 test\ntest my_crate_foo_0 ... ok\n".to_vec();
+        let runner = FakeProcessRunner::new(vec![(0, stdout)]);
+        let package_dir = dir.path().join("cargo-pkg");
+
+        let outcomes = compile_and_run_with_cargo(&tests, &package_dir, &crate_manifest_path, "my-crate", false, ColorChoice::Auto, &runner).unwrap();
+
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].success);
+        assert!(std::fs::read_to_string(package_dir.join("Cargo.toml")).unwrap().contains("my-crate"));
+        assert!(std::fs::read_to_string(package_dir.join("tests").join("doctests.rs"))
+            .unwrap()
+            .contains("fn my_crate_foo_0()"));
+    }
+
+    fn data_with_docs(id: &str, docs: &str, deprecated: bool) -> Data {
+        let mut attributes = std::collections::HashMap::new();
+        attributes.insert("docs".to_string(), serde_json::Value::String(docs.to_string()));
+        attributes.insert("deprecated".to_string(), serde_json::Value::Bool(deprecated));
+        Data {
+            id: id.to_string(),
+            ty: "function".to_string(),
+            attributes,
+            relationships: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn skip_deprecated_leaves_out_deprecated_items_examples() {
+        let docs = "```rust\nlet x = 1;\n```\n";
+        let documentation = Documentation {
+            data: data_with_docs("crate", "", false),
+            included: vec![
+                data_with_docs("live", docs, false),
+                data_with_docs("old", docs, true),
+            ],
+            meta: std::collections::HashMap::new(),
+            ..Default::default()
+        };
+
+        let tests = find_tests(&documentation, &DoctestConfig::default(), true);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "live_0");
+    }
+
+    #[test]
+    fn without_the_flag_deprecated_items_are_still_tested() {
+        let docs = "```rust\nlet x = 1;\n```\n";
+        let documentation = Documentation {
+            data: data_with_docs("crate", "", false),
+            included: vec![data_with_docs("old", docs, true)],
+            meta: std::collections::HashMap::new(),
+            ..Default::default()
+        };
+
+        let tests = find_tests(&documentation, &DoctestConfig::default(), false);
+        assert_eq!(tests.len(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+//! A small terminal UI abstraction used to report build progress.
+//!
+//! Keeping this behind a `Task`/`Ui` pair (rather than sprinkling
+//! `println!`/`indicatif` calls through `build()`) gives us one place to
+//! silence output for `--quiet`, and one place to hook in for consumers
+//! that want progress events instead of a terminal spinner.
+//!
+//! `indicatif`'s spinner is only compiled in with the (default) `cli`
+//! feature; without it, `Task` still tracks elapsed time (so `--timings`
+//! keeps working) but renders nothing, since a consumer embedding this
+//! crate without `cli` has no terminal to draw a spinner on anyway.
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "cli")]
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_derive::Serialize;
+
+use crate::color::ColorChoice;
+
+/// A single phase of work (e.g. "Generating analysis", "Building JSON"),
+/// shown to the user as a spinner while it runs.
+pub struct Task {
+    #[cfg(feature = "cli")]
+    bar: ProgressBar,
+    started: Instant,
+}
+
+impl Task {
+    #[cfg(feature = "cli")]
+    fn new(message: &str) -> Task {
+        let bar = ProgressBar::new_spinner();
+        if let Ok(style) = ProgressStyle::default_spinner().template("{spinner:.cyan} {msg}") {
+            bar.set_style(style);
+        }
+        bar.set_message(message.to_string());
+        bar.enable_steady_tick(Duration::from_millis(100));
+        Task {
+            bar,
+            started: Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "cli"))]
+    fn new(_message: &str) -> Task {
+        Task {
+            started: Instant::now(),
+        }
+    }
+
+    /// Mark the task as finished, replacing the spinner with its message,
+    /// and return how long the task ran for.
+    pub fn finish(self, #[cfg_attr(not(feature = "cli"), allow(unused_variables))] message: &str) -> Duration {
+        let elapsed = self.started.elapsed();
+        #[cfg(feature = "cli")]
+        self.bar.finish_with_message(message.to_string());
+        elapsed
+    }
+}
+
+/// Wall-clock durations of each named phase of a build, collected when
+/// `--timings` is passed.
+#[derive(Debug, Default, Serialize)]
+pub struct Timings {
+    phases: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        Timings::default()
+    }
+
+    /// Record how long `phase` took.
+    pub fn record(&mut self, phase: &str, duration: Duration) {
+        self.phases.push((phase.to_string(), duration));
+    }
+
+    /// A short human-readable summary, one phase per line.
+    pub fn summary(&self) -> String {
+        self.phases
+            .iter()
+            .map(|(phase, duration)| format!("{:>24}: {:.2?}", phase, duration))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// The user-facing progress reporter for a `build` (or `test`) invocation.
+pub struct Ui {
+    quiet: bool,
+}
+
+impl Ui {
+    /// `color` resolves whether the spinner (and anything else `console`
+    /// styles, e.g. `indicatif`'s own template colors) is drawn in color;
+    /// see [`ColorChoice::resolve_stderr`]. Without the `cli` feature this
+    /// is accepted but unused, since nothing outside the `rustdoc` binary
+    /// prints anything it would color.
+    pub fn new(quiet: bool, #[cfg_attr(not(feature = "cli"), allow(unused_variables))] color: ColorChoice) -> Ui {
+        #[cfg(feature = "cli")]
+        {
+            let enabled = color.resolve_stderr();
+            console::set_colors_enabled(enabled);
+            console::set_colors_enabled_stderr(enabled);
+        }
+
+        Ui { quiet }
+    }
+
+    /// Start a new task, unless the `Ui` is running quietly.
+    pub fn start_task(&self, message: &str) -> Option<Task> {
+        if self.quiet {
+            None
+        } else {
+            Some(Task::new(message))
+        }
+    }
+
+    /// Print a line of informational output, unless running quietly.
+    pub fn info(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_lists_one_phase_per_line() {
+        let mut timings = Timings::new();
+        timings.record("metadata", Duration::from_millis(5));
+        timings.record("analysis", Duration::from_secs(2));
+
+        let summary = timings.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("metadata"));
+        assert!(lines[1].contains("analysis"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Internal invariant checking for a generated
+//! [`crate::json::Documentation`]: that every relationship member's declared
+//! `ty` matches the `ty` of the `Data` it actually points at, that every
+//! member id actually resolves to something in `included` (or the crate
+//! itself), and that a plural relationship key (e.g. `"structs"`) only ever
+//! holds members of the matching type.
+//!
+//! Checked automatically in debug builds right after
+//! [`crate::json::create_documentation`] assembles a `Documentation`, so a
+//! generation bug fails loudly in development instead of reaching a
+//! frontend that trusts `ty` without cross-checking it. Also exposed here
+//! for `rustdoc check` to run against an already-written `data.json`.
+
+use std::collections::HashMap;
+
+use crate::json::{Data, Documentation};
+use crate::relationship_kinds::RELATIONSHIP_KINDS;
+
+/// One inconsistency found by [`check_consistency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// A relationship member's declared `ty` doesn't match the actual `ty`
+    /// of the item with that id.
+    MismatchedType { id: String, relationship: String, declared_ty: String, actual_ty: String },
+    /// A relationship member points at an id that isn't in `included` or
+    /// the crate itself.
+    UnknownId { id: String, relationship: String },
+    /// A plural relationship key doesn't match the type of one of its own
+    /// members, e.g. a `"structs"` relationship holding an `enum`.
+    MismatchedRelationshipKind { key: String, member_id: String, member_ty: String },
+}
+
+/// Check `documentation` for the invariants described in the module docs,
+/// returning every inconsistency found (empty if it's internally
+/// consistent).
+pub fn check_consistency(documentation: &Documentation) -> Vec<Inconsistency> {
+    let by_id: HashMap<&str, &Data> =
+        std::iter::once(&documentation.data).chain(documentation.included.iter()).map(|data| (data.id.as_str(), data)).collect();
+
+    let mut errors = Vec::new();
+
+    let items = std::iter::once(&documentation.data).chain(documentation.included.iter());
+    for data in items {
+        let relationships = match &data.relationships {
+            Some(relationships) => relationships,
+            None => continue,
+        };
+
+        for (key, members) in relationships.iter() {
+            let expected_ty = RELATIONSHIP_KINDS.iter().find(|kind| kind.plural == key).and_then(|kind| kind.singular);
+
+            for member in members.as_slice() {
+                match by_id.get(member.id.as_str()) {
+                    Some(actual) if actual.ty != member.ty => {
+                        errors.push(Inconsistency::MismatchedType {
+                            id: member.id.clone(),
+                            relationship: key.to_string(),
+                            declared_ty: member.ty.clone(),
+                            actual_ty: actual.ty.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => errors.push(Inconsistency::UnknownId { id: member.id.clone(), relationship: key.to_string() }),
+                }
+
+                if let Some(expected_ty) = expected_ty {
+                    if member.ty != expected_ty {
+                        errors.push(Inconsistency::MismatchedRelationshipKind {
+                            key: key.to_string(),
+                            member_id: member.id.clone(),
+                            member_ty: member.ty.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{Datum, Relationships};
+    use std::collections::HashMap as Map;
+
+    fn data(id: &str, ty: &str) -> Data {
+        Data { id: id.to_string(), ty: ty.to_string(), attributes: Map::new(), relationships: None, links: None }
+    }
+
+    #[test]
+    fn a_consistent_documentation_has_no_inconsistencies() {
+        let mut crate_data = data("crate", "crate");
+        let mut relationships = Relationships::default();
+        relationships.add_child("modules", Datum { id: "m1".to_string(), ty: "module".to_string() });
+        crate_data.relationships = Some(relationships);
+
+        let documentation = Documentation { data: crate_data, included: vec![data("m1", "module")], meta: Map::new(), links: None };
+
+        assert!(check_consistency(&documentation).is_empty());
+    }
+
+    #[test]
+    fn a_relationship_member_with_the_wrong_declared_type_is_flagged() {
+        let mut crate_data = data("crate", "crate");
+        let mut relationships = Relationships::default();
+        relationships.add_child("structs", Datum { id: "s1".to_string(), ty: "struct".to_string() });
+        crate_data.relationships = Some(relationships);
+
+        let documentation = Documentation { data: crate_data, included: vec![data("s1", "enum")], meta: Map::new(), links: None };
+
+        assert_eq!(
+            check_consistency(&documentation),
+            vec![Inconsistency::MismatchedType {
+                id: "s1".to_string(),
+                relationship: "structs".to_string(),
+                declared_ty: "struct".to_string(),
+                actual_ty: "enum".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn a_relationship_member_with_no_matching_item_is_flagged() {
+        let mut crate_data = data("crate", "crate");
+        let mut relationships = Relationships::default();
+        relationships.add_child("functions", Datum { id: "missing".to_string(), ty: "function".to_string() });
+        crate_data.relationships = Some(relationships);
+
+        let documentation = Documentation { data: crate_data, included: Vec::new(), meta: Map::new(), links: None };
+
+        assert_eq!(
+            check_consistency(&documentation),
+            vec![Inconsistency::UnknownId { id: "missing".to_string(), relationship: "functions".to_string() }]
+        );
+    }
+
+    #[test]
+    fn a_plural_key_holding_the_wrong_member_type_is_flagged() {
+        let mut crate_data = data("crate", "crate");
+        let mut relationships = Relationships::default();
+        relationships.add_child("modules", Datum { id: "e1".to_string(), ty: "enum".to_string() });
+        crate_data.relationships = Some(relationships);
+
+        let documentation = Documentation { data: crate_data, included: vec![data("e1", "enum")], meta: Map::new(), links: None };
+
+        assert_eq!(
+            check_consistency(&documentation),
+            vec![Inconsistency::MismatchedRelationshipKind {
+                key: "modules".to_string(),
+                member_id: "e1".to_string(),
+                member_ty: "enum".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn irregular_relationship_keys_are_not_checked_against_a_plural_type() {
+        let mut function_data = data("f1", "function");
+        let mut relationships = Relationships::default();
+        relationships.set_parent(Datum { id: "m1".to_string(), ty: "module".to_string() });
+        function_data.relationships = Some(relationships);
+
+        let documentation =
+            Documentation { data: data("crate", "crate"), included: vec![function_data, data("m1", "module")], meta: Map::new(), links: None };
+
+        assert!(check_consistency(&documentation).is_empty());
+    }
+}
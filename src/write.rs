@@ -0,0 +1,96 @@
+//! Small helpers for writing build output: skip rewriting files whose
+//! contents haven't changed, and clean up files left behind by a previous
+//! build into the same directory that the current build no longer produces.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use crate::error::*;
+
+/// Write `contents` to `path`, unless a file already there has identical
+/// contents, so a build that produces mostly-the-same output doesn't touch
+/// every file's mtime.
+pub fn write_if_changed(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Ok(existing) = fs::read(path) {
+        if existing == contents {
+            return Ok(());
+        }
+    }
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Remove every file directly inside `dir` whose name isn't in `keep`, so
+/// repeated builds into the same output directory never accumulate files
+/// left behind by items (or crates) that no longer produce them.
+///
+/// A missing `dir` is treated as having nothing to prune.
+pub fn prune_stale(dir: &Path, keep: &HashSet<OsString>) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && !keep.contains(&entry.file_name()) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_if_changed_leaves_identical_files_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+
+        write_if_changed(&path, b"one").unwrap();
+        let written_at = fs::metadata(&path).unwrap().modified().unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        write_if_changed(&path, b"one").unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), written_at);
+        assert_eq!(fs::read(&path).unwrap(), b"one");
+    }
+
+    #[test]
+    fn write_if_changed_rewrites_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+
+        write_if_changed(&path, b"one").unwrap();
+        write_if_changed(&path, b"two").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+    }
+
+    #[test]
+    fn prune_stale_removes_files_not_in_keep() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), "keep").unwrap();
+        fs::write(dir.path().join("stale.txt"), "stale").unwrap();
+
+        let mut keep = HashSet::new();
+        keep.insert(OsString::from("keep.txt"));
+        prune_stale(dir.path(), &keep).unwrap();
+
+        assert!(dir.path().join("keep.txt").exists());
+        assert!(!dir.path().join("stale.txt").exists());
+    }
+
+    #[test]
+    fn prune_stale_ignores_a_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        prune_stale(&dir.path().join("does-not-exist"), &HashSet::new()).unwrap();
+    }
+}
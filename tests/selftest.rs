@@ -0,0 +1,12 @@
+//! Runs the same pipeline as `rustdoc selftest`, directly against the
+//! library rather than the built binary.
+//!
+//! Ignored by default, like the fixtures in `tests/source.rs`: it shells
+//! out to `cargo check` with `-Z save-analysis`, which needs a nightly
+//! toolchain old enough to still support that flag.
+
+#[test]
+#[ignore = "requires a nightly rustc old enough to support `-Z save-analysis`"]
+fn selftest_passes() {
+    rustdoc::selftest::run().unwrap();
+}
@@ -0,0 +1,80 @@
+//! Snapshot-tests generated documentation for `example/` against a
+//! checked-in fixture, to catch an unintended format regression more
+//! completely than a `tests/source.rs` directive (which only asserts that
+//! individual fields exist, not the shape of everything around them).
+//!
+//! Ignored for the same reason as `tests/selftest.rs`: generating real
+//! documentation needs `-Z save-analysis`, which this toolchain doesn't
+//! support.
+//!
+//! There's no snapshot-testing library dependency here (`insta` et al.);
+//! this is a small local harness in the same spirit as `tests/source.rs`'s
+//! own directive parser. It builds documentation for `example/`, normalizes
+//! away anything not stable across machines or toolchains (absolute source
+//! paths, and every analysis-derived resource id — an id is only "stable
+//! within a single analysis session" per the comment in `rustdoc`'s
+//! `json::build_data`, not across separate builds), then diffs the rest against
+//! `tests/snapshots/example.json`. Set `BLESS_SNAPSHOTS=1` to write (or
+//! overwrite) the snapshot instead of failing on a mismatch — the same
+//! two-step "run once to accept, then diff on every run after" workflow
+//! `insta` itself uses.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+/// Strip everything from `documentation`'s JSON that isn't stable across
+/// separate builds: every `id` field (analysis-derived resource ids), and
+/// any occurrence of `example_dir` inside a string (an absolute path baked
+/// into a `span.file` attribute).
+fn normalize(value: &mut Value, example_dir: &str) {
+    match value {
+        Value::Object(map) => {
+            map.remove("id");
+            for nested in map.values_mut() {
+                normalize(nested, example_dir);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                normalize(item, example_dir);
+            }
+        }
+        Value::String(s) if s.contains(example_dir) => {
+            *s = s.replace(example_dir, "<example>");
+        }
+        _ => {}
+    }
+}
+
+fn snapshot_path() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join("example.json")
+}
+
+#[test]
+#[ignore = "requires a nightly rustc old enough to support `-Z save-analysis`"]
+fn example_crate_documentation_matches_its_snapshot() {
+    let example_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("example");
+    let config = rustdoc::Config::new(example_dir.join("Cargo.toml")).unwrap();
+    let mut documentation = rustdoc::build(&config).unwrap();
+
+    documentation.included.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut actual = serde_json::to_value(&documentation).unwrap();
+    normalize(&mut actual, &example_dir.to_string_lossy());
+    let actual = serde_json::to_string_pretty(&actual).unwrap();
+
+    let path = snapshot_path();
+
+    if std::env::var("BLESS_SNAPSHOTS").is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!("no snapshot at '{}'; run with `BLESS_SNAPSHOTS=1` to create one", path.display())
+    });
+
+    assert_eq!(actual, expected, "generated documentation no longer matches '{}' (run with `BLESS_SNAPSHOTS=1` if this change is intended)", path.display());
+}
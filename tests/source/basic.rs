@@ -0,0 +1,2 @@
+//@has "data.attributes.docs"
+//! A small widget crate used to exercise the source-test harness.
@@ -0,0 +1,2 @@
+//@count "included" 0
+//! Docs used to exercise the @count directive.
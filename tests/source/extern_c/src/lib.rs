@@ -0,0 +1,13 @@
+//@has "data.attributes.docs"
+//@count "included" 2
+
+//! A crate with an `extern "C"` block, used to exercise documentation of
+//! foreign functions and statics.
+
+extern "C" {
+    /// Adds two numbers, defined elsewhere.
+    pub fn foreign_add(a: i32, b: i32) -> i32;
+
+    /// A count maintained elsewhere.
+    pub static FOREIGN_COUNT: i32;
+}
@@ -0,0 +1,2 @@
+//@matches "data.attributes.docs" "exercise"
+//! Docs used to exercise the @matches directive.
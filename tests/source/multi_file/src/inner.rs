@@ -0,0 +1,2 @@
+/// A widget re-exported from the crate root.
+pub struct Widget;
@@ -0,0 +1,9 @@
+//@has "included"
+//@count "included" 2
+
+//! A small crate spanning multiple files, used to exercise the
+//! directory-based source-test harness.
+
+mod inner;
+
+pub use inner::Widget;
@@ -0,0 +1,10 @@
+//@has "data.attributes.docs"
+#![no_std]
+
+//! A `#![no_std]` crate, used to exercise the documentation pipeline
+//! against a crate with no implicit dependency on `std`.
+
+/// Doubles `x`.
+pub fn double(x: i32) -> i32 {
+    x * 2
+}
@@ -0,0 +1,8 @@
+//@has "data.attributes.docs"
+
+//! A crate whose library target lives somewhere other than `src/lib.rs`,
+//! used to exercise the directory-based source-test harness's cargo
+//! metadata-driven target resolution.
+
+/// A widget.
+pub struct Widget;
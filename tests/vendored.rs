@@ -0,0 +1,30 @@
+//! Checks that `cargo::metadata` resolves a crate using a directory-source
+//! replacement (the layout `cargo vendor` produces) fully offline, per
+//! synth-2454: a plain `cargo metadata` with no such fixture would just
+//! hit the network to resolve `leftpad-vendored`, which isn't a real crate.
+//!
+//! The fixture lives under `tests/vendored/`: `consumer/` is the crate being
+//! documented, whose `.cargo/config.toml` replaces `crates-io` with a
+//! `directory` source pointing at `vendor/`, which holds a hand-written
+//! stand-in for `leftpad-vendored` (with the `.cargo-checksum.json` a
+//! directory source requires).
+
+use std::path::Path;
+
+#[test]
+fn resolves_a_vendored_dependency_offline() {
+    let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vendored/consumer/Cargo.toml");
+
+    let metadata = rustdoc::cargo::metadata(&manifest_path, true, false)
+        .unwrap_or_else(|e| panic!("couldn't read cargo metadata for the vendored fixture: {}", e));
+
+    let package = metadata.root_package().expect("no root package in the vendored fixture");
+    assert_eq!(package.name, "vendored-consumer");
+
+    let dependency = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == "leftpad-vendored")
+        .expect("the vendored dependency wasn't resolved");
+    assert_eq!(dependency.version.to_string(), "0.1.0");
+}
@@ -0,0 +1,4 @@
+//! A fixture crate depending on a vendored, source-replaced dependency, used
+//! to check that `cargo::metadata` resolves fully offline.
+
+pub fn noop() {}